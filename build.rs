@@ -0,0 +1,23 @@
+use std::env;
+use std::path::Path;
+
+// embeds the manifest that declares the app DPI aware when linking for
+// Windows with MSVC, so Windows doesn't blur it by stretching the window;
+// other targets need nothing
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=packaging/windows/pendulum.exe.manifest");
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    if var("CARGO_CFG_TARGET_OS") != "windows" || var("CARGO_CFG_TARGET_ENV") != "msvc" {
+        return;
+    }
+    let manifest = Path::new(&var("CARGO_MANIFEST_DIR"))
+        .join("packaging")
+        .join("windows")
+        .join("pendulum.exe.manifest");
+    println!("cargo:rustc-link-arg-bins=/MANIFEST:EMBED");
+    println!(
+        "cargo:rustc-link-arg-bins=/MANIFESTINPUT:{}",
+        manifest.display()
+    );
+}
@@ -0,0 +1,30 @@
+#!/bin/sh
+# Builds Pendulum.app in target/release, with the font, the quiz, the examples
+# and the SDL libraries it links to inside, so it runs on Macs without Rust.
+set -e
+cd "$(dirname "$0")/../.."
+cargo build --release
+
+app=target/release/Pendulum.app
+rm -rf "$app"
+mkdir -p "$app/Contents/MacOS" "$app/Contents/Resources" "$app/Contents/Frameworks"
+cp packaging/macos/Info.plist "$app/Contents/"
+cp target/release/pendulum "$app/Contents/MacOS/"
+cp -R Roboto.ttf quiz.txt examples "$app/Contents/Resources/"
+
+# the SDL libraries, copied in and pointed to there by the binary and by each
+# other; other libraries they link to, like Homebrew's FreeType, are not
+frameworks="$app/Contents/Frameworks"
+binary="$app/Contents/MacOS/pendulum"
+for library in $(otool -L "$binary" | awk '/libSDL2/ { print $1 }'); do
+    cp "$library" "$frameworks/"
+done
+chmod u+w "$frameworks"/*
+for file in "$binary" "$frameworks"/*; do
+    for library in $(otool -L "$file" | awk 'NR > 1 && /libSDL2/ { print $1 }'); do
+        install_name_tool -change "$library" \
+            "@executable_path/../Frameworks/$(basename "$library")" "$file"
+    done
+done
+codesign --force --deep --sign - "$app"
+echo "Built $app"
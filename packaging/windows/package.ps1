@@ -0,0 +1,18 @@
+# Builds target\release\pendulum.zip: the executable, with its high-DPI
+# manifest embedded, next to the font, the quiz, the examples and the SDL
+# DLLs, so it runs on machines without Rust. The DLLs are looked for where
+# the build found the SDL libraries, given by $env:SDL2_DIR, else the
+# current folder.
+$ErrorActionPreference = "Stop"
+Set-Location (Join-Path $PSScriptRoot "..\..")
+cargo build --release
+
+$folder = "target\release\pendulum"
+Remove-Item -Recurse -Force $folder -ErrorAction SilentlyContinue
+New-Item -ItemType Directory $folder | Out-Null
+Copy-Item target\release\pendulum.exe, Roboto.ttf, quiz.txt $folder
+Copy-Item -Recurse examples $folder
+$sdl = if ($env:SDL2_DIR) { $env:SDL2_DIR } else { "." }
+Get-ChildItem -Recurse $sdl -Filter "SDL2*.dll" | Copy-Item -Destination $folder
+Compress-Archive -Force $folder "target\release\pendulum.zip"
+Write-Output "Built target\release\pendulum.zip"
@@ -0,0 +1,1824 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::time::Instant;
+use std::{cmp, fs, process};
+
+use chrono::Local;
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+use sdl2::Sdl;
+
+use crate::bridge::{Bridge, Delay};
+use crate::bundle;
+use crate::cli::Options;
+use crate::clock::PendulumClock;
+use crate::control::{Controller, Law};
+use crate::convention::Convention;
+use crate::decor::Decor;
+use crate::doppler::Doppler;
+use crate::events::SimEvent;
+use crate::gallery::Gallery;
+use crate::haptics::Haptics;
+use crate::input::{self, Action};
+use crate::integrator::STEPS;
+use crate::logbook::Logbook;
+use crate::mode::{Alternate, Mode};
+use crate::net::{Frame, Host, Viewer};
+use crate::panel::Panel;
+use crate::peaks::Peaks;
+use crate::phasor::Phasor;
+use crate::physics::{
+    self, Conserve, Params, World, DAMPINGS, DRIVES, DRIVE_FREQUENCIES, GRAVITIES, LENGTHS,
+};
+use crate::projection::Projection;
+use crate::quality::{Quality, TIERS};
+use crate::quiz::{Observation, Quiz, MAX_CHOICES};
+use crate::randomize::{Randomizer, Start};
+use crate::recorder::Recorder;
+#[cfg(feature = "serialport")]
+use crate::rig::Rig;
+use crate::rng::Rng;
+use crate::schedule::{Disturbance, Schedule};
+use crate::sensors::Sensors;
+use crate::simulation::Simulation;
+use crate::snapshot::Snapshot;
+use crate::stats::{Achievement, Stats};
+use crate::sync::SyncGroup;
+use crate::thermal::Thermal;
+use crate::wind::Wind;
+use crate::{draw_dialog, save_bmp, text, BORDER, CENTER, G, HEIGHT, L, THETA_0, WIDTH};
+
+// s, how far `.` moves a paused pendulum, or one `--dt` when that's longer
+const SINGLE_STEP: f64 = 1.0 / 60.0;
+// cm/s² W changes the wind by
+const WIND_STEP: f64 = 25.0;
+// px of the energy bar the energy it's measured from takes up, and the most it shows
+const ENERGY_BAR: f64 = 120.0;
+const ENERGY_BAR_MAX: f64 = 2.5 * ENERGY_BAR;
+// s of simulation a frame can fall behind by, past which time is dropped
+// rather than the pendulum racing to catch up after a stall
+const MAX_BACKLOG: f64 = 0.25;
+// s, time constant of the rod easing towards a new length, so it doesn't jump
+const EASING: f64 = 0.1;
+// s of simulation between the states a blown up pendulum can be rolled back to
+const CHECKPOINT: f64 = 1.0;
+// s of wall-clock time a frame may spend stepping, past which the rest of
+// its backlog is dropped rather than making the next frame later still
+const STEP_BUDGET: f64 = 0.02;
+// s, time constant of the measured simulation speed, and the fraction of
+// real time below which the HUD warns about it
+const SPEED_EASING: f64 = 1.0;
+const SLOW: f64 = 0.95;
+
+/// What the main window is doing. Each state handles its own input, falling
+/// back to the keys that work everywhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Running,
+    // the pendulum stands still, the overlays stay interactive
+    Paused,
+}
+
+/// What an event asks the application to do next.
+enum Transition {
+    Stay,
+    To(State),
+    Quit,
+}
+
+/// Everything about a session of the main window but the window itself.
+pub struct App<'a> {
+    state: State,
+    options: &'a Options,
+    sdl_context: Sdl,
+    haptics: Haptics,
+    quiz: Quiz,
+    quiz_on: bool,
+    // the examples, and whether they're listed to be opened
+    gallery: Gallery,
+    gallery_on: bool,
+    // disturbances from --schedule
+    schedule: Option<Schedule>,
+    hud_out: Option<Box<dyn Write>>,
+    hud_written: Instant,
+    // readings of simulated sensors, every step
+    sensors: Option<Sensors>,
+    // every frame, from --record
+    recorder: Option<Recorder>,
+    // the white-noise torque of --thermal-noise
+    thermal: Option<Thermal>,
+    // blowing on the single pendulum's bob, calm unless W or --wind
+    wind: Wind,
+    started: Instant,
+    theta_0: f64,
+    simulation: Simulation,
+    // the last state saved before it blew up, what Backspace rolls back to
+    checkpoint: Simulation,
+    // parameters the simulation's are easing towards
+    target: Params<f64>,
+    conserve: Conserve,
+    // once the parameters were changed, the HUD shows them
+    show_tuning: bool,
+    show_sensitivity: bool,
+    // kept across sessions in the data directory
+    stats: Stats,
+    show_stats: bool,
+    // per unit mass in cm²/s², the total energy the readout is measured
+    // from, when it was switched on or since the last reset
+    energy: Option<f64>,
+    show_bounds: bool,
+    show_precision: bool,
+    divergence_panel: Panel<'a>,
+    show_channels: bool,
+    channels_panel: Panel<'a>,
+    show_decor: bool,
+    decor: Decor,
+    // shadow of the bob on a line below, shown with H
+    projection: Option<Projection>,
+    // complex-plane view of the swing, shown with Z
+    phasor: Option<Phasor>,
+    // the bob's tone as heard from a draggable listener, played with A
+    doppler: Option<Doppler>,
+    // largest angle, speed and tension since the last reset or N, shown with M
+    peaks: Peaks,
+    show_peaks: bool,
+    // notable events since the start, shown with L
+    logbook: Logbook,
+    show_log: bool,
+    // the pendulum of the other modes, the single one waiting where it was meanwhile
+    alternate: Option<Alternate>,
+    // clock face moved by the pendulum's beats
+    clock: Option<PendulumClock>,
+    sync: Option<SyncGroup>,
+    host: Option<Host>,
+    viewer: Option<Viewer>,
+    // the external process pushing the single pendulum
+    bridge: Option<Bridge>,
+    // the real cart-pole shown in the cart-pole mode instead of a simulated one
+    #[cfg(feature = "serialport")]
+    rig: Option<Rig>,
+    // pixels the mouse moved, a screensaver ignores small jitters
+    mouse_travel: i32,
+    // asked for during input, carried out by the next update
+    reset: bool,
+    single_step: bool,
+    // s of wall-clock time not simulated yet, less than a step
+    accumulator: f64,
+    // simulated seconds per wall-clock one, eased, and the steps given up on
+    // to keep up
+    speed: f64,
+    dropped: u64,
+    // how much is drawn, less when frames take too long
+    quality: Quality,
+    // due at the end of the next frame, Some(true) for a clean one
+    screenshot: Option<bool>,
+}
+
+impl<'a> App<'a> {
+    // the files from the command line, each already checked
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        options: &'a Options,
+        quiz: Quiz,
+        schedule: Option<Schedule>,
+        randomizer: Option<Randomizer>,
+        hud_out: Option<Box<dyn Write>>,
+        sensors: Option<Sensors>,
+        sdl_context: &Sdl,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Self {
+        let start = options.start;
+        let params = Params {
+            length: start.map_or(L, |it| it.params.length),
+            gravity: start.map_or(G, |it| it.params.gravity),
+            damping: options.damping,
+            drag: options.drag,
+            wind: options.wind,
+            drive: options.drive,
+            drive_frequency: options.drive_frequency,
+            drive2: options.drive2,
+            drive_frequency2: options.drive_frequency2,
+            torque: 0.0,
+        };
+        let seed = options.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+        let mut rng = Rng::new(seed);
+        // a screensaver starts from a random swing, and sometimes with the clock
+        let theta_0 = if options.screensaver {
+            rng.range(0.4 * PI, 0.95 * PI) * if rng.below(2) == 0 { 1.0 } else { -1.0 }
+        } else {
+            start.map_or(THETA_0, |it| it.theta)
+        };
+        let mut drawn = Start {
+            params,
+            theta: theta_0,
+            w: start.map_or(0.0, |it| it.w),
+            second: [theta_0, 0.0],
+        };
+        let mut logbook = Logbook::new();
+        let mut stats = Stats::load();
+        if let Some(achievement) = stats.visit(options.mode) {
+            if stats.achieve(achievement) {
+                logbook.push(0.0, format!("achievement: {}", achievement.name()));
+            }
+        }
+        if let Some(randomizer) = &randomizer {
+            let values = randomizer.draw(&mut rng, &mut drawn);
+            logbook.push(0.0, format!("seed {}: {}", seed, values.join(", ")));
+        }
+        let Start {
+            params,
+            theta: theta_0,
+            w,
+            ..
+        } = drawn;
+        let mut simulation = Simulation::new(params, theta_0, options.integrator);
+        simulation.kick(w);
+        let thermal = (options.thermal_noise > 0.0).then(|| {
+            let seed = options
+                .noise_seed
+                .unwrap_or_else(|| Rng::from_time().next_u64());
+            logbook.push(0.0, format!("thermal noise seed {}", seed));
+            Thermal::new(options.thermal_noise, seed)
+        });
+        let clock = (options.screensaver && rng.below(2) == 0).then(|| {
+            PendulumClock::new(&simulation.params, simulation.theta(), simulation.w(), true)
+        });
+        let mut sync = options.sync_group.map(|group| {
+            SyncGroup::join(group).unwrap_or_else(|error| {
+                eprintln!("Unable to join sync group {}: {}", group, error);
+                process::exit(1);
+            })
+        });
+        let host = options.host.map(|address| {
+            Host::listen(address).unwrap_or_else(|error| {
+                eprintln!("Unable to listen on {}: {}", address, error);
+                process::exit(1);
+            })
+        });
+        // a new member restarts the whole group, so they all start together
+        if let Some(sync) = sync.as_mut() {
+            request_reset(sync);
+        }
+        let now = Instant::now();
+        App {
+            state: State::Running,
+            options,
+            sdl_context: sdl_context.clone(),
+            // controllers are opened as their ControllerDeviceAdded events arrive
+            haptics: Haptics::new(
+                sdl_context.game_controller().unwrap(),
+                options.rumble.clone(),
+            ),
+            quiz,
+            quiz_on: false,
+            gallery: Gallery::load(&options.fresh()),
+            gallery_on: false,
+            schedule,
+            // text copy of the HUD for screen readers
+            hud_out,
+            hud_written: now,
+            sensors,
+            recorder: options.record.as_deref().map(open_recorder),
+            thermal,
+            wind: Wind::new(
+                options.wind,
+                options.gust,
+                options.gusting,
+                options.gust_period,
+            ),
+            started: now,
+            theta_0,
+            checkpoint: simulation.clone(),
+            simulation,
+            target: params,
+            conserve: options.conserve,
+            show_tuning: false,
+            show_sensitivity: false,
+            stats,
+            show_stats: false,
+            energy: None,
+            show_bounds: false,
+            show_precision: false,
+            // plots refresh at 10 Hz, independently of the frame rate, less
+            // often with less detail or when frames take too long
+            divergence_panel: Panel::new(
+                texture_creator,
+                Rect::new(BORDER, HEIGHT as i32 - BORDER - 80, 200, 80),
+                TIERS[options.detail].plot_hz,
+            ),
+            show_channels: false,
+            // just above the divergence plot
+            channels_panel: Panel::new(
+                texture_creator,
+                Rect::new(BORDER, HEIGHT as i32 - BORDER - 170, 200, 80),
+                TIERS[options.detail].plot_hz,
+            ),
+            show_decor: options.decor.is_some(),
+            decor: options.decor.unwrap_or(Decor::ALL),
+            projection: None,
+            phasor: None,
+            doppler: None,
+            peaks: Peaks::default(),
+            show_peaks: false,
+            logbook,
+            show_log: false,
+            alternate: Alternate::new(options.mode, options, theta_0),
+            clock,
+            sync,
+            host,
+            viewer: options.view.clone().map(Viewer::connect),
+            bridge: options
+                .controller
+                .clone()
+                .map(|link| Bridge::new(link, Delay::new(options.latency, options.jitter, seed))),
+            #[cfg(feature = "serialport")]
+            rig: options.rig.as_deref().map(|path| {
+                let log = options.rig_log.as_deref().map(create);
+                Rig::open(
+                    path,
+                    options.rig_baud,
+                    options.encoder_resolution,
+                    options.track_resolution,
+                    log,
+                )
+                .unwrap_or_else(|error| {
+                    eprintln!("Unable to open the rig on {}: {}", path, error);
+                    process::exit(1);
+                })
+            }),
+            mouse_travel: 0,
+            reset: false,
+            single_step: false,
+            accumulator: 0.0,
+            speed: 1.0,
+            dropped: 0,
+            quality: Quality::new(options.detail),
+            screenshot: None,
+        }
+    }
+
+    /// Reacts to an event, returning false once the application should quit.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        if self.options.screensaver && is_input(event, &mut self.mouse_travel) {
+            return false;
+        }
+        if let Some(doppler) = self.doppler.as_mut() {
+            doppler.handle(event);
+        }
+        // the cart's arrows and the magnets' clicks, instead of what they do
+        // for the waiting pendulum
+        if self.alternate.as_mut().is_some_and(|it| it.handle(event)) {
+            return true;
+        }
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => self.haptics.connect(which),
+            Event::ControllerDeviceRemoved { which, .. } => self.haptics.disconnect(which),
+            // how macOS hands over a pendulum:// link opened while it runs
+            Event::DropFile { ref filename, .. }
+                if filename.starts_with("pendulum://") && self.viewer.is_none() =>
+            {
+                match Snapshot::parse(filename, &self.options.fresh()) {
+                    Ok(snapshot) => self.restore(snapshot, "opened"),
+                    Err(error) => self
+                        .logbook
+                        .push(self.simulation.time, format!("unable to open: {}", error)),
+                }
+                return true;
+            }
+            _ => {}
+        }
+        let Some(action) = input::action(event) else {
+            return true;
+        };
+        let transition = match self.state {
+            State::Running => self.handle_running(action),
+            State::Paused => self.handle_paused(action),
+        };
+        match transition {
+            Transition::Stay => true,
+            Transition::To(state) => {
+                self.state = state;
+                true
+            }
+            Transition::Quit => false,
+        }
+    }
+
+    fn handle_running(&mut self, action: Action) -> Transition {
+        match action {
+            // a viewer only mirrors the host, which decides when it moves
+            Action::Pause if self.viewer.is_none() => Transition::To(State::Paused),
+            _ => self.handle_common(action),
+        }
+    }
+
+    fn handle_paused(&mut self, action: Action) -> Transition {
+        match action {
+            Action::Pause => Transition::To(State::Running),
+            Action::Step => {
+                self.single_step = true;
+                Transition::Stay
+            }
+            _ => self.handle_common(action),
+        }
+    }
+
+    // actions that do the same in every state
+    fn handle_common(&mut self, action: Action) -> Transition {
+        match action {
+            Action::Quit => return Transition::Quit,
+            Action::Reset if self.viewer.is_none() => match self.sync.as_mut() {
+                Some(sync) => request_reset(sync),
+                None => self.reset = true,
+            },
+            Action::Sensitivity if self.viewer.is_none() => {
+                self.show_sensitivity = !self.show_sensitivity;
+            }
+            Action::Stats => self.show_stats = !self.show_stats,
+            Action::Energy => {
+                self.energy = match self.energy {
+                    Some(_) => None,
+                    None => Some(self.total_energy()),
+                }
+            }
+            Action::Gallery if self.viewer.is_none() => self.gallery_on = !self.gallery_on,
+            Action::Bounds if self.viewer.is_none() => {
+                self.show_bounds = !self.show_bounds;
+            }
+            Action::Precision if self.viewer.is_none() => {
+                self.show_precision = !self.show_precision;
+                self.divergence_panel.invalidate();
+            }
+            Action::Integrator if self.viewer.is_none() => {
+                self.simulation.scheme = self.simulation.scheme.next();
+                self.logbook.push(
+                    self.simulation.time,
+                    format!("integrator: {}", self.simulation.scheme.name()),
+                );
+            }
+            Action::Channels => {
+                self.show_channels = !self.show_channels;
+                self.channels_panel.invalidate();
+            }
+            Action::Clock => {
+                self.clock = match self.clock.take() {
+                    None => Some(PendulumClock::new(
+                        &self.simulation.params,
+                        self.simulation.theta(),
+                        self.simulation.w(),
+                        false,
+                    )),
+                    Some(mut clock) if !clock.disciplined => {
+                        clock.disciplined = true;
+                        Some(clock)
+                    }
+                    Some(_) => None,
+                };
+            }
+            Action::Decor => {
+                self.show_decor = !self.show_decor;
+            }
+            Action::Projection => {
+                self.projection = match self.projection {
+                    None => Some(Projection::new()),
+                    Some(_) => None,
+                };
+            }
+            Action::Phasor => {
+                self.phasor = match self.phasor {
+                    None => Some(Phasor::new()),
+                    Some(_) => None,
+                };
+            }
+            Action::Doppler => {
+                self.doppler = match self.doppler {
+                    None => self
+                        .sdl_context
+                        .audio()
+                        .and_then(|audio| {
+                            Doppler::open(
+                                &audio,
+                                (CENTER.0 + L as i16 + 50, CENTER.1),
+                                self.options.sound_speed * 100.0,
+                            )
+                        })
+                        .map_err(|error| eprintln!("Unable to play sound: {}", error))
+                        .ok(),
+                    Some(_) => None,
+                };
+            }
+            Action::Longer if self.viewer.is_none() => self.tune(10.0, 0.0),
+            Action::Shorter if self.viewer.is_none() => self.tune(-10.0, 0.0),
+            Action::Heavier if self.viewer.is_none() => self.tune(0.0, 50.0),
+            Action::Lighter if self.viewer.is_none() => self.tune(0.0, -50.0),
+            Action::World if self.viewer.is_none() => {
+                let world = World::after(self.target.gravity);
+                // set outright, zero-g being below what the keys go to
+                self.target.gravity = world.gravity();
+                self.show_tuning = true;
+                let message = format!("g: {:.2} m/s², {}", world.gravity() / 100.0, world.name());
+                self.logbook.push(self.simulation.time, message);
+                self.achieve((world != World::Earth).then_some(Achievement::OffWorld));
+            }
+            Action::StrongerDrive
+            | Action::WeakerDrive
+            | Action::MoreDamping
+            | Action::LessDamping
+            | Action::FasterDrive
+            | Action::SlowerDrive
+                if self.viewer.is_none()
+                    && matches!(&self.alternate, Some(Alternate::Cart(cart)) if cart.law == Some(Law::Pid)) =>
+            {
+                self.tune_pid(action)
+            }
+            Action::MoreDamping if self.viewer.is_none() => self.damp(0.05),
+            Action::LessDamping if self.viewer.is_none() => self.damp(-0.05),
+            Action::StrongerDrive if self.viewer.is_none() => self.drive(0.25, 0.0),
+            Action::WeakerDrive if self.viewer.is_none() => self.drive(-0.25, 0.0),
+            Action::FasterDrive if self.viewer.is_none() => self.drive(0.0, 0.02),
+            Action::SlowerDrive if self.viewer.is_none() => self.drive(0.0, -0.02),
+            Action::Conserve if self.viewer.is_none() => {
+                self.conserve = match self.conserve {
+                    Conserve::State => Conserve::Energy,
+                    Conserve::Energy => Conserve::AngularMomentum,
+                    Conserve::AngularMomentum => Conserve::State,
+                };
+                self.show_tuning = true;
+                self.logbook.push(
+                    self.simulation.time,
+                    format!("keeping {}", self.conserve.name()),
+                );
+            }
+            Action::Peaks => {
+                self.show_peaks = !self.show_peaks;
+            }
+            Action::ClearPeaks => self.peaks = Peaks::default(),
+            Action::Log => {
+                self.show_log = !self.show_log;
+            }
+            Action::ExportLog => {
+                let path = format!("pendulum-{}.txt", Local::now().format("%Y%m%d-%H%M%S"));
+                match self.logbook.save(&path) {
+                    Ok(()) => println!("Saved {}", path),
+                    Err(error) => eprintln!("Unable to write {}: {}", path, error),
+                }
+            }
+            Action::Mode if self.viewer.is_none() => {
+                let mode = self
+                    .alternate
+                    .as_ref()
+                    .map_or(Mode::Single, Alternate::mode);
+                self.alternate = Alternate::new(mode.next(), self.options, self.theta_0);
+                let toured = self.stats.visit(mode.next());
+                self.achieve(toured);
+                self.logbook.push(
+                    self.simulation.time,
+                    format!("mode: {}", mode.next().name()),
+                );
+            }
+            Action::Quiz => {
+                self.quiz_on = !self.quiz_on;
+            }
+            Action::Controller => {
+                if let Some(Alternate::Cart(cart)) = self.alternate.as_mut() {
+                    cart.next_law();
+                    let message = format!(
+                        "controller: {}",
+                        cart.law
+                            .map_or("off".to_string(), |it| it.name().to_uppercase())
+                    );
+                    self.logbook.push(cart.time, message);
+                }
+            }
+            Action::Rollback if self.viewer.is_none() && self.simulation.blowup.is_some() => {
+                // keeping the integrator, which may have been switched to one that copes
+                let scheme = self.simulation.scheme;
+                self.simulation = self.checkpoint.clone();
+                self.simulation.scheme = scheme;
+                self.target = self.simulation.params;
+                self.divergence_panel.invalidate();
+                self.channels_panel.invalidate();
+                self.logbook
+                    .push(self.simulation.time, "rolled back".to_string());
+                self.rebase_energy();
+            }
+            Action::Copy { link } => self.copy(link),
+            Action::Paste if self.viewer.is_none() => self.paste(),
+            Action::Kick { backward } if self.viewer.is_none() => {
+                let dw = if backward {
+                    -self.options.kick
+                } else {
+                    self.options.kick
+                };
+                let (time, kick) = match self.alternate.as_mut() {
+                    Some(alternate) => {
+                        alternate.kick(dw);
+                        (alternate.time(), format!("{:+.2} rad/s", dw))
+                    }
+                    // towards positive θ as the convention has it
+                    None => {
+                        let angles = self.options.angles;
+                        self.simulation.kick(angles.sign() * dw);
+                        (self.simulation.time, angles.show_rate(dw))
+                    }
+                };
+                self.logbook.push(time, format!("kick: {}", kick));
+            }
+            Action::Wind { leftward } if self.viewer.is_none() => {
+                self.wind
+                    .blow(if leftward { -WIND_STEP } else { WIND_STEP });
+                self.logbook.push(
+                    self.simulation.time,
+                    format!("wind: {:+.2} m/s²", self.wind.strength / 100.0),
+                );
+            }
+            Action::Screenshot { clean } => self.screenshot = Some(clean),
+            Action::Answer(choice) if self.gallery_on => {
+                if let Some(example) = self.gallery.examples.get(choice) {
+                    let how = format!("opened {}", example.title);
+                    let snapshot = example.snapshot;
+                    self.gallery_on = false;
+                    self.restore(snapshot, &how);
+                }
+            }
+            Action::Answer(choice) if self.quiz_on && choice < MAX_CHOICES => {
+                self.quiz.answer(choice);
+            }
+            _ => {}
+        }
+        Transition::Stay
+    }
+
+    // moves the target by `length` cm and `gravity` cm/s², within bounds
+    fn tune(&mut self, length: f64, gravity: f64) {
+        self.target.length = clamp(self.target.length + length, &LENGTHS);
+        self.target.gravity = clamp(self.target.gravity + gravity, &GRAVITIES);
+        self.show_tuning = true;
+    }
+
+    // carries out a scheduled disturbance. Parameters change at once rather
+    // than eased by wall-clock time, so that runs repeat exactly
+    fn disturb(&mut self, disturbance: Disturbance) {
+        let time = self.simulation.time;
+        let entry = match disturbance {
+            Disturbance::Kick(dw) => {
+                self.simulation.kick(dw);
+                format!("kick: {:+.2} rad/s", dw)
+            }
+            Disturbance::Length(length) => {
+                self.target.length = length;
+                self.simulation.retune(self.target, self.conserve);
+                format!("L: {:.0} cm", length)
+            }
+            Disturbance::Gravity(gravity) => {
+                self.target.gravity = gravity;
+                self.simulation.retune(self.target, self.conserve);
+                format!("g: {:.2} m/s²", gravity / 100.0)
+            }
+            Disturbance::Noise { strength, duration } => {
+                format!("noise: {:.2} rad/s² for {:.1} s", strength, duration)
+            }
+        };
+        self.logbook.push(time, entry);
+    }
+
+    // the drive's strength, the damping and the drive's frequency keys step
+    // the PID's Kp, Ki and Kd
+    fn tune_pid(&mut self, action: Action) {
+        let (gain, up) = match action {
+            Action::StrongerDrive => (0, true),
+            Action::WeakerDrive => (0, false),
+            Action::MoreDamping => (1, true),
+            Action::LessDamping => (1, false),
+            Action::FasterDrive => (2, true),
+            _ => (2, false),
+        };
+        let Some(Alternate::Cart(cart)) = self.alternate.as_mut() else {
+            return;
+        };
+        cart.tune(gain, up);
+        let [kp, ki, kd] = cart.pid.gains;
+        let message = format!("PID gains: Kp {:.0}, Ki {:.0}, Kd {:.0}", kp, ki, kd);
+        self.logbook.push(cart.time, message);
+    }
+
+    // the mode, parameters and state on the clipboard, as JSON or as a link
+    fn copy(&mut self, link: bool) {
+        let snapshot = Snapshot {
+            mode: self
+                .alternate
+                .as_ref()
+                .map_or(Mode::Single, Alternate::mode),
+            scheme: self.simulation.scheme,
+            params: Params {
+                wind: self.wind.strength,
+                ..self.target
+            },
+            gust: self.wind.gust,
+            gusting: self.wind.gusting,
+            gust_period: self.wind.period,
+            time: self.simulation.time,
+            theta: self.simulation.theta(),
+            w: self.simulation.w(),
+        };
+        let copied = self.sdl_context.video().and_then(|video| {
+            let text = if link {
+                snapshot.to_url()
+            } else {
+                snapshot.to_json()
+            };
+            video.clipboard().set_clipboard_text(&text)
+        });
+        let message = match copied {
+            Ok(()) if link => "copied a link".to_string(),
+            Ok(()) => "copied the state".to_string(),
+            Err(error) => format!("unable to copy: {}", error),
+        };
+        self.logbook.push(self.simulation.time, message);
+    }
+
+    // carries on from a snapshot on the clipboard, the other modes from their start
+    fn paste(&mut self) {
+        let pasted = self
+            .sdl_context
+            .video()
+            .and_then(|video| video.clipboard().clipboard_text())
+            .and_then(|text| Snapshot::parse(&text, &self.options.fresh()));
+        match pasted {
+            Ok(snapshot) => self.restore(snapshot, "pasted"),
+            Err(error) => {
+                let message = format!("unable to paste: {}", error);
+                self.logbook.push(self.simulation.time, message);
+            }
+        }
+    }
+
+    // carries on from `snapshot`, logged as `how` it came
+    fn restore(&mut self, snapshot: Snapshot, how: &str) {
+        self.target = snapshot.params;
+        self.simulation = Simulation::new(snapshot.params, snapshot.theta, snapshot.scheme);
+        self.simulation.kick(snapshot.w);
+        self.simulation.time = snapshot.time;
+        self.checkpoint = self.simulation.clone();
+        // the schedule picks up from the snapshot's time and the wind's
+        // random gusts start over, while the thermal noise carries on as it was
+        if let Some(schedule) = self.schedule.as_mut() {
+            schedule.seek(snapshot.time);
+        }
+        self.wind = Wind::new(
+            snapshot.params.wind,
+            snapshot.gust,
+            snapshot.gusting,
+            snapshot.gust_period,
+        );
+        self.alternate = Alternate::new(snapshot.mode, self.options, snapshot.theta);
+        self.peaks = Peaks::default();
+        self.rebase_energy();
+        self.divergence_panel.invalidate();
+        self.channels_panel.invalidate();
+        self.logbook.push(
+            self.simulation.time,
+            format!("{}: {}", how, snapshot.mode.name()),
+        );
+    }
+
+    // per unit mass in cm²/s², of the single pendulum
+    fn total_energy(&self) -> f64 {
+        let simulation = &self.simulation;
+        let (kinetic, potential) =
+            physics::energy(&simulation.params, simulation.theta(), simulation.w());
+        kinetic + potential
+    }
+
+    // the energy readout measured from now on, if it's shown
+    fn rebase_energy(&mut self) {
+        if self.energy.is_some() {
+            self.energy = Some(self.total_energy());
+        }
+    }
+
+    // drag needs no easing, it doesn't move the pendulum by itself
+    fn damp(&mut self, by: f64) {
+        self.target.damping = clamp(self.target.damping + by, &DAMPINGS);
+        self.simulation.params.damping = self.target.damping;
+        self.logbook.push(
+            self.simulation.time,
+            format!("damping: {:.2} /s", self.target.damping),
+        );
+    }
+
+    fn drive(&mut self, amplitude: f64, frequency: f64) {
+        // which way each is changed, 0.0.signum() being 1
+        let sign = |it: f64| if it == 0.0 { 0.0 } else { it.signum() };
+        let retuned = self
+            .alternate
+            .as_mut()
+            .and_then(|it| it.retune(sign(amplitude), sign(frequency)));
+        if let Some((time, message)) = retuned {
+            self.logbook.push(time, message);
+            return;
+        }
+        let target = &mut self.target;
+        target.drive = clamp(target.drive + amplitude, &DRIVES);
+        target.drive_frequency = clamp(target.drive_frequency + frequency, &DRIVE_FREQUENCIES);
+        self.simulation.params.drive = target.drive;
+        self.simulation.params.drive_frequency = target.drive_frequency;
+        self.logbook.push(
+            self.simulation.time,
+            format!(
+                "drive: {:.2} rad/s² at {:.2} rad/s",
+                target.drive, target.drive_frequency
+            ),
+        );
+    }
+
+    /// Advances everything by `dt` s of wall-clock time.
+    pub fn update(&mut self, dt: f64) {
+        self.quality.start_frame();
+        if self.sync.as_mut().is_some_and(|it| it.poll()) {
+            self.reset = true;
+        }
+        if self.reset {
+            self.reset = false;
+            self.accumulator = 0.0;
+            if let Some(schedule) = self.schedule.as_mut() {
+                schedule.rewind();
+            }
+            if let Some(thermal) = self.thermal.as_mut() {
+                thermal.rewind();
+            }
+            self.wind.rewind();
+            self.logbook.push(self.simulation.time, "reset".to_string());
+            self.peaks = Peaks::default();
+            self.simulation =
+                Simulation::new(self.simulation.params, self.theta_0, self.simulation.scheme);
+            self.checkpoint = self.simulation.clone();
+            self.rebase_energy();
+            if let Some(mode) = self.alternate.as_ref().map(Alternate::mode) {
+                self.alternate = Alternate::new(mode, self.options, self.theta_0);
+            }
+            self.divergence_panel.invalidate();
+            self.channels_panel.invalidate();
+            if let Some(projection) = self.projection.as_mut() {
+                projection.clear();
+            }
+            if let Some(phasor) = self.phasor.as_mut() {
+                phasor.reset();
+            }
+        }
+
+        // eased with wall-clock time, a paused pendulum can be retuned too
+        let params = self.simulation.params;
+        if self.viewer.is_none()
+            && (params.length != self.target.length || params.gravity != self.target.gravity)
+        {
+            let ease = |from: f64, to: f64, close: f64| {
+                let eased = from + (to - from) * (1.0 - (-dt / EASING).exp());
+                if (to - eased).abs() < close {
+                    to
+                } else {
+                    eased
+                }
+            };
+            let params = Params {
+                length: ease(params.length, self.target.length, 0.1),
+                gravity: ease(params.gravity, self.target.gravity, 0.5),
+                ..self.target
+            };
+            self.simulation.retune(params, self.conserve);
+            if params.length == self.target.length && params.gravity == self.target.gravity {
+                self.logbook.push(
+                    self.simulation.time,
+                    format!(
+                        "L: {:.0} cm, g: {:.2} m/s²",
+                        params.length,
+                        params.gravity / 100.0
+                    ),
+                );
+            }
+        }
+
+        let dt = match self.state {
+            State::Running => dt,
+            State::Paused if self.single_step => SINGLE_STEP.max(self.options.dt),
+            State::Paused => 0.0,
+        };
+        self.single_step = false;
+        // the quiz asks about the single pendulum
+        if self.quiz_on && dt > 0.0 && self.alternate.is_none() {
+            self.quiz.tick(
+                dt,
+                &Observation {
+                    theta: self.simulation.theta(),
+                    w: self.simulation.w(),
+                    length: self.simulation.params.length,
+                    angles: self.options.angles,
+                },
+            );
+        }
+        let happened = if let Some(viewer) = &self.viewer {
+            match viewer.latest() {
+                // the host's pace, not the viewer's
+                Some(frame) => {
+                    self.speed = frame.speed;
+                    self.dropped = frame.dropped;
+                    self.simulation.follow(&frame)
+                }
+                None => Vec::new(),
+            }
+        } else if dt > 0.0 && !(self.quiz_on && self.quiz.is_paused()) {
+            // the quiz freezes the pendulum while a question is on screen.
+            // Fixed steps keep the motion the same whatever the frame rate
+            let backlog = self.accumulator + dt;
+            self.accumulator = backlog.min(MAX_BACKLOG);
+            self.dropped += ((backlog - self.accumulator) / self.options.dt) as u64;
+            let stepping = Instant::now();
+            let mut simulated = 0.0;
+            let mut happened = Vec::new();
+            while self.accumulator >= self.options.dt {
+                if stepping.elapsed().as_secs_f64() > STEP_BUDGET {
+                    self.dropped += (self.accumulator / self.options.dt) as u64;
+                    self.accumulator %= self.options.dt;
+                    break;
+                }
+                self.accumulator -= self.options.dt;
+                simulated += self.options.dt;
+                // the schedule is for the single pendulum, which waits
+                if let Some(alternate) = self.alternate.as_mut() {
+                    // frozen where it was until reset
+                    if alternate.nonsense().is_some() {
+                        self.accumulator = 0.0;
+                        break;
+                    }
+                    #[cfg(feature = "serialport")]
+                    if let (Alternate::Cart(cart), Some(rig)) = (&mut *alternate, self.rig.as_mut())
+                    {
+                        if let Err(error) = cart.follow(rig, self.options.dt) {
+                            let message = format!("rig lost: {}", error);
+                            eprintln!("{}", message);
+                            self.logbook.push(cart.time, message);
+                            self.rig = None;
+                        }
+                        continue;
+                    }
+                    alternate.step(self.options.dt);
+                    if let Some((quantity, value)) = alternate.nonsense() {
+                        self.logbook.push(
+                            alternate.time(),
+                            format!("blew up: {} = {:.3e}", quantity, value),
+                        );
+                        break;
+                    }
+                    continue;
+                }
+                // frozen where it was until rolled back or reset
+                if self.simulation.blowup.is_some() {
+                    self.accumulator = 0.0;
+                    break;
+                }
+                let time = self.simulation.time;
+                let due = self.schedule.as_mut().map(|it| it.due(time));
+                for disturbance in due.unwrap_or_default() {
+                    self.disturb(disturbance);
+                }
+                if let Some(dw) = self
+                    .schedule
+                    .as_mut()
+                    .and_then(|it| it.noise(time, self.options.dt))
+                {
+                    self.simulation.kick(dw);
+                }
+                if let Some(thermal) = self.thermal.as_mut() {
+                    self.simulation
+                        .kick(thermal.kick(self.simulation.w(), self.options.dt));
+                }
+                self.simulation.params.wind = self.wind.step(time, self.options.dt);
+                if let Some(bridge) = self.bridge.as_mut() {
+                    let observed = [self.simulation.theta(), self.simulation.w()];
+                    self.simulation.params.torque = bridge.control(&observed, self.options.dt);
+                }
+                happened.extend(self.simulation.step(self.options.dt));
+                if let Some(blowup) = self.simulation.blowup {
+                    self.logbook.push(
+                        blowup.time,
+                        format!("blew up: {} = {:.3e}", blowup.quantity, blowup.value),
+                    );
+                    break;
+                }
+                if self.simulation.time - self.checkpoint.time >= CHECKPOINT {
+                    self.checkpoint = self.simulation.clone();
+                }
+                if let Some(sensors) = self.sensors.as_mut() {
+                    let simulation = &self.simulation;
+                    let recorded = sensors.record(
+                        &simulation.params,
+                        simulation.time,
+                        simulation.theta(),
+                        simulation.w(),
+                    );
+                    if let Err(error) = recorded {
+                        eprintln!("Unable to write the sensor readings: {}", error);
+                        self.sensors = None;
+                    }
+                }
+            }
+            if let Some(Err(error)) = self.sensors.as_mut().map(Sensors::flush) {
+                eprintln!("Unable to write the sensor readings: {}", error);
+                self.sensors = None;
+            }
+            // a pendulum frozen by a blowup isn't slow
+            if self.state == State::Running && !self.blown_up() {
+                let ease = 1.0 - (-dt / SPEED_EASING).exp();
+                self.speed += (simulated / dt - self.speed) * ease;
+            }
+            let devoted = self.stats.simulate(simulated);
+            self.achieve(devoted);
+            happened
+        } else {
+            Vec::new()
+        };
+        self.peaks.update(
+            &self.simulation.params,
+            self.simulation.theta(),
+            self.simulation.w(),
+        );
+        for event in happened {
+            self.haptics.play(event);
+            match event {
+                SimEvent::ZeroCrossing => {
+                    if let Some(clock) = self.clock.as_mut() {
+                        clock.tick();
+                    }
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.click();
+                    }
+                }
+                SimEvent::Flip => {
+                    self.logbook
+                        .push(self.simulation.time, "over the top".to_string());
+                    self.achieve(Some(Achievement::OverTheTop));
+                }
+                SimEvent::AmplitudeRecord => self.logbook.push(
+                    self.simulation.time,
+                    format!(
+                        "amplitude record: {}",
+                        self.options
+                            .angles
+                            .show_span(self.simulation.amplitude_record())
+                    ),
+                ),
+            }
+        }
+        if let Some(host) = &self.host {
+            host.broadcast(Frame {
+                speed: self.speed,
+                dropped: self.dropped,
+                ..self
+                    .simulation
+                    .frame(self.options.encoder_resolution, self.options.angles)
+            });
+        }
+        self.record();
+    }
+
+    // the records and achievements of the other modes, as they go
+    fn record(&mut self) {
+        let stats = &mut self.stats;
+        let achievement = match self.alternate.as_ref() {
+            Some(Alternate::Chain(chain)) if chain.chain.theta.len() >= 2 => {
+                stats.chaotic = stats.chaotic.max(chain.time);
+                (chain.time >= 60.0).then_some(Achievement::Butterfly)
+            }
+            Some(Alternate::Cart(cart)) => {
+                stats.balanced = stats.balanced.max(cart.balanced);
+                (cart.balanced >= 10.0).then_some(Achievement::SteadyHands)
+            }
+            Some(Alternate::Swing(swing)) => swing
+                .turn
+                .filter(|it| *it > FRAC_PI_2)
+                .map(|_| Achievement::Higher),
+            _ => None,
+        };
+        self.achieve(achievement);
+    }
+
+    // records `achievement`, announcing it in the log the first time
+    fn achieve(&mut self, achievement: Option<Achievement>) {
+        if let Some(achievement) = achievement.filter(|it| self.stats.achieve(*it)) {
+            self.logbook.push(
+                self.simulation.time,
+                format!("achievement: {}", achievement.name()),
+            );
+        }
+    }
+
+    /// Writes the usage statistics back to the data directory, on quitting.
+    pub fn save_stats(&self) {
+        if let Err(error) = self.stats.save() {
+            eprintln!("Unable to save the statistics: {}", error);
+        }
+    }
+
+    /// Draws the frame, `fps` going in the corner, and mirrors the HUD to
+    /// `--hud-out` when it is due.
+    pub fn draw(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: &Font,
+        small_font: &Font,
+        fps: f64,
+    ) {
+        // a clean frame has nothing but the pendulum and its scenery
+        let clean = self.screenshot == Some(true);
+        let mut lines = Vec::new();
+        if self.state == State::Paused {
+            lines.push("Paused, . steps".to_string());
+        }
+        if let Some(viewer) = &self.viewer {
+            lines.push(if viewer.is_connected() {
+                "Viewing the host".to_string()
+            } else {
+                "Waiting for the host…".to_string()
+            });
+        }
+        if let Some(bridge) = &self.bridge {
+            lines.push(if bridge.is_connected() {
+                format!(
+                    "controller: {:+.2} rad/s², {} replies missed",
+                    self.simulation.params.torque, bridge.missed
+                )
+            } else {
+                "Waiting for the controller…".to_string()
+            });
+        }
+        #[cfg(feature = "serialport")]
+        if let Some(path) = self.rig.as_ref().and(self.options.rig.as_ref()) {
+            lines.push(format!("rig on {}", path));
+        }
+        if self.quality.reduced() {
+            lines.push(format!("detail: {}", self.quality.tier().name));
+        }
+        // burnt into the footage, as well as given in frames.csv
+        if let Some(recorder) = &self.recorder {
+            lines.push(format!(
+                "recording frame {}, t = {:.3} s",
+                recorder.frame,
+                self.alternate
+                    .as_ref()
+                    .map_or(self.simulation.time, |it| it.time())
+            ));
+        }
+        if self.speed < SLOW {
+            lines.push(format!(
+                "running at {:.2}× real time, {} steps dropped",
+                self.speed, self.dropped
+            ));
+        }
+        // what the captions give, the values past the status lines above
+        let values = lines.len();
+        match self.alternate.as_mut() {
+            Some(alternate) => {
+                // what's left of it isn't worth drawing
+                if alternate.nonsense().is_none() {
+                    alternate.draw(canvas, self.quality.tier());
+                }
+                lines.extend(alternate.lines());
+            }
+            None => self.draw_single(canvas, clean, &mut lines),
+        }
+
+        if self.show_stats {
+            lines.extend(self.stats.lines());
+        }
+        if self.quiz_on {
+            lines.push(format!(
+                "Quiz: {}/{}",
+                self.quiz.correct, self.quiz.answered
+            ));
+        }
+        if let Some(clock) = &self.clock {
+            lines.push(format!(
+                "clock{}: {:+.1} s",
+                if clock.disciplined {
+                    " (disciplined)"
+                } else {
+                    ""
+                },
+                clock.error()
+            ));
+            if !clean {
+                clock.draw(canvas, (WIDTH as i16 - 70, 70), 55);
+            }
+        }
+
+        if self.show_log && !clean {
+            // between the divergence plot and the phasor
+            self.logbook.draw(
+                canvas,
+                texture_creator,
+                small_font,
+                Rect::new(220, HEIGHT as i32 - BORDER - 80, 250, 80),
+            );
+        }
+
+        if !self.options.screensaver && !clean {
+            let mut y = BORDER;
+            for line in &lines {
+                let texture = text(texture_creator, font, line);
+                let query = texture.query();
+                canvas
+                    .copy(
+                        &texture,
+                        None,
+                        Rect::new(BORDER, y, query.width, query.height),
+                    )
+                    .unwrap();
+                y += query.height as i32;
+            }
+
+            let fps = text(texture_creator, font, &format!("FPS: {:.2}", fps));
+            let fps_query = fps.query();
+            canvas
+                .copy(
+                    &fps,
+                    None,
+                    Rect::new(
+                        WIDTH as i32 - BORDER - fps_query.width as i32,
+                        HEIGHT as i32 - BORDER - fps_query.height as i32,
+                        fps_query.width,
+                        fps_query.height,
+                    ),
+                )
+                .unwrap();
+        }
+
+        // what blew up goes over the quiz, in the small font to fit
+        let blowup = self.blowup_overlay();
+        let dialog_font = if blowup.is_some() || self.gallery_on {
+            small_font
+        } else {
+            font
+        };
+        let overlay = blowup
+            .or_else(|| self.quiz.overlay().filter(|_| self.quiz_on))
+            .or_else(|| self.gallery_on.then(|| self.gallery.overlay()));
+        if let Some(overlay) = overlay.as_ref().filter(|_| !clean) {
+            draw_dialog(canvas, texture_creator, dialog_font, overlay);
+        }
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.caption(&lines[values..], overlay.as_deref());
+        }
+        if let Some(out) = self.hud_out.as_mut() {
+            if self.hud_written.elapsed().as_secs_f64() >= self.options.hud_interval {
+                self.hud_written = Instant::now();
+                let mut record = format!(
+                    "t: {:.1} s, {}",
+                    self.started.elapsed().as_secs_f64(),
+                    lines.join(", ")
+                );
+                if let Some(overlay) = &overlay {
+                    record = format!("{} | {}", record, overlay.join(" "));
+                }
+                if let Err(error) = writeln!(out, "{}", record).and_then(|_| out.flush()) {
+                    eprintln!("Unable to write the HUD: {}", error);
+                    self.hud_out = None;
+                }
+            }
+        }
+
+        if self.quality.end_frame() {
+            let tier = self.quality.tier();
+            self.divergence_panel.set_hz(tier.plot_hz);
+            self.channels_panel.set_hz(tier.plot_hz);
+            self.logbook
+                .push(self.simulation.time, format!("detail: {}", tier.name));
+        }
+
+        if self.screenshot.take().is_some() {
+            let path = format!("pendulum-{}.bmp", Local::now().format("%Y%m%d-%H%M%S"));
+            match canvas.read_pixels(None, PixelFormatEnum::RGB24) {
+                Ok(mut pixels) => save_bmp(&mut pixels, canvas.output_size().unwrap(), &path),
+                Err(error) => eprintln!("Unable to read the window: {}", error),
+            }
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            let tone = self.doppler.as_ref().map(|it| (it.heard, it.volume));
+            let (mode, time, swing) = match &self.alternate {
+                Some(alternate) => (alternate.mode(), alternate.time(), None),
+                None => (
+                    Mode::Single,
+                    self.simulation.time,
+                    Some((
+                        self.options.angles.angle(self.simulation.theta()),
+                        self.options.angles.rate(self.simulation.w()),
+                    )),
+                ),
+            };
+            if let Err(error) = recorder.record(canvas, time, mode.name(), swing, tone) {
+                eprintln!("Unable to record the frame, stopping: {}", error);
+                self.recorder = None;
+            }
+        }
+    }
+
+    // whether the pendulum shown is frozen by a blowup
+    fn blown_up(&self) -> bool {
+        match &self.alternate {
+            Some(alternate) => alternate.nonsense().is_some(),
+            None => self.simulation.blowup.is_some(),
+        }
+    }
+
+    // what blew up the pendulum shown, why and what to do about it
+    fn blowup_overlay(&self) -> Option<Vec<String>> {
+        // the other modes step by RK4 whatever the integrator, and can't be
+        // rolled back
+        if let Some(alternate) = &self.alternate {
+            let (quantity, value) = alternate.nonsense()?;
+            return Some(vec![
+                format!(
+                    "The {} mode blew up at t = {:.2} s: {} came out as {:.3e}",
+                    alternate.mode().name(),
+                    alternate.time(),
+                    quantity,
+                    value
+                ),
+                format!("Steps of {} s are too long for it", self.options.dt),
+                format!(
+                    "Try --dt {:.4}",
+                    (self.options.dt / 2.0).max(*STEPS.start())
+                ),
+                "R resets".to_string(),
+            ]);
+        }
+        let blowup = self.simulation.blowup?;
+        Some(vec![
+            format!(
+                "The pendulum blew up at t = {:.2} s: {} came out as {:.3e}",
+                blowup.time, blowup.quantity, blowup.value
+            ),
+            format!(
+                "Steps of {} s with {} are too long for the {} term",
+                self.options.dt,
+                self.simulation.scheme.name(),
+                blowup.term
+            ),
+            format!("Try --dt {:.4} or another integrator (Tab)", blowup.dt),
+            format!(
+                "Backspace rolls back to t = {:.2} s, R resets",
+                self.checkpoint.time
+            ),
+        ])
+    }
+
+    // the single pendulum and its overlays, adding their HUD lines to `lines`
+    fn draw_single(&mut self, canvas: &mut WindowCanvas, clean: bool, lines: &mut Vec<String>) {
+        let simulation = &self.simulation;
+        let theta = simulation.theta();
+        let w = simulation.w();
+        let length = simulation.params.length;
+        let angles = self.options.angles;
+
+        // calculating position of the pendulum
+        let x: i16 = CENTER.0 + (theta.sin() * length).round() as i16;
+        let y: i16 = CENTER.1 + (theta.cos() * length).round() as i16;
+
+        if self.show_decor {
+            self.decor.draw(canvas, (x, y));
+        }
+        self.wind.draw(canvas);
+
+        // drawing pendulum
+        canvas
+            .aa_line(CENTER.0, CENTER.1, x, y, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .aa_circle(x, y, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+        if self.show_peaks {
+            self.peaks.draw(canvas, length);
+        }
+        if let Some(start) = self.energy {
+            let (kinetic, potential) = physics::energy(&simulation.params, theta, w);
+            let total = kinetic + potential;
+            // J per kg from cm²/s²
+            let change = if start > 0.0 {
+                format!("{:+.2}%", (total / start - 1.0) * 100.0)
+            } else {
+                format!("{:+.3} J", (total - start) / 1e4)
+            };
+            lines.push(format!(
+                "KE: {:.3} J, PE: {:.3} J, E: {:.3} J per kg, {} since measured",
+                kinetic / 1e4,
+                potential / 1e4,
+                total / 1e4,
+                change
+            ));
+            if !clean {
+                draw_energy(canvas, kinetic, potential, start);
+            }
+        }
+
+        if let Some(projection) = self.projection.as_mut() {
+            projection.push(x);
+            projection.draw(canvas, (x, y));
+        }
+
+        // calculating speed vector of the pendulum
+        let vx: i16 = x + (theta.cos() * length * w / 10.0).round() as i16;
+        let vy: i16 = y - (theta.sin() * length * w / 10.0).round() as i16;
+        canvas
+            .aa_line(x, y, vx, vy, Color::RED)
+            .expect("Unable to draw line");
+
+        if let Some(doppler) = self.doppler.as_mut() {
+            doppler.update(
+                (x as f64, y as f64),
+                (theta.cos() * length * w, -theta.sin() * length * w),
+            );
+            if !clean {
+                doppler.draw(canvas);
+            }
+        }
+
+        // the angle, from where the convention measures it the shorter way round
+        let zero = angles.zero_degrees();
+        let turned =
+            ((90.0 - theta * 180.0 / PI - zero as f64 + 180.0).rem_euclid(360.0) - 180.0) as i16;
+        canvas
+            .filled_pie(
+                CENTER.0,
+                CENTER.1,
+                50,
+                cmp::min(zero, zero + turned),
+                cmp::max(zero, zero + turned),
+                Color::RGBA(0, 0, 255, 100),
+            )
+            .unwrap();
+
+        // drawing axis, down or up to the zero
+        let down = if angles.from_top { -100 } else { 100 };
+        canvas
+            .aa_line(
+                CENTER.0,
+                CENTER.1,
+                CENTER.0,
+                CENTER.1 + down,
+                Color::RGBA(0, 0, 255, 100),
+            )
+            .expect("Unable to draw line");
+        canvas
+            .aa_line(
+                CENTER.0,
+                CENTER.1,
+                CENTER.0 + 100,
+                CENTER.1,
+                Color::RGBA(0, 0, 255, 100),
+            )
+            .expect("Unable to draw line");
+
+        lines.extend([
+            format!("ω: {}", angles.show_rate(w)),
+            format!("θ: {}", angles.show_angle(theta)),
+            format!("v: {:.3} m/s", angles.rate(w) * length / 100.0),
+            format!("integrator: {}", simulation.scheme.name()),
+            match World::of(simulation.params.gravity) {
+                Some(world) => format!(
+                    "g: {:.2} m/s², {}",
+                    simulation.params.gravity / 100.0,
+                    world.name()
+                ),
+                None => format!("g: {:.2} m/s²", simulation.params.gravity / 100.0),
+            },
+        ]);
+        if angles != Convention::DEFAULT {
+            lines.push(format!("angles: {}", angles.name()));
+        }
+        if self.projection.is_some() {
+            lines.push(format!("x: {:.1} cm", theta.sin() * length));
+        }
+        if self.show_peaks {
+            lines.push(format!(
+                "peak θ: {}, v: {:.2} m/s, T: {:.2} mg",
+                angles.show_span(self.peaks.angle.value),
+                self.peaks.speed.value / 100.0,
+                self.peaks.tension.value
+            ));
+        }
+        if self.show_tuning {
+            lines.push(format!(
+                "L: {:.0} cm, g: {:.2} m/s², keeping {}",
+                length,
+                simulation.params.gravity / 100.0,
+                self.conserve.name()
+            ));
+        }
+        if simulation.params.damping > 0.0 {
+            lines.push(format!("damping: {:.2} /s", simulation.params.damping));
+        }
+        if self.wind.strength != 0.0 {
+            lines.push(self.wind.line(simulation.params.gravity));
+        }
+        if let Some(thermal) = &self.thermal {
+            lines.push(thermal.line(simulation.params.damping));
+        }
+        if simulation.params.drag > 0.0 {
+            // what each slows ω by now, and the speed they'd be even at
+            let params = &simulation.params;
+            let (viscous, quadratic) = (params.damping * w.abs(), params.drag * length * w * w);
+            lines.push(format!(
+                "air drag: {:.3} rad/s² against damping's {:.3}, {} dominating{}",
+                quadratic,
+                viscous,
+                if quadratic > viscous {
+                    "quadratic"
+                } else {
+                    "linear"
+                },
+                if params.damping > 0.0 {
+                    format!(
+                        ", the two even at {:.2} m/s",
+                        params.damping / params.drag / 100.0
+                    )
+                } else {
+                    String::new()
+                }
+            ));
+        }
+        if simulation.params.drive > 0.0 || simulation.params.drive2 > 0.0 {
+            let params = &simulation.params;
+            let mut line = format!(
+                "drive: {:.2} rad/s² at {:.2} rad/s",
+                params.drive, params.drive_frequency
+            );
+            if params.drive2 > 0.0 {
+                line.push_str(&format!(
+                    " + {:.2} rad/s² at {:.2} rad/s",
+                    params.drive2, params.drive_frequency2
+                ));
+            }
+            lines.push(line);
+        }
+        if simulation.params.damping > 0.0 || simulation.params.drive > 0.0 {
+            let (quality, ratio, strength) = physics::groups(&simulation.params);
+            let quality = if simulation.params.damping > 0.0 {
+                format!(
+                    "Q: {:.2}, {}",
+                    quality,
+                    if quality > 0.5 {
+                        "underdamped"
+                    } else {
+                        "overdamped"
+                    }
+                )
+            } else {
+                "Q: ∞".to_string()
+            };
+            lines.push(if simulation.params.drive > 0.0 {
+                format!("{}, Ω/ω0: {:.3}, A/ω0²: {:.3}", quality, ratio, strength)
+            } else {
+                quality
+            });
+        }
+        if let Some(doppler) = &self.doppler {
+            lines.push(format!("f: {:.1} Hz", doppler.heard));
+        }
+        if let Some(phasor) = self.phasor.as_mut() {
+            // turning the way of the convention's θ
+            let (theta, w) = (angles.sign() * theta, angles.rate(w));
+            let (re, im) = Phasor::z(&simulation.params, theta, w);
+            lines.push(format!(
+                "|z|: {:.3} rad, arg z: {:.0}°",
+                re.hypot(im),
+                im.atan2(re).to_degrees()
+            ));
+            if !clean {
+                phasor.draw(
+                    canvas,
+                    (WIDTH as i16 - 70, HEIGHT as i16 - 100),
+                    50,
+                    &simulation.params,
+                    theta,
+                    w,
+                );
+            }
+        }
+        if self.show_sensitivity {
+            // per cm -> per m, per cm/s2 -> per m/s2
+            let scale = angles.sign() * angles.scale() * 100.0;
+            lines.push(format!(
+                "∂θ/∂L: {:.3}{} per m",
+                simulation.sensitivity.dtheta_dlength() * scale,
+                angles.unit()
+            ));
+            lines.push(format!(
+                "∂θ/∂g: {:.4}{} per m/s²",
+                simulation.sensitivity.dtheta_dgravity() * scale,
+                angles.unit()
+            ));
+        }
+
+        if self.show_channels {
+            // per cm²/s -> per m²/s, both per kg of bob
+            lines.push(format!(
+                "ang. momentum: {:.3} m²/s per kg",
+                angles.sign() * physics::angular_momentum(&simulation.params, w) / 1e4
+            ));
+            lines.push(format!(
+                "torque: {:.3} N·m per kg",
+                angles.sign() * physics::torque(&simulation.params, simulation.time, theta, w)
+                    / 1e4
+            ));
+            if !clean {
+                let sign = angles.sign();
+                self.channels_panel.draw(canvas, |panel, rect| {
+                    simulation
+                        .momentum
+                        .draw_linear(panel, rect, Color::RED, sign);
+                    simulation
+                        .torque
+                        .draw_linear(panel, rect, Color::RGB(0, 150, 0), sign);
+                });
+            }
+        }
+
+        if self.show_bounds {
+            let bounds = &simulation.bounds.theta;
+            let (a, b) = (angles.unwrapped(bounds.lo), angles.unwrapped(bounds.hi));
+            lines.push(format!(
+                "θ bounds: [{:.6}, {:.6}]{}",
+                a.min(b) * angles.scale(),
+                a.max(b) * angles.scale(),
+                angles.unit()
+            ));
+            lines.push(format!(
+                "bound width: {:.2e}{}",
+                bounds.width() * angles.scale(),
+                angles.unit()
+            ));
+        }
+        if self.show_precision {
+            lines.push(format!(
+                "|θ64 - θ32|: {:.2e}{}",
+                simulation.precision.divergence() * angles.scale(),
+                angles.unit()
+            ));
+            if !clean {
+                self.divergence_panel.draw(canvas, |panel, rect| {
+                    simulation.divergence.draw_log(panel, rect, Color::RED)
+                });
+            }
+        }
+    }
+}
+
+/// Draws the kinetic energy stacked on the potential as a bar in the bottom
+/// right corner, red on blue, against a tick at the energy `start` of their
+/// total: a swing trading one for the other keeps the bar's height, damping
+/// lowers it and an integrator gaining energy raises it.
+fn draw_energy(canvas: &mut WindowCanvas, kinetic: f64, potential: f64, start: f64) {
+    let total = kinetic + potential;
+    let scale = if start > 0.0 { start } else { total };
+    if scale.is_nan() || scale <= 0.0 {
+        return;
+    }
+    let px = |energy: f64| (energy / scale * ENERGY_BAR).clamp(0.0, ENERGY_BAR_MAX) as i16;
+    let (left, right) = (
+        WIDTH as i16 - 3 * BORDER as i16,
+        WIDTH as i16 - BORDER as i16,
+    );
+    let bottom = HEIGHT as i16 - 3 * BORDER as i16;
+    let (low, high) = (bottom - px(potential), bottom - px(total));
+    canvas
+        .box_(left, bottom, right, low, Color::RGB(60, 110, 220))
+        .expect("Unable to draw box");
+    if high < low {
+        canvas
+            .box_(left, low, right, high, Color::RGB(220, 60, 60))
+            .expect("Unable to draw box");
+    }
+    let tick = bottom - px(start.max(0.0));
+    canvas
+        .hline(left - 4, right + 4, tick, Color::BLACK)
+        .expect("Unable to draw line");
+}
+
+fn clamp(value: f64, range: &RangeInclusive<f64>) -> f64 {
+    value.clamp(*range.start(), *range.end())
+}
+
+// whether a screensaver should quit on this event
+fn is_input(event: &Event, mouse_travel: &mut i32) -> bool {
+    match event {
+        Event::MouseMotion { xrel, yrel, .. } => {
+            *mouse_travel += xrel.abs() + yrel.abs();
+            *mouse_travel > 10
+        }
+        Event::KeyDown { .. }
+        | Event::MouseButtonDown { .. }
+        | Event::MouseWheel { .. }
+        | Event::ControllerButtonDown { .. }
+        | Event::FingerDown { .. } => true,
+        _ => false,
+    }
+}
+
+// a failed announcement only affects the other instances, so it is not fatal
+fn request_reset(sync: &mut SyncGroup) {
+    if let Err(error) = sync.request_reset() {
+        eprintln!("Unable to reach the sync group: {}", error);
+    }
+}
+
+/// Opens the `--hud-out` destination, exiting if the file can't be created.
+pub fn open_hud_out(options: &Options) -> Option<Box<dyn Write>> {
+    options.hud_out.as_deref().map(create)
+}
+
+/// Opens `--sensors-out`, if given, exiting if it can't be written.
+pub fn open_sensors_out(options: &Options) -> Option<Sensors> {
+    let path = options.sensors_out.as_deref()?;
+    Some(
+        Sensors::new(create(path), options.encoder_resolution, options.angles).unwrap_or_else(
+            |error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            },
+        ),
+    )
+}
+
+// `--record`'s folder, exiting if it can't be written
+fn open_recorder(dir: &str) -> Recorder {
+    Recorder::open(dir).unwrap_or_else(|error| {
+        eprintln!("{}: {}", dir, error);
+        process::exit(1);
+    })
+}
+
+// `path`, or stdout for `-`
+fn create(path: &str) -> Box<dyn Write> {
+    if path == "-" {
+        return Box::new(io::stdout());
+    }
+    match File::create(path) {
+        Ok(file) => Box::new(BufWriter::new(file)),
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Loads the `--randomize` constraints, if any, exiting if they are invalid.
+pub fn load_randomizer(options: &Options) -> Option<Randomizer> {
+    let path = options.randomize.as_ref()?;
+    let source = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+    Some(Randomizer::parse(&source, path).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    }))
+}
+
+/// Loads `--schedule`, if given, exiting if it is invalid.
+pub fn load_schedule(options: &Options) -> Option<Schedule> {
+    let path = options.schedule.as_ref()?;
+    let source = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+    Some(Schedule::parse(&source, path).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    }))
+}
+
+/// Loads the quiz from `--quiz`, or the built-in one, exiting if it is invalid.
+pub fn load_quiz(options: &Options) -> Quiz {
+    let (source, name) = match &options.quiz {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(source) => (source, path.as_str()),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            }
+        },
+        None => (bundle::quiz(), "quiz.txt"),
+    };
+    Quiz::parse(&source, name).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    })
+}
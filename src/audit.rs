@@ -0,0 +1,170 @@
+use std::ops::RangeInclusive;
+
+use crate::cli::AuditOptions;
+use crate::integrator::{Integrator, Scheme};
+use crate::physics::{self, Params, Pendulum};
+use crate::{G, L};
+
+/// Degrees off the bottom the audited swings are let go from, short of
+/// the top where it would never move.
+pub const AMPLITUDES: RangeInclusive<f64> = 1.0..=179.0;
+
+/// How far one run's total energy got from what it started with, in
+/// cm²/s² per unit mass, signed.
+struct Drift {
+    // the furthest, and s in when it was
+    worst: f64,
+    at: f64,
+    // at the end of the run
+    last: f64,
+    // s in when it stopped being a number, if it did
+    blowup: Option<f64>,
+}
+
+/// Runs the `audit` subcommand: the single pendulum undamped, undriven
+/// and without wind or drag, so its total energy ought to stay what it
+/// was at t = 0, stepped with each integrator at each `--dt` for
+/// `--duration`, and a table on stdout of how far the energy drifted
+/// from the start at worst and by the end, in J per kg and as a
+/// percentage. How the worst drift shrinks from one step to the next
+/// gives each scheme's order in dt, beside the order it's meant to have.
+pub fn run(options: &AuditOptions) {
+    let params = Params {
+        length: L,
+        gravity: G,
+        damping: 0.0,
+        drag: 0.0,
+        wind: 0.0,
+        drive: 0.0,
+        drive_frequency: 0.0,
+        drive2: 0.0,
+        drive_frequency2: 0.0,
+        torque: 0.0,
+    };
+    let theta = -options.amplitude.to_radians();
+    let (kinetic, potential) = physics::energy(&params, theta, 0.0);
+    let start = kinetic + potential;
+    // cm²/s² -> J/kg
+    println!(
+        "energy drift of the single pendulum let go at {}° over {} s, undamped and undriven, {:.4} J per kg at t = 0",
+        options.amplitude,
+        options.duration,
+        start / 1e4
+    );
+    println!();
+    println!(
+        "{:<14} {:>9}  {:>28}  {:>8}  {:>28}  {:>5}  {:>8}",
+        "integrator", "dt (s)", "worst drift", "at (s)", "at the end", "order", "expected"
+    );
+    let percent = |drift: f64| 100.0 * drift / start;
+    for scheme in &options.integrators {
+        // the worst drift and dt of the step before, for the order
+        let mut before: Option<(f64, f64)> = None;
+        for &dt in &options.dts {
+            let drift = audit(*scheme, &params, theta, start, dt, options.duration);
+            let name = scheme.name();
+            if let Some(blowup) = drift.blowup {
+                println!("{:<14} {:>9.6}  blew up at {:.2} s", name, dt, blowup);
+                before = None;
+                continue;
+            }
+            let order = before
+                .and_then(|(worst, step)| order((worst, step), (drift.worst, dt)))
+                .map_or("…".to_string(), |it| format!("{:.1}", it));
+            println!(
+                "{:<14} {:>9.6}  {:>+11.3e} J/kg {:>+10.3e}%  {:>8.2}  {:>+11.3e} J/kg {:>+10.3e}%  {:>5}  {:>8}",
+                name,
+                dt,
+                drift.worst / 1e4,
+                percent(drift.worst),
+                drift.at,
+                drift.last / 1e4,
+                percent(drift.last),
+                order,
+                scheme.order()
+            );
+            before = Some((drift.worst, dt));
+        }
+    }
+}
+
+// the order in dt of a drift going from `before` to `after`, each a worst
+// drift and its dt, if neither is exactly 0: worst ∝ dt^order
+fn order(before: (f64, f64), after: (f64, f64)) -> Option<f64> {
+    let ((worst, step), (then, dt)) = (before, after);
+    (worst != 0.0 && then != 0.0).then(|| (then.abs() / worst.abs()).ln() / (dt / step).ln())
+}
+
+// one run of `duration` s at `dt` from rest at `theta`, its energy
+// measured against `start` after every step
+fn audit(
+    scheme: Scheme,
+    params: &Params<f64>,
+    theta: f64,
+    start: f64,
+    dt: f64,
+    duration: f64,
+) -> Drift {
+    let mut pendulum = Pendulum { theta, w: 0.0 };
+    let mut drift = Drift {
+        worst: 0.0,
+        at: 0.0,
+        last: 0.0,
+        blowup: None,
+    };
+    let steps = (duration / dt).round() as usize;
+    for i in 0..steps {
+        let time = i as f64 * dt;
+        scheme.step(&mut pendulum, params, time, dt);
+        let (kinetic, potential) = physics::energy(params, pendulum.theta, pendulum.w);
+        let now = kinetic + potential - start;
+        if !now.is_finite() {
+            drift.blowup = Some(time + dt);
+            break;
+        }
+        if now.abs() > drift.worst.abs() {
+            drift.worst = now;
+            drift.at = time + dt;
+        }
+        drift.last = now;
+    }
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_each_scheme_its_order() {
+        let params = Params {
+            length: L,
+            gravity: G,
+            damping: 0.0,
+            drag: 0.0,
+            wind: 0.0,
+            drive: 0.0,
+            drive_frequency: 0.0,
+            drive2: 0.0,
+            drive_frequency2: 0.0,
+            torque: 0.0,
+        };
+        let theta = -30f64.to_radians();
+        let (kinetic, potential) = physics::energy(&params, theta, 0.0);
+        let start = kinetic + potential;
+        let fitted = |scheme: Scheme| {
+            let [coarse, fine] = [1.0 / 240.0, 1.0 / 480.0]
+                .map(|dt| (audit(scheme, &params, theta, start, dt, 10.0).worst, dt));
+            order(coarse, fine).unwrap()
+        };
+        for scheme in [Scheme::Euler, Scheme::Verlet] {
+            let order = fitted(scheme);
+            assert!(
+                (order - scheme.order() as f64).abs() < 0.2,
+                "{} came out of order {}",
+                scheme.name(),
+                order
+            );
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use crate::control::Controller;
+use crate::rng::Rng;
+
+/// s of simulated latency and jitter the link to the controller can have.
+pub const DELAYS: RangeInclusive<f64> = 0.0..=5.0;
+
+const VERSION: u8 = 1;
+// how long a step waits for its reply before holding the push it had
+const TIMEOUT: Duration = Duration::from_millis(100);
+const RECONNECT: Duration = Duration::from_secs(1);
+// a reply is the sequence number of what it answers and the push
+const REPLY: usize = 4 + 8;
+
+/// How the bridge reaches the external controller, at a host:port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Link {
+    Udp(String),
+    Tcp(String),
+}
+
+impl Link {
+    /// Reads `udp://host:port` or `tcp://host:port`.
+    pub fn parse(raw: &str) -> Option<Link> {
+        let (scheme, address) = raw.split_once("://")?;
+        if !address.contains(':') {
+            return None;
+        }
+        match scheme {
+            "udp" => Some(Link::Udp(address.to_string())),
+            "tcp" => Some(Link::Tcp(address.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// A value passed on after a delay of simulated time, `latency` s plus up to
+/// `jitter` s more at random, the way a reading or a command reaches the
+/// other end of a link to hardware. With jitter values can overtake one
+/// another, and one sent before the last to come out is dropped. Until the
+/// first comes out it gives the default, 0.
+pub struct Delay<T = f64> {
+    latency: f64,
+    jitter: f64,
+    rng: Rng,
+    // s each value comes out at, and the value, in the order they were sent
+    pending: VecDeque<(f64, T)>,
+    // what came out last, held until the next does
+    held: T,
+    // s since the delay started
+    time: f64,
+}
+
+impl<T: Copy + Default> Delay<T> {
+    pub fn new(latency: f64, jitter: f64, seed: u64) -> Self {
+        Delay {
+            latency,
+            jitter,
+            rng: Rng::new(seed),
+            pending: VecDeque::new(),
+            held: T::default(),
+            time: 0.0,
+        }
+    }
+
+    /// Sends `value` on, to come out once its delay has passed.
+    pub fn send(&mut self, value: T) {
+        let at = self.time + self.latency + self.jitter * self.rng.next_f64();
+        self.pending.push_back((at, value));
+    }
+
+    /// What has come out by now, to hold over the next `dt` s.
+    pub fn step(&mut self, dt: f64) -> T {
+        if let Some(i) = self.pending.iter().rposition(|it| it.0 <= self.time) {
+            self.held = self.pending[i].1;
+            self.pending.drain(..=i);
+        }
+        self.time += dt;
+        self.held
+    }
+
+    /// Forgets everything still on its way, and comes out with the default
+    /// until the next value.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.held = T::default();
+    }
+}
+
+/// A controller in another process, Python or MATLAB, say, reached over UDP
+/// or TCP: every step the bridge sends it what is observed and waits for the
+/// push to reply with, which then acts after a simulated `Delay`.
+///
+/// An observation is a version byte, the count N of values observed as a
+/// byte, a little-endian u32 numbering it, then s since the bridge started,
+/// the step dt in s, and the N values, each a little-endian f64. The reply
+/// is the u32 of the observation it answers followed by the push as an f64,
+/// 12 bytes. A reply that doesn't come within `TIMEOUT` leaves the push as
+/// it was; without a link the push is 0, and the bridge tries to connect
+/// again every `RECONNECT`.
+pub struct Bridge {
+    link: Link,
+    connection: Option<Connection>,
+    // when connecting last failed
+    failed: Option<Instant>,
+    sequence: u32,
+    // s since the bridge started
+    time: f64,
+    delay: Delay,
+    // steps the push was held for want of a reply
+    pub missed: u64,
+}
+
+impl Bridge {
+    pub fn new(link: Link, delay: Delay) -> Self {
+        Bridge {
+            link,
+            connection: None,
+            failed: None,
+            sequence: 0,
+            time: 0.0,
+            delay,
+            missed: 0,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    // the push replied to `observed`, if it came in time
+    fn ask(&mut self, observed: &[f64], dt: f64) -> io::Result<Option<f64>> {
+        if self.connection.is_none() {
+            if self.failed.is_some_and(|it| it.elapsed() < RECONNECT) {
+                return Ok(None);
+            }
+            match Connection::open(&self.link) {
+                Ok(connection) => self.connection = Some(connection),
+                Err(error) => {
+                    self.failed = Some(Instant::now());
+                    return Err(error);
+                }
+            }
+        }
+        let connection = self.connection.as_mut().unwrap();
+        self.sequence = self.sequence.wrapping_add(1);
+        let mut bytes = vec![VERSION, observed.len() as u8];
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        for value in [self.time, dt].iter().chain(observed) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        connection.send(&bytes)?;
+        connection.receive(self.sequence, Instant::now() + TIMEOUT)
+    }
+}
+
+impl<const N: usize> Controller<N> for Bridge {
+    fn control(&mut self, observed: &[f64; N], dt: f64) -> f64 {
+        match self.ask(observed, dt) {
+            Ok(Some(push)) if push.is_finite() => self.delay.send(push),
+            Ok(_) => self.missed += 1,
+            Err(_) => {
+                self.connection = None;
+                self.delay.clear();
+                self.missed += 1;
+            }
+        }
+        self.time += dt;
+        self.delay.step(dt)
+    }
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    // and the bytes of a reply only partly read
+    Tcp(TcpStream, Vec<u8>),
+}
+
+impl Connection {
+    fn open(link: &Link) -> io::Result<Connection> {
+        let (Link::Udp(address) | Link::Tcp(address)) = link;
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no address to connect to"))?;
+        match link {
+            Link::Udp(_) => {
+                let local: SocketAddr = match address {
+                    SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                    SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(address)?;
+                Ok(Connection::Udp(socket))
+            }
+            Link::Tcp(_) => {
+                let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+                stream.set_nodelay(true)?;
+                Ok(Connection::Tcp(stream, Vec::new()))
+            }
+        }
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Connection::Udp(socket) => socket.send(bytes).map(|_| ()),
+            Connection::Tcp(stream, _) => stream.write_all(bytes),
+        }
+    }
+
+    // the push replied to observation `sequence`, skipping replies to ones
+    // that timed out before, or none if it hasn't come by `deadline`
+    fn receive(&mut self, sequence: u32, deadline: Instant) -> io::Result<Option<f64>> {
+        let mut buffer = [0; 64];
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(None);
+            }
+            let read = match self {
+                Connection::Udp(socket) => {
+                    socket.set_read_timeout(Some(left))?;
+                    socket.recv(&mut buffer)
+                }
+                Connection::Tcp(stream, _) => {
+                    stream.set_read_timeout(Some(left))?;
+                    stream.read(&mut buffer)
+                }
+            };
+            let size = match read {
+                Ok(0) if matches!(self, Connection::Tcp(..)) => {
+                    return Err(ErrorKind::UnexpectedEof.into())
+                }
+                Ok(size) => size,
+                Err(error)
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    return Ok(None)
+                }
+                Err(error) => return Err(error),
+            };
+            let replies = match self {
+                // a datagram is a reply whole, or isn't one
+                Connection::Udp(_) if size == REPLY => buffer[..REPLY].to_vec(),
+                Connection::Udp(_) => continue,
+                Connection::Tcp(_, partial) => {
+                    partial.extend_from_slice(&buffer[..size]);
+                    let whole = partial.len() / REPLY * REPLY;
+                    partial.drain(..whole).collect()
+                }
+            };
+            for reply in replies.chunks(REPLY) {
+                let number = u32::from_le_bytes(reply[..4].try_into().unwrap());
+                if number == sequence {
+                    return Ok(Some(f64::from_le_bytes(reply[4..].try_into().unwrap())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn holds_each_value_until_the_next_comes_out() {
+        let mut delay = Delay::new(0.02, 0.0, 1);
+        let out: Vec<f64> = (1..=5)
+            .map(|i| {
+                delay.send(i as f64);
+                delay.step(0.01)
+            })
+            .collect();
+        assert_eq!(out, [0.0, 0.0, 1.0, 2.0, 3.0]);
+        delay.clear();
+        assert_eq!(delay.step(0.01), 0.0);
+    }
+
+    #[test]
+    fn drops_a_value_overtaken_by_a_newer_one() {
+        let mut delay = Delay::new(0.0, 1.0, 7);
+        for i in 0..100 {
+            delay.send(i as f64);
+            delay.step(0.01);
+        }
+        // whatever the jitter, what comes out never goes back
+        let mut last = 0.0;
+        for _ in 0..200 {
+            let out = delay.step(0.01);
+            assert!(out >= last);
+            last = out;
+        }
+        assert_eq!(last, 99.0);
+    }
+
+    #[test]
+    fn reads_links_by_their_scheme() {
+        assert_eq!(
+            Link::parse("udp://localhost:9000"),
+            Some(Link::Udp("localhost:9000".to_string()))
+        );
+        assert_eq!(
+            Link::parse("tcp://127.0.0.1:9000"),
+            Some(Link::Tcp("127.0.0.1:9000".to_string()))
+        );
+        for raw in ["127.0.0.1:9000", "http://localhost:80", "tcp://localhost"] {
+            assert_eq!(Link::parse(raw), None, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn asks_a_controller_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // pushes back against θ, twice as hard
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut bytes = [0; 6 + 4 * 8];
+            while stream.read_exact(&mut bytes).is_ok() {
+                assert_eq!(&bytes[..2], &[VERSION, 2]);
+                let theta = f64::from_le_bytes(bytes[22..30].try_into().unwrap());
+                let mut reply = bytes[2..6].to_vec();
+                reply.extend_from_slice(&(-2.0 * theta).to_le_bytes());
+                stream.write_all(&reply).unwrap();
+            }
+        });
+        let mut bridge = Bridge::new(Link::Tcp(address.to_string()), Delay::new(0.0, 0.0, 1));
+        for theta in [0.5, -0.25, 1.0] {
+            assert_eq!(bridge.control(&[theta, 0.0], 0.01), -2.0 * theta);
+        }
+        assert_eq!(bridge.missed, 0);
+    }
+}
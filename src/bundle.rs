@@ -0,0 +1,141 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use sdl2::hint;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use sdl2::video::Window;
+
+// px, each side of the window icon
+const ICON: u32 = 32;
+// the examples built in, for when there is no packaged folder of them
+const EXAMPLES: [(&str, &str); 6] = [
+    ("1-chaos.txt", include_str!("../examples/1-chaos.txt")),
+    ("2-double.txt", include_str!("../examples/2-double.txt")),
+    (
+        "3-resonance.txt",
+        include_str!("../examples/3-resonance.txt"),
+    ),
+    ("4-swing-up.txt", include_str!("../examples/4-swing-up.txt")),
+    ("5-kapitza.txt", include_str!("../examples/5-kapitza.txt")),
+    ("6-string.txt", include_str!("../examples/6-string.txt")),
+];
+
+// where a packaged app keeps its files: Contents/Resources beside
+// Contents/MacOS in a macOS bundle, else the executable's own folder, as
+// in a Windows zip
+fn resources() -> Vec<PathBuf> {
+    let Some(folder) = env::current_exe()
+        .ok()
+        .and_then(|it| it.parent().map(PathBuf::from))
+    else {
+        return Vec::new();
+    };
+    vec![folder.join("../Resources"), folder]
+}
+
+// `name` from the resources, if it is there
+fn read(name: &str) -> Option<Vec<u8>> {
+    resources()
+        .into_iter()
+        .find_map(|folder| fs::read(folder.join(name)).ok())
+}
+
+/// The font, the packaged one if there is one, else the one built in. It
+/// lives as long as the program, like the fonts loaded from it.
+pub fn font() -> &'static [u8] {
+    match read("Roboto.ttf") {
+        Some(bytes) => Box::leak(bytes.into_boxed_slice()),
+        None => include_bytes!("../Roboto.ttf"),
+    }
+}
+
+/// The built-in quiz, which a packaged quiz.txt can replace.
+pub fn quiz() -> String {
+    read("quiz.txt")
+        .and_then(|it| String::from_utf8(it).ok())
+        .unwrap_or_else(|| include_str!("../quiz.txt").to_string())
+}
+
+/// The example scenes as their file names and contents, sorted by name:
+/// the `.txt` files of a packaged `examples` folder if there is one, else
+/// the built-in ones.
+pub fn examples() -> Vec<(String, String)> {
+    let packaged = resources().into_iter().find_map(|folder| {
+        let mut examples: Vec<(String, String)> = fs::read_dir(folder.join("examples"))
+            .ok()?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "txt" {
+                    return None;
+                }
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                Some((name, fs::read_to_string(&path).ok()?))
+            })
+            .collect();
+        examples.sort();
+        Some(examples)
+    });
+    packaged.unwrap_or_else(|| {
+        EXAMPLES
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect()
+    })
+}
+
+/// The user's own folder for the app's files, as SDL picks it: under
+/// AppData on Windows, Application Support on macOS and ~/.local/share
+/// elsewhere, made if it isn't there yet.
+pub fn data_dir() -> Option<PathBuf> {
+    sdl2::filesystem::pref_path("danielabeledo", "pendulum")
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// Names the app for the desktop, before SDL starts.
+pub fn hint() {
+    hint::set("SDL_APP_NAME", "Pendulum");
+    hint::set("SDL_VIDEO_X11_WMCLASS", "pendulum");
+}
+
+/// Makes windows on Windows as big as the screen's scaling says, drawn at
+/// its full resolution, before SDL starts. Only for windows drawn at a
+/// logical size, which the renderer then scales up.
+pub fn high_dpi() {
+    hint::set("SDL_WINDOWS_DPI_AWARENESS", "permonitorv2");
+    hint::set("SDL_WINDOWS_DPI_SCALING", "1");
+}
+
+/// Gives `window` the app's icon, a red bob hanging a little to the side.
+pub fn set_icon(window: &mut Window) {
+    let Ok(mut icon) = Surface::new(ICON, ICON, PixelFormatEnum::RGBA32) else {
+        return;
+    };
+    let size = ICON as f64;
+    let (pivot, bob, radius) = ((size / 2.0, 2.0), (size * 0.7, size * 0.7), size * 0.2);
+    let pitch = icon.pitch() as usize;
+    icon.with_lock_mut(|pixels| {
+        for row in 0..ICON as usize {
+            for column in 0..ICON as usize {
+                let (x, y) = (column as f64 + 0.5, row as f64 + 0.5);
+                // how far from the rod, from the point on it nearest
+                let (dx, dy) = (bob.0 - pivot.0, bob.1 - pivot.1);
+                let along = (((x - pivot.0) * dx + (y - pivot.1) * dy) / (dx * dx + dy * dy))
+                    .clamp(0.0, 1.0);
+                let rod = (x - pivot.0 - along * dx).hypot(y - pivot.1 - along * dy);
+                let color = if (x - bob.0).hypot(y - bob.1) < radius {
+                    [220, 30, 30, 255]
+                } else if rod < 1.0 {
+                    [0, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 0]
+                };
+                let at = row * pitch + column * 4;
+                pixels[at..at + 4].copy_from_slice(&color);
+            }
+        }
+    });
+    window.set_icon(icon);
+}
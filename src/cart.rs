@@ -0,0 +1,471 @@
+#[cfg(feature = "serialport")]
+use std::io;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+use crate::control::{self, Controller, Law, Lqr, Mpc, Pid, GAINS, MAX_CONTROL, POLE, TRACK};
+use crate::events;
+use crate::input::{self, Action};
+use crate::mode::{Trail, SUBSTEPS, TRAIL};
+use crate::physics;
+use crate::plot::Plot;
+#[cfg(feature = "serialport")]
+use crate::rig::Rig;
+#[cfg(feature = "serialport")]
+use crate::twin::Twin;
+use crate::{BORDER, CENTER, HEIGHT, WIDTH};
+
+// cm/s² the cart speeds up at while an arrow is held
+const THRUST: f64 = 1500.0;
+/// rad from upright at the start.
+pub const TILT: f64 = 0.05;
+// rad from upright the pole still counts as balanced within
+const BALANCED: f64 = 0.2;
+// px, of the drawn cart
+const CART: (i16, i16) = (40, 20);
+// px below the middle of the window the track runs at
+const RAIL: i16 = 80;
+// px, the side of the square the PID's poles are plotted in
+const POLES: i16 = 110;
+// what the keys change the PID's gains by
+const GAIN_STEPS: [f64; 3] = [500.0, 100.0, 200.0];
+
+/// The main window's cart-pole mode: the pendulum standing on a cart that
+/// the left and right arrows speed up either way, to be kept balanced
+/// upright for as long as possible.
+///
+/// The cart follows the keys whatever the pole does, so the pole only feels
+/// its acceleration a: θ'' = (g·sin θ - a·cos θ)/L, θ from upright.
+///
+/// A controller can push the cart too, the PID, the LQR or the MPC of
+/// `control`.
+pub struct CartMode {
+    // cm and cm/s along the track, θ from upright and ω
+    state: [f64; 4],
+    // the arrows held, left and right
+    held: (bool, bool),
+    pub time: f64,
+    // since the pole was last out of balance, and the longest so far
+    pub balanced: f64,
+    best: f64,
+    trail: Trail,
+    // the controller pushing, if any
+    pub law: Option<Law>,
+    pub pid: Pid,
+    lqr: Lqr,
+    // boxed, it's larger than all the rest of the mode
+    mpc: Box<Mpc>,
+    // s of a step, which the LQR's gains are worked out for
+    dt: f64,
+    // 1/s, of the linearized cart-pole under the PID's gains
+    poles: [(f64, f64); 5],
+    // cm/s², what the controller pushes the cart with, at every step
+    control: Plot,
+    // the model run beside a rig's real cart-pole, drawn faint behind it,
+    // boxed like the MPC
+    #[cfg(feature = "serialport")]
+    twin: Option<Box<Twin>>,
+}
+
+impl CartMode {
+    pub fn new(dt: f64) -> Self {
+        CartMode {
+            state: [0.0, 0.0, TILT, 0.0],
+            held: (false, false),
+            time: 0.0,
+            balanced: 0.0,
+            best: 0.0,
+            trail: Trail::new(TRAIL, Color::RED),
+            law: None,
+            pid: Pid::new(GAINS),
+            lqr: Lqr::new(dt),
+            mpc: Box::new(Mpc::new()),
+            dt,
+            poles: control::pid_poles(GAINS),
+            control: Plot::new(600),
+            #[cfg(feature = "serialport")]
+            twin: None,
+        }
+    }
+
+    /// The mode with `twin` run beside the rig it follows.
+    #[cfg(feature = "serialport")]
+    pub fn with_twin(mut self, twin: Option<Twin>) -> Self {
+        self.twin = twin.map(Box::new);
+        self
+    }
+
+    /// Takes presses and releases of the arrows, or of the pad's, returning
+    /// whether `event` was one, which then does nothing else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match input::held(event) {
+            // the arrows the waiting pendulum's gravity is changed with
+            Some((Action::Lighter, down)) => self.held.0 = down,
+            Some((Action::Heavier, down)) => self.held.1 = down,
+            _ => return false,
+        }
+        true
+    }
+
+    // none, then each of `Law::ALL` in turn, each starting afresh
+    pub fn next_law(&mut self) {
+        let i = self.law.map_or(0, |law| {
+            Law::ALL.iter().position(|it| *it == law).unwrap_or(0) + 1
+        });
+        self.law = Law::ALL.get(i).copied();
+        self.pid = Pid::new(self.pid.gains);
+        self.lqr = Lqr::new(self.dt);
+        *self.mpc = Mpc::new();
+    }
+
+    // one step up or down of Kp, Ki or Kd, from 0 to 2
+    pub fn tune(&mut self, gain: usize, up: bool) {
+        let step = if up {
+            GAIN_STEPS[gain]
+        } else {
+            -GAIN_STEPS[gain]
+        };
+        let gains = &mut self.pid.gains;
+        gains[gain] = (gains[gain] + step).max(0.0);
+        self.poles = control::pid_poles(*gains);
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let [x, v, theta, w] = self.state;
+        vec![("x", x), ("v", v), ("θ", theta), ("ω", w)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        // simulated, there's no rig for a twin to follow
+        #[cfg(feature = "serialport")]
+        {
+            self.twin = None;
+        }
+        let thrust = self.thrust(dt);
+        advance(&mut self.state, thrust, dt);
+        self.settle(dt);
+    }
+
+    /// Shows and pushes the real cart-pole of `rig` instead of simulating
+    /// one: its latest sample is the state, and the arrows and the
+    /// controller push its cart as they would the simulated one's. The
+    /// twin, if any, is pushed the same.
+    #[cfg(feature = "serialport")]
+    pub fn follow(&mut self, rig: &mut Rig, dt: f64) -> io::Result<()> {
+        let sample = rig.poll()?;
+        if let Some(state) = sample {
+            self.state = state;
+        }
+        let thrust = self.thrust(dt);
+        rig.command(thrust)?;
+        if let Some(twin) = self.twin.as_mut() {
+            twin.step(&self.state, sample.is_some(), thrust, dt);
+        }
+        self.settle(dt);
+        Ok(())
+    }
+
+    // cm/s², the arrows' and the controller's push over the next `dt` s
+    fn thrust(&mut self, dt: f64) -> f64 {
+        let mut thrust = match self.held {
+            (true, false) => -THRUST,
+            (false, true) => THRUST,
+            _ => 0.0,
+        };
+        let controller: Option<&mut dyn Controller> = match self.law {
+            Some(Law::Pid) => Some(&mut self.pid),
+            Some(Law::Lqr) => Some(&mut self.lqr),
+            Some(Law::Mpc) => Some(&mut *self.mpc),
+            None => None,
+        };
+        if let Some(controller) = controller {
+            // held through the step like a digital controller's
+            let control = controller
+                .control(&self.state, dt)
+                .clamp(-MAX_CONTROL, MAX_CONTROL);
+            self.control.push(control);
+            thrust += control;
+        }
+        thrust
+    }
+
+    // the time and how long the pole has been balanced for, `dt` s on
+    fn settle(&mut self, dt: f64) {
+        self.time += dt;
+
+        self.balanced = if events::wrap(self.state[2]).abs() < BALANCED {
+            self.balanced + dt
+        } else {
+            0.0
+        };
+        self.best = self.best.max(self.balanced);
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.state[3] += dw;
+    }
+
+    /// Draws the track, the cart and the pole standing on it, and with the
+    /// PID on what it pushes with, as an arrow on the cart and a plot.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let [x, _, theta, _] = self.state;
+        let rail = CENTER.1 + RAIL;
+        let track = TRACK.round() as i16 + CART.0 / 2;
+        canvas
+            .hline(
+                CENTER.0 - track,
+                CENTER.0 + track,
+                rail,
+                Color::RGB(150, 150, 150),
+            )
+            .expect("Unable to draw line");
+        for end in [CENTER.0 - track, CENTER.0 + track] {
+            canvas
+                .vline(end, rail - 10, rail, Color::RGB(150, 150, 150))
+                .expect("Unable to draw line");
+        }
+
+        #[cfg(feature = "serialport")]
+        if let Some(twin) = &self.twin {
+            draw_ghost(canvas, twin.state, rail);
+        }
+
+        let pivot = (CENTER.0 + x.round() as i16, rail - CART.1);
+        canvas
+            .rectangle(
+                pivot.0 - CART.0 / 2,
+                pivot.1,
+                pivot.0 + CART.0 / 2,
+                rail - 4,
+                Color::BLACK,
+            )
+            .expect("Unable to draw rectangle");
+        for wheel in [pivot.0 - CART.0 / 4, pivot.0 + CART.0 / 4] {
+            canvas
+                .aa_circle(wheel, rail - 4, 4, Color::BLACK)
+                .expect("Unable to draw circle");
+        }
+
+        if self.law == Some(Law::Mpc) {
+            self.draw_fan(canvas, rail);
+        }
+        let bob = (
+            pivot.0 + (theta.sin() * POLE).round() as i16,
+            pivot.1 - (theta.cos() * POLE).round() as i16,
+        );
+        self.trail.push(bob);
+        self.trail.draw(canvas, detail);
+        canvas
+            .aa_line(pivot.0, pivot.1, bob.0, bob.1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+
+        if self.law.is_some() {
+            let push = self.control.last().unwrap_or(0.0) / MAX_CONTROL * 50.0;
+            let tip = pivot.0 + push.round() as i16;
+            canvas
+                .thick_line(
+                    pivot.0,
+                    rail - CART.1 / 2,
+                    tip,
+                    rail - CART.1 / 2,
+                    3,
+                    Color::RED,
+                )
+                .expect("Unable to draw line");
+            self.control.draw_linear(
+                canvas,
+                Rect::new(BORDER, HEIGHT as i32 - BORDER - 60, 200, 60),
+                Color::RED,
+                1.0,
+            );
+        }
+        if self.law == Some(Law::Pid) {
+            self.draw_poles(canvas);
+        }
+    }
+
+    // where the MPC's bob goes along each of the paths it last tried, faint,
+    // and along the plan it made of them after
+    fn draw_fan(&self, canvas: &mut WindowCanvas, rail: i16) {
+        let bob = |&(x, theta): &(f64, f64)| {
+            (
+                CENTER.0 + (x + theta.sin() * POLE).round() as i16,
+                rail - CART.1 - (theta.cos() * POLE).round() as i16,
+            )
+        };
+        let paths = self
+            .mpc
+            .fan
+            .iter()
+            .map(|it| (it, Color::RGBA(60, 110, 220, 40)));
+        let plan = (&self.mpc.predicted, Color::RGB(60, 110, 220));
+        for (path, color) in paths.chain([plan]) {
+            for (a, b) in path.iter().map(bob).zip(path.iter().skip(1).map(bob)) {
+                canvas
+                    .aa_line(a.0, a.1, b.0, b.1, color)
+                    .expect("Unable to draw line");
+            }
+        }
+    }
+
+    // the PID's poles on the complex plane in the top right corner, the
+    // stable half shaded, a cross each and red when on the unstable side
+    fn draw_poles(&self, canvas: &mut WindowCanvas) {
+        let scale = pole_scale(&self.poles);
+        let (left, top) = (WIDTH as i16 - BORDER as i16 - POLES, BORDER as i16);
+        let (middle, half) = ((left + POLES / 2, top + POLES / 2), POLES / 2);
+        canvas
+            .box_(
+                left,
+                top,
+                middle.0,
+                top + POLES,
+                Color::RGBA(60, 160, 60, 40),
+            )
+            .expect("Unable to draw box");
+        canvas
+            .rectangle(
+                left,
+                top,
+                left + POLES,
+                top + POLES,
+                Color::RGB(150, 150, 150),
+            )
+            .expect("Unable to draw rectangle");
+        canvas
+            .hline(left, left + POLES, middle.1, Color::RGB(150, 150, 150))
+            .expect("Unable to draw line");
+        canvas
+            .vline(middle.0, top, top + POLES, Color::RGB(150, 150, 150))
+            .expect("Unable to draw line");
+        for &(re, im) in &self.poles {
+            let at = |it: f64| (it / scale * half as f64).clamp(-half as f64, half as f64) as i16;
+            let (x, y) = (middle.0 + at(re), middle.1 - at(im));
+            let color = if re < 0.0 { Color::BLACK } else { Color::RED };
+            canvas
+                .aa_line(x - 3, y - 3, x + 3, y + 3, color)
+                .expect("Unable to draw line");
+            canvas
+                .aa_line(x - 3, y + 3, x + 3, y - 3, color)
+                .expect("Unable to draw line");
+        }
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [x, v, theta, w] = self.state;
+        let mut lines = vec![
+            format!(
+                "θ from upright: {:.3} rad, ω: {:.3} rad/s",
+                events::wrap(theta),
+                w
+            ),
+            format!("cart: {:+.0} cm, {:+.0} cm/s", x, v),
+            format!(
+                "balanced for {:.1} s, best {:.1} s",
+                self.balanced, self.best
+            ),
+        ];
+        let push = self.control.last().unwrap_or(0.0);
+        match self.law {
+            Some(Law::Lqr) => lines.push(format!("LQR: pushing {:+.0} cm/s²", push)),
+            Some(Law::Mpc) => lines.push(format!(
+                "MPC: a plan 1 s ahead from {} paths, pushing {:+.0} cm/s²",
+                self.mpc.fan.len(),
+                push
+            )),
+            _ => {}
+        }
+        if self.law == Some(Law::Pid) {
+            let [kp, ki, kd] = self.pid.gains;
+            lines.push(format!(
+                "PID: Kp {:.0}, Ki {:.0}, Kd {:.0}, pushing {:+.0} cm/s²",
+                kp, ki, kd, push
+            ));
+            // the slowest to die out, or the quickest to grow
+            let (re, im) = self
+                .poles
+                .iter()
+                .copied()
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap_or_default();
+            lines.push(format!(
+                "poles (±{} /s): slowest {:+.2} ± {:.2}i /s, {}",
+                pole_scale(&self.poles),
+                re,
+                im.abs(),
+                if re < 0.0 { "stable" } else { "unstable" }
+            ));
+        }
+        #[cfg(feature = "serialport")]
+        if let Some(twin) = &self.twin {
+            lines.extend(twin.lines());
+        }
+        lines
+    }
+}
+
+// the twin's cart and pole at `state`, faint blue
+#[cfg(feature = "serialport")]
+fn draw_ghost(canvas: &mut WindowCanvas, state: [f64; 4], rail: i16) {
+    let color = Color::RGBA(60, 110, 220, 110);
+    let [x, _, theta, _] = state;
+    let pivot = (CENTER.0 + x.round() as i16, rail - CART.1);
+    let bob = (
+        pivot.0 + (theta.sin() * POLE).round() as i16,
+        pivot.1 - (theta.cos() * POLE).round() as i16,
+    );
+    canvas
+        .rectangle(
+            pivot.0 - CART.0 / 2,
+            pivot.1,
+            pivot.0 + CART.0 / 2,
+            rail - 4,
+            color,
+        )
+        .expect("Unable to draw rectangle");
+    canvas
+        .aa_line(pivot.0, pivot.1, bob.0, bob.1, color)
+        .expect("Unable to draw line");
+    canvas
+        .aa_circle(bob.0, bob.1, 5, color)
+        .expect("Unable to draw circle");
+}
+
+/// Moves the cart-pole in `state`, cm and cm/s of the cart along the track,
+/// θ from upright and ω, on by `dt` s with the cart pushed by `thrust` in
+/// cm/s² throughout, stopping it dead at the ends of the track.
+pub fn advance(state: &mut [f64; 4], thrust: f64, dt: f64) {
+    for _ in 0..SUBSTEPS {
+        *state = physics::rk4(*state, dt / SUBSTEPS as f64, |it| {
+            control::derivatives(it, thrust)
+        });
+    }
+    // stopped dead at the end of the track, jolting the pole
+    let [x, v, theta, _] = *state;
+    if x.abs() > TRACK {
+        state[0] = x.clamp(-TRACK, TRACK);
+        state[1] = 0.0;
+        state[3] += theta.cos() * v / POLE;
+    }
+}
+
+// 1/s the edges of the poles' plot are at, the first of 1, 2, 5, 10, 20...
+// that takes them all in, up to 1000
+fn pole_scale(poles: &[(f64, f64)]) -> f64 {
+    let furthest = poles
+        .iter()
+        .map(|it| it.0.abs().max(it.1.abs()))
+        .fold(0.0, f64::max);
+    (0..3)
+        .flat_map(|power| [1.0, 2.0, 5.0].map(|it| it * 10f64.powi(power)))
+        .find(|it| *it >= furthest)
+        .unwrap_or(1000.0)
+}
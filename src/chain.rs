@@ -0,0 +1,216 @@
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::flips::{self, FlipMap};
+use crate::input::{self, Action};
+use crate::mode::{Trail, TRAIL};
+use crate::physics::{Chain, ChainParams, DoubleParams};
+use crate::quality::Tier;
+use crate::traces::Traces;
+use crate::{BORDER, CENTER, WIDTH};
+
+// RK4 steps per rod per step of `--dt`. Shorter rods swing faster and whip
+// harder, so their error grows with the length of the chain: at these, ten
+// rods released high lose a few hundred thousandths of their energy a minute
+const SUBSTEPS_PER_LINK: usize = 2;
+// px, top left corner of the flip-time map, clear of the chain's reach
+const MAP: (i32, i32) = (WIDTH as i32 - BORDER - flips::SIZE, BORDER);
+
+/// The main window's chain mode: rods hanging one from the bob of the
+/// other, a double pendulum with two of them, whose swings are chaotic once
+/// they are large. A double pendulum has the map of how soon it flips from
+/// each start beside it, filling in while it swings: V hides it and a click
+/// on it lets the rods go from there.
+pub struct ChainMode {
+    params: ChainParams,
+    pub chain: Chain,
+    pub time: f64,
+    // what the energy started at, the drift from which shows the integration error
+    energy: f64,
+    trail: Trail,
+    // the last bob's path scored and the best saved, with --traces-out
+    traces: Option<Traces>,
+    // of a double pendulum, and whether it's shown
+    flips: Option<FlipMap>,
+    show_flips: bool,
+    // rad, the angles of the top two rods at the start
+    start: (f64, f64),
+}
+
+impl ChainMode {
+    pub fn new(params: ChainParams, theta: f64, traces: Option<String>) -> Self {
+        let chain = Chain::new(&params, theta);
+        let reach = params.lengths.iter().sum();
+        let flips = (params.lengths.len() == 2).then(|| {
+            FlipMap::new(DoubleParams {
+                l1: params.lengths[0],
+                l2: params.lengths[1],
+                m1: params.masses[0],
+                m2: params.masses[1],
+                gravity: params.gravity,
+            })
+        });
+        ChainMode {
+            traces: traces.map(|dir| Traces::new(dir, reach)),
+            flips,
+            show_flips: true,
+            start: (theta, theta),
+            energy: chain.energy(&params),
+            params,
+            chain,
+            time: 0.0,
+            trail: Trail::new(TRAIL, Color::RED),
+        }
+    }
+
+    /// Shows or hides the flip-time map on V, and lets the rods go from a
+    /// click on it, returning whether `event` was either, which then does
+    /// nothing else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        if self.flips.is_none() {
+            return false;
+        }
+        if input::action(event) == Some(Action::View) {
+            self.show_flips = !self.show_flips;
+            return true;
+        }
+        let Event::MouseButtonDown {
+            mouse_btn: MouseButton::Left,
+            x,
+            y,
+            ..
+        } = *event
+        else {
+            return false;
+        };
+        let Some((theta1, theta2)) =
+            FlipMap::angles(x - MAP.0, y - MAP.1).filter(|_| self.show_flips)
+        else {
+            return false;
+        };
+        self.chain = Chain {
+            theta: vec![theta1, theta2],
+            w: vec![0.0; 2],
+        };
+        self.energy = self.chain.energy(&self.params);
+        self.time = 0.0;
+        self.start = (theta1, theta2);
+        self.trail = Trail::new(TRAIL, Color::RED);
+        true
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        self.chain
+            .theta
+            .iter()
+            .map(|it| ("θ", *it))
+            .chain(self.chain.w.iter().map(|it| ("ω", *it)))
+            .collect()
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let substeps = SUBSTEPS_PER_LINK * self.chain.theta.len();
+        for _ in 0..substeps {
+            self.chain.step(&self.params, dt / substeps as f64);
+        }
+        self.time += dt;
+        if let Some(traces) = &mut self.traces {
+            let end = self
+                .chain
+                .theta
+                .iter()
+                .zip(&self.params.lengths)
+                .fold((0.0, 0.0), |(x, y), (theta, length)| {
+                    (x + theta.sin() * length, y + theta.cos() * length)
+                });
+            traces.push(self.time, end);
+        }
+    }
+
+    // rad/s added to the top rod
+    pub fn kick(&mut self, dw: f64) {
+        self.chain.w[0] += dw;
+        self.energy = self.chain.energy(&self.params);
+    }
+
+    // change of the energy since the start, over m·g·L of the whole chain,
+    // which a start from level, of no energy at all, doesn't make infinite
+    pub fn drift(&self) -> f64 {
+        let scale = self.params.gravity
+            * self.params.masses.iter().sum::<f64>()
+            * self.params.lengths.iter().sum::<f64>();
+        (self.chain.energy(&self.params) - self.energy) / scale
+    }
+
+    /// Draws the rods and bobs, and the path of the last bob, then works out
+    /// as many more squares of the flip-time map as the `tier` of detail
+    /// allows and draws it.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, tier: Tier) {
+        let detail = tier.trail;
+        let mut joints = vec![CENTER];
+        for (theta, length) in self.chain.theta.iter().zip(&self.params.lengths) {
+            let (x, y) = joints[joints.len() - 1];
+            joints.push((
+                x + (theta.sin() * length).round() as i16,
+                y + (theta.cos() * length).round() as i16,
+            ));
+        }
+        self.trail.push(joints[joints.len() - 1]);
+        self.trail.draw(canvas, detail);
+        for pair in joints.windows(2) {
+            canvas
+                .aa_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, Color::BLACK)
+                .expect("Unable to draw line");
+        }
+        // heavier bobs are bigger, the last one open like the single pendulum's
+        let heaviest = self.params.masses.iter().cloned().fold(0.0, f64::max);
+        for (i, (x, y)) in joints[1..].iter().enumerate() {
+            let radius = (5.0 * (self.params.masses[i] / heaviest).cbrt()).max(2.0) as i16;
+            if i == self.params.masses.len() - 1 {
+                canvas
+                    .aa_circle(*x, *y, radius, Color::BLACK)
+                    .expect("Unable to draw circle");
+            } else {
+                canvas
+                    .filled_circle(*x, *y, radius - 1, Color::BLACK)
+                    .expect("Unable to draw circle");
+            }
+        }
+        if let Some(flips) = self.flips.as_mut().filter(|_| self.show_flips) {
+            flips.draw(canvas, MAP, tier.flips, self.start);
+        }
+    }
+
+    /// The HUD lines of the mode: every rod's angle and angular velocity
+    /// for a double pendulum, just the ends' for longer chains.
+    pub fn lines(&self) -> Vec<String> {
+        let Chain { theta, w } = &self.chain;
+        let n = theta.len();
+        let shown: Vec<usize> = if n <= 2 {
+            (0..n).collect()
+        } else {
+            vec![0, n - 1]
+        };
+        let mut lines: Vec<String> = shown
+            .into_iter()
+            .map(|i| {
+                format!(
+                    "θ{}: {:.3} rad, ω{}: {:.3} rad/s",
+                    i + 1,
+                    events::wrap(theta[i]),
+                    i + 1,
+                    w[i]
+                )
+            })
+            .collect();
+        lines.push(format!("energy drift: {:.1e}", self.drift()));
+        if let Some(flips) = self.flips.as_ref().filter(|_| self.show_flips) {
+            lines.push(format!("flip times mapped: {:.0}%", 100.0 * flips.mapped()));
+        }
+        lines
+    }
+}
@@ -0,0 +1,1361 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, SocketAddrV4};
+use std::ops::RangeInclusive;
+use std::process;
+
+use crate::audit::AMPLITUDES;
+use crate::bridge::{Link, DELAYS};
+use crate::compound::{Body, PIVOT_DISTANCES};
+use crate::conical::HALF_ANGLES;
+use crate::control::{Conditions, Law, CONTROL_RATES, GAINS, TRACK_RESOLUTIONS};
+use crate::convention::Convention;
+use crate::coupled::COUPLINGS;
+use crate::decor::Decor;
+use crate::events::SimEvent;
+use crate::foucault::{LATITUDES, WARPS};
+use crate::haptics::Pulse;
+use crate::integrator::{Scheme, STEPS};
+use crate::kapitza::{PIVOT_DEPTHS, PIVOT_FREQUENCIES};
+use crate::magnetic::COLORS;
+use crate::mode::Mode;
+use crate::physics::{
+    self, Conserve, Params, CROSS_SECTIONS, DAMPINGS, DRAG_COEFFICIENTS, DRIVES, DRIVE_FREQUENCIES,
+};
+use crate::quality::TIERS;
+use crate::resonance::{RATIOS, SWEEP_DAMPINGS};
+use crate::snapshot::Snapshot;
+use crate::spring::{REST_LENGTHS, STIFFNESSES};
+use crate::string::LAUNCHES;
+use crate::swing::PUMP_DEPTHS;
+use crate::thermal::INTENSITIES;
+use crate::wave::{WAVE_CYCLES, WAVE_PENDULUMS};
+use crate::wind::{Gusting, GUSTS, GUST_PERIODS, WINDS};
+use crate::{G, L, THETA_0};
+
+// rods in the chain mode, each one more making a step cost a lot more
+const MAX_LINKS: u64 = 10;
+// the magnetic mode's, one color each
+const MAX_MAGNETS: u64 = COLORS.len() as u64;
+// the golden ratio, the most irrational number, so that by default the
+// second drive never falls in step with the first
+const GOLDEN: f64 = 1.618_033_988_749_895;
+// what a pendulum:// link sets, which given before it would be overridden
+const LINKED: [&str; 11] = [
+    "--mode",
+    "--integrator",
+    "--damping",
+    "--drive",
+    "--drive-frequency",
+    "--drive2",
+    "--drive-frequency2",
+    "--wind",
+    "--gust",
+    "--gusting",
+    "--gust-period",
+];
+
+const USAGE: &str = "usage: pendulum [options]
+       pendulum rng [rng options]
+       pendulum harmonograph [harmonograph options]
+       pendulum sand [sand options]
+       pendulum parametric [parametric options]
+       pendulum montecarlo --randomize <file> [montecarlo options]
+       pendulum audit [audit options]
+       pendulum resonance [resonance options]
+       pendulum compare [compare options]
+
+options:
+  --quiz <file>          questions for the quiz mode (Q) instead of the built-in ones
+  --schedule <file>      disturbances at set times, one `<time> <kind> <value>` a line:
+                         kick <rad/s>, length <cm>, gravity <m/s²>, noise <rad/s²> <secs>
+  --randomize <file>     draw the start from constraints, one a line: `theta in 120..170`,
+                         `w = 0` or `length fixed`; logged with the seed it was drawn from
+  --seed <n>             draw a --randomize or screensaver start from this seed instead
+                         of the current time
+  --hud-out <file>       periodically write the HUD values as text to <file>, - for stdout
+  --hud-interval <secs>  how often --hud-out writes, every second by default
+  --sensors-out <file>   write simulated encoder and IMU readings every step as CSV to
+                         <file>, - for stdout
+  --record <dir>         save every frame to <dir> as a numbered BMP, with frames.csv
+                         giving each its wall-clock and simulated time, θ and ω,
+                         audio.wav of the Doppler tone and zero-crossing clicks, and
+                         captions.srt and captions.vtt of the HUD
+  --encoder-resolution <counts>
+                         encoder counts per revolution, 4096 by default
+  --angles <convention>  how θ is shown and exported, a comma list of bottom or top,
+                         counterclockwise or clockwise, radians or degrees, e.g.
+                         top,clockwise,degrees; bottom,counterclockwise,radians by default
+  --rumble <event>=<strength>,<ms>
+                         controller rumble for an event, strength from 0 to 1, or
+                         <event>=off; events: zero-crossing, flip, amplitude-record
+  --sync-group <ip:port> reset together with the other instances in this multicast
+                         group, e.g. 239.255.42.42:4242
+  --host <ip:port>       stream the simulation to viewers connecting on this address,
+                         e.g. 0.0.0.0:4243
+  --view <host:port>     only show the simulation of a --host, reconnecting when needed
+  --controller <udp|tcp://host:port>
+                         push the single pendulum with the torque an external process
+                         replies with each step to what it observes, see the README
+  --latency <secs>       simulated delay before the --controller's torque acts, 0 to 5,
+                         0 by default
+  --jitter <secs>        up to this much more delay at random, 0 to 5, 0 by default
+  --rig <port>           show and push a real cart-pole rig on this serial port in the
+                         cart-pole mode, starting in it unless --mode says otherwise;
+                         needs the serialport feature
+  --rig-baud <rate>      the port's baud rate, 115200 by default
+  --rig-log <file>       write every sample of the rig as CSV to <file>, - for stdout
+  --twin                 run a simulated cart-pole beside the --rig, drawn faint behind
+                         it, with how far it is off the rig
+  --refit                also fit the --twin's pole and cart drag to the rig as it runs
+  --track-resolution <cm>
+                         cm per count of the rig's cart encoder, 0.001 to 10, 0.01 by
+                         default; its pole's encoder has --encoder-resolution counts
+  --screensaver          fullscreen without HUD, exiting on any input; /s and -root
+                         are accepted too for Windows .scr and xscreensaver
+  --kiosk                fullscreen without a mouse cursor, for exhibits and touchscreens
+  --renderer <name>      SDL render driver, e.g. opengles2 or software; opengl if there
+                         is one, else opengles2, else SDL's choice by default
+  --detail <tier>        the most scenery drawn: full, reduced or low, full by default;
+                         slow frames still lower it further
+  --decor <parts>        start with the scenery (D) on, showing only these of
+                         wall,stand,shadows, or all
+  --sound-speed <m/s>    speed of sound for the Doppler tone (A), 343 by default,
+                         lower it to exaggerate the shift
+  --dt <secs>            simulation time step, the same whatever the frame rate,
+                         from 0.0001 to 0.05, 1/120 by default
+  --damping <1/s>        viscous drag b/m slowing the swing, changed with Page Up/Down,
+                         from 0 to 2, 0 by default
+  --drag-coefficient <Cd>
+                         quadratic air drag on the bob, ½·ρ·Cd·A·v², from 0 to 2,
+                         0.47 for a sphere, none by default
+  --cross-section <cm²>  the bob's A in the air drag, from 1 to 2000, 80 by default
+  --thermal-noise <rad²/s³>
+                         white-noise torque of intensity D on the bob, a Langevin
+                         oscillator, from 0 to 20, none by default
+  --noise-seed <n>       draw the thermal noise from this seed instead of the current
+                         time, the same again on every reset
+  --wind <m/s²>          horizontal wind pushing the bob, to the right positive, changed
+                         with W and Shift+W, from -5 to 5, calm by default
+  --gust <fraction>      how much the wind gusts, from 0 to 1 of its strength, 0 by
+                         default
+  --gusting <kind>       sine, rising and falling evenly, or noise, at random; sine
+                         by default
+  --gust-period <secs>   how long a gust lasts, from 0.5 to 60, 5 by default
+  --drive <rad/s²>       driving torque over inertia A in A·cos(Ω·t), changed with
+                         Home/End, from 0 to 50, 0 by default
+  --drive-frequency <rad/s>
+                         Ω, changed with Insert/Delete, from 0.02 to 20, 1.48 by
+                         default (2/3 of ω0)
+  --drive2 <rad/s²>      a second drive A2 in A2·cos(Ω2·t), added to the first for a
+                         quasi-periodic drive, from 0 to 50, 0 by default
+  --drive-frequency2 <rad/s>
+                         Ω2, from 0.02 to 20, 2.39 by default, the golden ratio times
+                         the default Ω, never in step with it
+  --integrator <name>    how the pendulum is stepped, also switched with Tab: euler,
+                         semi-implicit, rk4 or verlet; rk4 by default
+  --kick <rad/s>         how much a kick (I, Shift+I backward) changes the angular
+                         velocity, 1 by default
+  --conserve <quantity>  what changing the length (up/down) or gravity (left/right)
+                         keeps: state, energy or momentum, state by default
+  --mode <name>          what swings at the start, also switched with J: single, chain
+                         (two rods by default), spring, spherical, kapitza, cart,
+                         magnetic, foucault, conical, wave, coupled, compound, string or
+                         swing; single by default
+  --links <n>            rods in the chain, from 1 to 10, splitting 200 cm evenly
+  --link-lengths <cm,...>
+                         length of each rod from the top, adding up to 200 at most
+  --link-masses <m,...>  mass of each bob from the top, in any unit, 1 each by default
+  --traces-out <dir>     save the chain's last bob's path to <dir> as SVG every 30 s that it
+                         scores well for its length and how much it curls
+  --stiffness <1/s²>     spring constant over the bob's mass k/m, from 20 to 500, 24.5
+                         by default, for a bounce twice as fast as the swing
+  --rest-length <cm>     the spring's natural length L0, from 50 to 150, 120 by default
+  --pivot-depth <cm>     how far the Kapitza pendulum's pivot moves either way, from 0
+                         to 15, 5 by default
+  --pivot-frequency <rad/s>
+                         how fast it shakes, from 10 to 300, 120 by default
+  --magnets <n>          magnets under the magnetic pendulum, from 2 to 6, 3 by default
+  --latitude <degrees>   where the Foucault pendulum swings, from -90 (south pole) to 90,
+                         48.85 by default (Paris)
+  --warp <x>             how many times faster than real time it swings, from 1 to
+                         10000, 1000 by default
+  --half-angle <degrees> the conical pendulum's cone, from 1 to 85, 30 by default
+  --wave-pendulums <n>   pendulums in the pendulum wave, from 2 to 24, 15 by default
+  --wave-cycle <secs>    how long until they are back in line, from 20 to 240, 60 by
+                         default
+  --coupling <1/s²>      stiffness over a bob's mass k/m of the spring between the
+                         coupled pendulums, from 0 to 20, 1 by default
+  --body <shape>         what swings in the compound mode: rod, disk, ring or bob, rod by
+                         default
+  --inertia <kg·m²>      a body of this moment of inertia about the pivot instead
+  --mass <kg>            its mass, 1 by default
+  --pivot-distance <cm>  from the centre of mass to the pivot, from 1 to 200, the
+                         rod's end, 160 for the disk and ring, 200 for the bob, 100 for
+                         --inertia by default
+  --launch <v²/gL>       how fast the bob on a string is launched from the bottom, from
+                         0 to 6, 3.5 by default, going slack at 120°
+  --pump-depth <cm>      how far the rider on the swing stands up, from 0 to 50, 20 by
+                         default
+  --auto-pump            the rider pumps the swing by themselves from the start, standing
+                         up at the bottom and squatting at the ends
+  --from-url <link>      start as a pendulum://open?mode=...&length=... link says, as
+                         copied with Ctrl+Shift+C; a bare link works too. Options it
+                         sets can only follow it, changing what it set
+  -h, --help             show this help
+
+rng options, a toy random byte stream from a double pendulum, NOT cryptographically secure:
+  --bytes <n>            stop after <n> bytes instead of running until interrupted
+  --hex                  write hex digits instead of raw bytes
+  --seed <n>             start from this seed instead of the current time
+  --show                 visualize the generator in a window instead of writing to stdout
+
+harmonograph options, drawing figures with pendulums (R: new figure, E: export SVG):
+  --lissajous            one undamped pendulum per axis, tracing closed Lissajous curves
+  --seed <n>             start from this seed instead of the current time
+  --svg <file>           where E exports the figure, harmonograph.svg by default
+
+sand options, a pendulum pouring sand on the floor (R: new swing, X: sweep, mouse: erase,
+E: export):
+  --2d                   independent swings along x and y instead of a spherical pendulum
+  --seed <n>             start from this seed instead of the current time
+  --out <file>           where E exports the floor, sand.bmp by default
+
+parametric options, a pendulum whose pivot moves up and down (arrows: drive, R: restart,
+E: export the instability tongues):
+  --ratio <Ω/ω0>         drive frequency over the natural one, 2 by default
+  --depth <cm>           how far the pivot moves either way, 15 by default
+  --out <file>           where E exports the tongue diagram as CSV, tongues.csv by default
+
+montecarlo options, many runs of the single pendulum from drawn starts, summed up on stdout:
+  --randomize <file>     the constraints to draw each start from, as for the main window
+  --double               run the double pendulum, each rod half of length, and estimate how
+                         likely its second rod is to flip within --duration; theta2 and w2
+                         constrain that rod, and it always steps with rk4
+  --runs <n>             how many runs, 1000 by default
+  --duration <secs>      how long each runs for, 20 by default
+  --dt <secs>            simulation time step, from 0.0001 to 0.05, 1/120 by default
+  --integrator <name>    euler, semi-implicit, rk4 or verlet; rk4 by default
+  --seed <n>             draw from this seed instead of the current time
+  --checkpoint <file>    write how far the runs have got to <file> every 30 s and at the end
+  --resume-from <file>   carry on from a --checkpoint with the same options, drawing as if
+                         never stopped, and keep writing to it unless --checkpoint says where;
+                         --runs may be raised to add more
+
+audit options, how far each integrator lets the energy of a free swing drift, on stdout:
+  --dt <secs,...>        the time steps to audit, from 0.0001 to 0.05 each, 1/480, 1/240,
+                         1/120, 1/60 and 1/30 by default
+  --integrator <name>    audit only euler, semi-implicit, rk4 or verlet, all of them by
+                         default
+  --duration <secs>      how long each runs for, 60 by default
+  --amplitude <degrees>  how far off the bottom it is let go from, from 1 to 179, 117 by
+                         default
+
+resonance options, the steady swing as the drive frequency is swept, as CSV and SVG:
+  --damping <1/s>        swing damping b, from 0.01 to 2, 0.25 by default
+  --drive <rad/s²>       drive A in A·cos(Ω·t), from 0 to 50, 0.1 by default
+  --from <Ω/ω0>          the drive frequency to start at, from 0.1 to 3, 0.5 by default
+  --to <Ω/ω0>            the one to end at, above --from, 1.5 by default
+  --steps <n>            how many frequencies to measure at, 101 by default
+  --settle <periods>     how many drive periods each is left to settle for, 40 by default
+  --dt <secs>            simulation time step, from 0.0001 to 0.05, 1/120 by default
+  --hysteresis           sweep back down again after, for both branches of a strong drive
+  --out <file>           where the curve is written as CSV, resonance.csv by default
+  --svg <file>           where it is plotted, resonance.svg by default
+
+compare options, two controllers balancing the cart-pole through the same disturbances:
+  --controllers <a,b>    the two to compare, each pid, lqr or mpc, pid,lqr by default
+  --gains <kp,ki,kd>     the PID's gains, 5000,500,2000 by default
+  --schedule <file>      the disturbances, as for the main window, of which kicks and noise
+                         apply; a kick at 2 s and at 8 s and noise at 14 s by default
+  --latency <secs>       how late the controllers' sensors read, 0 to 5, 0 by default
+  --control-rate <Hz>    push this often, holding the push between, 1 to 1000; every
+                         step by default
+  --encoder-resolution <counts>
+                         read the pole's angle to the nearest of this many counts per
+                         revolution, telling its speed from the counts; exactly by default
+  --track-resolution <cm>
+                         read the cart's position to the nearest this many cm, 0.001 to
+                         10, likewise; exactly by default
+  --duration <secs>      how long they run for, 20 by default
+  --dt <secs>            simulation time step, from 0.0001 to 0.05, 1/120 by default
+  --out <file>           where the table is written as CSV, compare.csv by default";
+
+/// What to run, picked by the first argument.
+pub enum Command {
+    Simulate(Box<Options>),
+    Rng(RngOptions),
+    Harmonograph(ArtOptions),
+    Sand(SandOptions),
+    Parametric(ParametricOptions),
+    MonteCarlo(MonteCarloOptions),
+    Audit(AuditOptions),
+    Resonance(ResonanceOptions),
+    Compare(CompareOptions),
+}
+
+impl Command {
+    /// Parses the process arguments, exiting with the usage on errors.
+    pub fn from_args() -> Self {
+        let mut args = std::env::args().skip(1).peekable();
+        let parsed = match args.peek().map(String::as_str) {
+            Some("rng") => RngOptions::parse(args.skip(1)).map(Command::Rng),
+            Some("harmonograph") => ArtOptions::parse(args.skip(1)).map(Command::Harmonograph),
+            Some("sand") => SandOptions::parse(args.skip(1)).map(Command::Sand),
+            Some("parametric") => ParametricOptions::parse(args.skip(1)).map(Command::Parametric),
+            Some("montecarlo") => MonteCarloOptions::parse(args.skip(1)).map(Command::MonteCarlo),
+            Some("audit") => AuditOptions::parse(args.skip(1)).map(Command::Audit),
+            Some("resonance") => ResonanceOptions::parse(args.skip(1)).map(Command::Resonance),
+            Some("compare") => CompareOptions::parse(args.skip(1)).map(Command::Compare),
+            _ => Options::parse(args).map(|it| Command::Simulate(Box::new(it))),
+        };
+        match parsed {
+            Ok(command) => command,
+            Err(message) => {
+                eprintln!("{}\n{}", message, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+}
+
+/// Command line options.
+pub struct Options {
+    // quiz file replacing the built-in questions
+    pub quiz: Option<String>,
+    // file of disturbances to apply at set times
+    pub schedule: Option<String>,
+    // file of ranges to draw the start from, and the seed to draw it with
+    pub randomize: Option<String>,
+    pub seed: Option<u64>,
+    // where to mirror the HUD as text, for screen readers
+    pub hud_out: Option<String>,
+    pub hud_interval: f64,
+    // where to write the simulated sensor readings, and the encoder's counts per revolution
+    pub sensors_out: Option<String>,
+    pub encoder_resolution: u32,
+    // how the single pendulum's θ is shown and exported
+    pub angles: Convention,
+    // folder every frame is saved to
+    pub record: Option<String>,
+    // controller rumble for each event, events without one don't rumble
+    pub rumble: HashMap<SimEvent, Pulse>,
+    // multicast group of instances that start and reset together
+    pub sync_group: Option<SocketAddrV4>,
+    // address to stream the state to viewers on
+    pub host: Option<SocketAddr>,
+    // host to follow instead of simulating
+    pub view: Option<String>,
+    // external process pushing the single pendulum, and s of simulated
+    // delay before its pushes act, plus up to `jitter` more
+    pub controller: Option<Link>,
+    pub latency: f64,
+    pub jitter: f64,
+    // serial port of a real cart-pole, its baud rate and where its samples
+    // are logged, and cm per count of its cart's encoder
+    pub rig: Option<String>,
+    pub rig_baud: u32,
+    pub rig_log: Option<String>,
+    // a simulated cart-pole beside the rig, and whether it is fit to it
+    pub twin: bool,
+    pub refit: bool,
+    pub track_resolution: f64,
+    // fullscreen, no HUD, quits on any input
+    pub screensaver: bool,
+    // fullscreen, no mouse cursor
+    pub kiosk: bool,
+    // SDL render driver, the default one when none is given
+    pub renderer: Option<String>,
+    // index of the most detailed tier of scenery drawn
+    pub detail: usize,
+    // scenery parts, shown from the start when given
+    pub decor: Option<Decor>,
+    // m/s, for the Doppler tone
+    pub sound_speed: f64,
+    // s, fixed simulation step
+    pub dt: f64,
+    // 1/s, b/m
+    pub damping: f64,
+    // 1/cm, ½·ρ·Cd·A/m
+    pub drag: f64,
+    // rad²/s³, and the seed it's drawn from
+    pub thermal_noise: f64,
+    pub noise_seed: Option<u64>,
+    // cm/s², and its gusts' fraction, kind and s
+    pub wind: f64,
+    pub gust: f64,
+    pub gusting: Gusting,
+    pub gust_period: f64,
+    // rad/s² and rad/s, of A·cos(Ω·t)
+    pub drive: f64,
+    pub drive_frequency: f64,
+    // the second drive's A2 and Ω2
+    pub drive2: f64,
+    pub drive_frequency2: f64,
+    pub integrator: Scheme,
+    // rad/s, what a kick adds to ω
+    pub kick: f64,
+    // kept through changes of length and gravity
+    pub conserve: Conserve,
+    // what swings at the start
+    pub mode: Mode,
+    // cm and arbitrary units, from the top rod down, as many of each
+    pub link_lengths: Vec<f64>,
+    pub link_masses: Vec<f64>,
+    // folder the chain's best traces are saved to
+    pub traces_out: Option<String>,
+    // 1/s², k/m, and cm, of the spring mode
+    pub stiffness: f64,
+    pub rest_length: f64,
+    // cm and rad/s, of the Kapitza mode's shaking pivot
+    pub pivot_depth: f64,
+    pub pivot_frequency: f64,
+    // under the bob of the magnetic mode, evenly round a circle
+    pub magnets: usize,
+    // degrees north, and times faster than real time, of the Foucault mode
+    pub latitude: f64,
+    pub warp: f64,
+    // degrees, of the conical mode's cone
+    pub half_angle: f64,
+    // of the pendulum wave, and s until its pendulums line up again
+    pub wave_pendulums: usize,
+    pub wave_cycle: f64,
+    // k/m of the spring between the coupled pendulums
+    pub coupling: f64,
+    // what swings in the compound mode, and how far from its centre of mass
+    // it hangs, each preset's own distance by default
+    pub body: Body,
+    pub pivot_distance: Option<f64>,
+    // v²/(g·L) the bob on a string is launched with
+    pub launch: f64,
+    // cm the rider on the swing stands up by, and whether they do it by themselves
+    pub pump_depth: f64,
+    pub auto_pump: bool,
+    // the link it was opened with, for what there are no other options for
+    pub start: Option<Snapshot>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            quiz: None,
+            schedule: None,
+            randomize: None,
+            seed: None,
+            hud_out: None,
+            hud_interval: 1.0,
+            sensors_out: None,
+            record: None,
+            encoder_resolution: 4096,
+            angles: Convention::DEFAULT,
+            rumble: SimEvent::ALL
+                .into_iter()
+                .filter_map(|event| Pulse::default_for(event).map(|it| (event, it)))
+                .collect(),
+            sync_group: None,
+            host: None,
+            view: None,
+            controller: None,
+            latency: 0.0,
+            jitter: 0.0,
+            rig: None,
+            rig_baud: 115_200,
+            rig_log: None,
+            twin: false,
+            refit: false,
+            track_resolution: 0.01,
+            screensaver: false,
+            kiosk: false,
+            renderer: None,
+            detail: 0,
+            decor: None,
+            sound_speed: 343.0,
+            dt: 1.0 / 120.0,
+            damping: 0.0,
+            drag: 0.0,
+            thermal_noise: 0.0,
+            noise_seed: None,
+            wind: 0.0,
+            gust: 0.0,
+            gusting: Gusting::Sine,
+            gust_period: 5.0,
+            drive: 0.0,
+            drive_frequency: 1.48,
+            drive2: 0.0,
+            drive_frequency2: 1.48 * GOLDEN,
+            integrator: Scheme::RungeKutta4,
+            kick: 1.0,
+            conserve: Conserve::State,
+            mode: Mode::Single,
+            link_lengths: vec![L / 2.0; 2],
+            link_masses: vec![1.0; 2],
+            traces_out: None,
+            stiffness: 24.5,
+            rest_length: 120.0,
+            pivot_depth: 5.0,
+            pivot_frequency: 120.0,
+            magnets: 3,
+            latitude: 48.85,
+            warp: 1000.0,
+            half_angle: 30.0,
+            wave_pendulums: 15,
+            wave_cycle: 60.0,
+            coupling: 1.0,
+            body: Body::Rod,
+            pivot_distance: None,
+            launch: 3.5,
+            pump_depth: 20.0,
+            auto_pump: false,
+            start: None,
+        }
+    }
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let (mut links, mut lengths, mut masses) = (None, None, None);
+        // the compound mode's, a custom body needing them all at once
+        let (mut shaped, mut inertia, mut mass) = (false, None, 1.0);
+        let (mut coefficient, mut cross_section) = (None, 80.0);
+        // the arguments so far, for a link to check it overrides none
+        let mut given = Vec::new();
+        while let Some(arg) = args.next() {
+            given.push(arg.clone());
+            match arg.as_str() {
+                "--quiz" => options.quiz = Some(value(&mut args, &arg)?),
+                "--schedule" => options.schedule = Some(value(&mut args, &arg)?),
+                "--randomize" => options.randomize = Some(value(&mut args, &arg)?),
+                "--seed" => options.seed = Some(integer(&mut args, &arg)?),
+                "--hud-out" => options.hud_out = Some(value(&mut args, &arg)?),
+                "--hud-interval" => options.hud_interval = positive(&mut args, &arg)?,
+                "--sensors-out" => options.sensors_out = Some(value(&mut args, &arg)?),
+                "--record" => options.record = Some(value(&mut args, &arg)?),
+                "--encoder-resolution" => match integer(&mut args, &arg)? {
+                    n @ 1..=0xffff_ffff => options.encoder_resolution = n as u32,
+                    _ => return Err(format!("{} expects a whole number from 1", arg)),
+                },
+                "--angles" => options.angles = Convention::parse(&value(&mut args, &arg)?)?,
+                "--rumble" => {
+                    let (event, pulse) = rumble(&value(&mut args, &arg)?)?;
+                    match pulse {
+                        Some(pulse) => options.rumble.insert(event, pulse),
+                        None => options.rumble.remove(&event),
+                    };
+                }
+                "--sync-group" => {
+                    let raw = value(&mut args, &arg)?;
+                    match raw.parse::<SocketAddrV4>() {
+                        Ok(group) if group.ip().is_multicast() => options.sync_group = Some(group),
+                        _ => {
+                            return Err(format!(
+                                "--sync-group expects a multicast <ip:port>, got `{}`",
+                                raw
+                            ))
+                        }
+                    }
+                }
+                "--host" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.host = Some(raw.parse().map_err(|_| {
+                        format!("--host expects an <ip:port> to listen on, got `{}`", raw)
+                    })?);
+                }
+                "--view" => options.view = Some(value(&mut args, &arg)?),
+                "--controller" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.controller = Some(Link::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--controller expects udp://<host:port> or tcp://<host:port>, got `{}`",
+                            raw
+                        )
+                    })?);
+                }
+                "--latency" => options.latency = within(&mut args, &arg, &DELAYS)?,
+                "--jitter" => options.jitter = within(&mut args, &arg, &DELAYS)?,
+                "--rig" if cfg!(feature = "serialport") => {
+                    options.rig = Some(value(&mut args, &arg)?)
+                }
+                "--rig" => {
+                    return Err("--rig needs the app built with --features serialport".to_string())
+                }
+                "--rig-baud" => match integer(&mut args, &arg)? {
+                    n @ 1..=0xffff_ffff => options.rig_baud = n as u32,
+                    _ => return Err(format!("{} expects a whole number from 1", arg)),
+                },
+                "--rig-log" => options.rig_log = Some(value(&mut args, &arg)?),
+                "--twin" => options.twin = true,
+                "--refit" => {
+                    options.twin = true;
+                    options.refit = true;
+                }
+                "--track-resolution" => {
+                    options.track_resolution = within(&mut args, &arg, &TRACK_RESOLUTIONS)?
+                }
+                "--decor" => options.decor = Some(Decor::parse(&value(&mut args, &arg)?)?),
+                "--sound-speed" => options.sound_speed = positive(&mut args, &arg)?,
+                "--dt" => options.dt = within(&mut args, &arg, &STEPS)?,
+                "--damping" => options.damping = within(&mut args, &arg, &DAMPINGS)?,
+                "--drag-coefficient" => {
+                    coefficient = Some(within(&mut args, &arg, &DRAG_COEFFICIENTS)?)
+                }
+                "--cross-section" => cross_section = within(&mut args, &arg, &CROSS_SECTIONS)?,
+                "--thermal-noise" => options.thermal_noise = within(&mut args, &arg, &INTENSITIES)?,
+                "--noise-seed" => options.noise_seed = Some(integer(&mut args, &arg)?),
+                // m/s² -> cm/s²
+                "--wind" => {
+                    let range = WINDS.start() / 100.0..=WINDS.end() / 100.0;
+                    options.wind = within(&mut args, &arg, &range)? * 100.0
+                }
+                "--gust" => options.gust = within(&mut args, &arg, &GUSTS)?,
+                "--gusting" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.gusting = Gusting::parse(&raw)
+                        .ok_or_else(|| format!("--gusting expects sine or noise, got `{}`", raw))?;
+                }
+                "--gust-period" => options.gust_period = within(&mut args, &arg, &GUST_PERIODS)?,
+                "--drive" => options.drive = within(&mut args, &arg, &DRIVES)?,
+                "--drive-frequency" => {
+                    options.drive_frequency = within(&mut args, &arg, &DRIVE_FREQUENCIES)?
+                }
+                "--drive2" => options.drive2 = within(&mut args, &arg, &DRIVES)?,
+                "--drive-frequency2" => {
+                    options.drive_frequency2 = within(&mut args, &arg, &DRIVE_FREQUENCIES)?
+                }
+                "--integrator" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.integrator = Scheme::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--integrator expects euler, semi-implicit, rk4 or verlet, got `{}`",
+                            raw
+                        )
+                    })?;
+                }
+                "--kick" => options.kick = positive(&mut args, &arg)?,
+                "--conserve" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.conserve = Conserve::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--conserve expects state, energy or momentum, got `{}`",
+                            raw
+                        )
+                    })?;
+                }
+                "--mode" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.mode = Mode::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--mode expects single, chain, spring, spherical, kapitza, cart, magnetic, foucault, conical, wave, coupled, compound, string or swing, got `{}`",
+                            raw
+                        )
+                    })?;
+                }
+                "--links" => match integer(&mut args, &arg)? {
+                    n @ 1..=MAX_LINKS => links = Some(n as usize),
+                    n => return Err(format!("--links expects 1 to {}, got {}", MAX_LINKS, n)),
+                },
+                "--link-lengths" => lengths = Some(list(&mut args, &arg)?),
+                "--link-masses" => masses = Some(list(&mut args, &arg)?),
+                "--traces-out" => options.traces_out = Some(value(&mut args, &arg)?),
+                "--stiffness" => options.stiffness = within(&mut args, &arg, &STIFFNESSES)?,
+                "--rest-length" => options.rest_length = within(&mut args, &arg, &REST_LENGTHS)?,
+                "--pivot-depth" => options.pivot_depth = within(&mut args, &arg, &PIVOT_DEPTHS)?,
+                "--pivot-frequency" => {
+                    options.pivot_frequency = within(&mut args, &arg, &PIVOT_FREQUENCIES)?
+                }
+                "--magnets" => match integer(&mut args, &arg)? {
+                    n @ 2..=MAX_MAGNETS => options.magnets = n as usize,
+                    n => return Err(format!("--magnets expects 2 to {}, got {}", MAX_MAGNETS, n)),
+                },
+                "--latitude" => options.latitude = within(&mut args, &arg, &LATITUDES)?,
+                "--warp" => options.warp = within(&mut args, &arg, &WARPS)?,
+                "--half-angle" => options.half_angle = within(&mut args, &arg, &HALF_ANGLES)?,
+                "--wave-pendulums" => match integer(&mut args, &arg)? {
+                    n if WAVE_PENDULUMS.contains(&n) => options.wave_pendulums = n as usize,
+                    n => {
+                        return Err(format!(
+                            "--wave-pendulums expects {} to {}, got {}",
+                            WAVE_PENDULUMS.start(),
+                            WAVE_PENDULUMS.end(),
+                            n
+                        ))
+                    }
+                },
+                "--wave-cycle" => options.wave_cycle = within(&mut args, &arg, &WAVE_CYCLES)?,
+                "--coupling" => options.coupling = within(&mut args, &arg, &COUPLINGS)?,
+                "--body" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.body = Body::parse(&raw).ok_or_else(|| {
+                        format!("--body expects rod, disk, ring or bob, got `{}`", raw)
+                    })?;
+                    shaped = true;
+                }
+                "--inertia" => inertia = Some(positive(&mut args, &arg)?),
+                "--mass" => mass = positive(&mut args, &arg)?,
+                "--launch" => options.launch = within(&mut args, &arg, &LAUNCHES)?,
+                "--pump-depth" => options.pump_depth = within(&mut args, &arg, &PUMP_DEPTHS)?,
+                "--auto-pump" => options.auto_pump = true,
+                "--pivot-distance" => {
+                    options.pivot_distance = Some(within(&mut args, &arg, &PIVOT_DISTANCES)?)
+                }
+                "--screensaver" | "/s" | "/S" | "-root" => options.screensaver = true,
+                // Windows asking for the settings dialog and the preview pane, neither of which exists
+                flag if screensaver_flag(flag) == Some('c') => {
+                    println!("pendulum has no screensaver settings");
+                    process::exit(0);
+                }
+                flag if screensaver_flag(flag) == Some('p') => process::exit(0),
+                "--kiosk" => options.kiosk = true,
+                "--renderer" => options.renderer = Some(value(&mut args, &arg)?),
+                "--detail" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.detail =
+                        TIERS.iter().position(|it| it.name == raw).ok_or_else(|| {
+                            format!("--detail expects full, reduced or low, got `{}`", raw)
+                        })?;
+                }
+                "--from-url" => options.open(&value(&mut args, &arg)?, &given)?,
+                // how the desktop hands over a link the app is registered for
+                link if link.starts_with("pendulum://") => options.open(link, &given)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        if options.view.is_some() && (options.host.is_some() || options.sync_group.is_some()) {
+            return Err("--view can't be combined with --host or --sync-group".to_string());
+        }
+        // the rig is shown in the cart-pole mode, unless another was asked for
+        if options.rig.is_some() && options.mode == Mode::Single {
+            options.mode = Mode::Cart;
+        }
+        if options.twin && options.rig.is_none() {
+            return Err("--twin and --refit need a --rig".to_string());
+        }
+        if options.view.is_some() && options.rig.is_some() {
+            return Err("--view can't be combined with --rig".to_string());
+        }
+        if options.view.is_some() && options.controller.is_some() {
+            return Err("--view can't be combined with --controller".to_string());
+        }
+        if options.view.is_some() && options.schedule.is_some() {
+            return Err("--view can't be combined with --schedule".to_string());
+        }
+        if options.view.is_some() && options.sensors_out.is_some() {
+            return Err("--view can't be combined with --sensors-out".to_string());
+        }
+        if options.view.is_some() && options.mode != Mode::Single {
+            return Err("--view can't be combined with --mode".to_string());
+        }
+        // the count comes from whichever of them is given
+        let n = links
+            .or(lengths.as_ref().map(Vec::len))
+            .or(masses.as_ref().map(Vec::len))
+            .unwrap_or(2);
+        options.link_lengths = lengths.unwrap_or_else(|| vec![L / n as f64; n]);
+        options.link_masses = masses.unwrap_or_else(|| vec![1.0; n]);
+        for (name, values) in [
+            ("--link-lengths", &options.link_lengths),
+            ("--link-masses", &options.link_masses),
+        ] {
+            if values.len() != n {
+                return Err(format!("{} expects {} values, one per link", name, n));
+            }
+        }
+        if n as u64 > MAX_LINKS {
+            return Err(format!("the chain can have {} links at most", MAX_LINKS));
+        }
+        // a longer chain wouldn't fit in the window
+        if options.link_lengths.iter().sum::<f64>() > L + 1e-9 {
+            return Err(format!("--link-lengths add up to more than {} cm", L));
+        }
+        if let Some(inertia) = inertia {
+            if shaped {
+                return Err("--inertia can't be combined with --body".to_string());
+            }
+            // kg·m² -> kg·cm², less the part of a point mass at the pivot distance
+            let distance = options
+                .pivot_distance
+                .unwrap_or(Body::Custom(0.0).pivot_distance());
+            let gyration = inertia * 1e4 / mass - distance * distance;
+            if gyration < 0.0 {
+                return Err(format!(
+                    "--inertia must be at least --mass·d² = {} kg·m², a point mass {} cm from the pivot",
+                    mass * distance * distance / 1e4,
+                    distance
+                ));
+            }
+            options.body = Body::Custom(gyration.sqrt());
+        }
+        if let Some(coefficient) = coefficient {
+            options.drag = physics::drag(coefficient, cross_section);
+        }
+        Ok(options)
+    }
+
+    /// What a fresh start under these options swings from, which a
+    /// snapshot leaving fields out takes them from.
+    pub fn fresh(&self) -> Snapshot {
+        Snapshot {
+            mode: self.mode,
+            scheme: self.integrator,
+            params: Params {
+                length: L,
+                gravity: G,
+                damping: self.damping,
+                drag: self.drag,
+                wind: self.wind,
+                drive: self.drive,
+                drive_frequency: self.drive_frequency,
+                drive2: self.drive2,
+                drive_frequency2: self.drive_frequency2,
+                torque: 0.0,
+            },
+            gust: self.gust,
+            gusting: self.gusting,
+            gust_period: self.gust_period,
+            time: 0.0,
+            theta: THETA_0,
+            w: 0.0,
+        }
+    }
+
+    // what of a pendulum:// link the options hold, the rest kept for the app,
+    // refusing options `given` before it that it would override
+    fn open(&mut self, link: &str, given: &[String]) -> Result<(), String> {
+        if !link.starts_with("pendulum://") {
+            return Err(format!(
+                "--from-url expects a pendulum:// link, got `{}`",
+                link
+            ));
+        }
+        let overridden: Vec<&str> = LINKED
+            .into_iter()
+            .filter(|it| given.iter().any(|arg| arg == it))
+            .collect();
+        if !overridden.is_empty() {
+            return Err(format!(
+                "{} before a pendulum:// link would be overridden by it; give them after it",
+                overridden.join(", ")
+            ));
+        }
+        let fresh = self.fresh();
+        let start =
+            Snapshot::parse(link, &fresh).map_err(|error| format!("{}: {}", link, error))?;
+        self.mode = start.mode;
+        self.integrator = start.scheme;
+        self.damping = start.params.damping;
+        self.drag = start.params.drag;
+        self.drive = start.params.drive;
+        self.drive_frequency = start.params.drive_frequency;
+        self.drive2 = start.params.drive2;
+        self.drive_frequency2 = start.params.drive_frequency2;
+        self.wind = start.params.wind;
+        self.gust = start.gust;
+        self.gusting = start.gusting;
+        self.gust_period = start.gust_period;
+        self.start = Some(start);
+        Ok(())
+    }
+}
+
+/// Options of the `rng` subcommand.
+#[derive(Default)]
+pub struct RngOptions {
+    // bytes to write, endless without
+    pub bytes: Option<u64>,
+    pub hex: bool,
+    // window instead of stdout
+    pub show: bool,
+    pub seed: Option<u64>,
+}
+
+impl RngOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = RngOptions::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bytes" => options.bytes = Some(integer(&mut args, &arg)?),
+                "--hex" => options.hex = true,
+                "--seed" => options.seed = Some(integer(&mut args, &arg)?),
+                "--show" => options.show = true,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `harmonograph` subcommand.
+pub struct ArtOptions {
+    pub lissajous: bool,
+    pub seed: Option<u64>,
+    // SVG export path
+    pub svg: String,
+}
+
+impl ArtOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = ArtOptions {
+            lissajous: false,
+            seed: None,
+            svg: "harmonograph.svg".to_string(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--lissajous" => options.lissajous = true,
+                "--seed" => options.seed = Some(integer(&mut args, &arg)?),
+                "--svg" => options.svg = value(&mut args, &arg)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `sand` subcommand.
+pub struct SandOptions {
+    // two planar swings instead of a spherical pendulum
+    pub planar: bool,
+    pub seed: Option<u64>,
+    // BMP export path
+    pub out: String,
+}
+
+impl SandOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = SandOptions {
+            planar: false,
+            seed: None,
+            out: "sand.bmp".to_string(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--2d" => options.planar = true,
+                "--seed" => options.seed = Some(integer(&mut args, &arg)?),
+                "--out" => options.out = value(&mut args, &arg)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `parametric` subcommand.
+pub struct ParametricOptions {
+    // Ω/ω0
+    pub ratio: f64,
+    // cm
+    pub depth: f64,
+    // CSV export path
+    pub out: String,
+}
+
+impl ParametricOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = ParametricOptions {
+            ratio: 2.0,
+            depth: 15.0,
+            out: "tongues.csv".to_string(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ratio" => options.ratio = positive(&mut args, &arg)?,
+                "--depth" => options.depth = positive(&mut args, &arg)?,
+                "--out" => options.out = value(&mut args, &arg)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `montecarlo` subcommand.
+pub struct MonteCarloOptions {
+    // file of constraints on the start
+    pub randomize: String,
+    pub runs: u64,
+    // s, of each run, and of each step
+    pub duration: f64,
+    pub dt: f64,
+    pub integrator: Scheme,
+    pub seed: Option<u64>,
+    // runs of the double pendulum instead, for how often its second rod flips
+    pub double: bool,
+    // files to write how far the runs have got to, and to carry on from
+    pub checkpoint: Option<String>,
+    pub resume_from: Option<String>,
+}
+
+impl MonteCarloOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut randomize = None;
+        let mut options = MonteCarloOptions {
+            randomize: String::new(),
+            runs: 1000,
+            duration: 20.0,
+            dt: 1.0 / 120.0,
+            integrator: Scheme::RungeKutta4,
+            seed: None,
+            double: false,
+            checkpoint: None,
+            resume_from: None,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--randomize" => randomize = Some(value(&mut args, &arg)?),
+                "--double" => options.double = true,
+                "--runs" => match integer(&mut args, &arg)? {
+                    0 => return Err("--runs expects a whole number from 1".to_string()),
+                    n => options.runs = n,
+                },
+                "--duration" => options.duration = positive(&mut args, &arg)?,
+                "--dt" => options.dt = within(&mut args, &arg, &STEPS)?,
+                "--integrator" => {
+                    let raw = value(&mut args, &arg)?;
+                    options.integrator = Scheme::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--integrator expects euler, semi-implicit, rk4 or verlet, got `{}`",
+                            raw
+                        )
+                    })?;
+                }
+                "--seed" => options.seed = Some(integer(&mut args, &arg)?),
+                "--checkpoint" => options.checkpoint = Some(value(&mut args, &arg)?),
+                "--resume-from" => options.resume_from = Some(value(&mut args, &arg)?),
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        options.randomize = randomize.ok_or("montecarlo needs --randomize <file>")?;
+        Ok(options)
+    }
+}
+
+/// Options of the `audit` subcommand.
+pub struct AuditOptions {
+    // s, of the steps, shortest first, and of each run
+    pub dts: Vec<f64>,
+    pub integrators: Vec<Scheme>,
+    pub duration: f64,
+    // degrees, let go from at rest
+    pub amplitude: f64,
+}
+
+impl AuditOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = AuditOptions {
+            dts: vec![
+                1.0 / 480.0,
+                1.0 / 240.0,
+                1.0 / 120.0,
+                1.0 / 60.0,
+                1.0 / 30.0,
+            ],
+            integrators: Scheme::ALL.to_vec(),
+            duration: 60.0,
+            amplitude: -THETA_0.to_degrees(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dt" => {
+                    let mut dts = list(&mut args, &arg)?;
+                    if let Some(dt) = dts.iter().find(|it| !STEPS.contains(it)) {
+                        return Err(format!(
+                            "--dt expects steps from {} to {}, got `{}`",
+                            STEPS.start(),
+                            STEPS.end(),
+                            dt
+                        ));
+                    }
+                    dts.sort_by(f64::total_cmp);
+                    dts.dedup();
+                    options.dts = dts;
+                }
+                "--integrator" => {
+                    let raw = value(&mut args, &arg)?;
+                    let scheme = Scheme::parse(&raw).ok_or_else(|| {
+                        format!(
+                            "--integrator expects euler, semi-implicit, rk4 or verlet, got `{}`",
+                            raw
+                        )
+                    })?;
+                    options.integrators = vec![scheme];
+                }
+                "--duration" => options.duration = positive(&mut args, &arg)?,
+                "--amplitude" => options.amplitude = within(&mut args, &arg, &AMPLITUDES)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `resonance` subcommand.
+pub struct ResonanceOptions {
+    // 1/s and rad/s², of the swing
+    pub damping: f64,
+    pub drive: f64,
+    // Ω/ω0, swept from one to the other
+    pub from: f64,
+    pub to: f64,
+    pub steps: usize,
+    // drive periods
+    pub settle: f64,
+    // s
+    pub dt: f64,
+    // sweeping back down again after
+    pub hysteresis: bool,
+    // where the CSV and the plot go
+    pub out: String,
+    pub svg: String,
+}
+
+impl ResonanceOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = ResonanceOptions {
+            damping: 0.25,
+            drive: 0.1,
+            from: 0.5,
+            to: 1.5,
+            steps: 101,
+            settle: 40.0,
+            dt: 1.0 / 120.0,
+            hysteresis: false,
+            out: "resonance.csv".to_string(),
+            svg: "resonance.svg".to_string(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--damping" => options.damping = within(&mut args, &arg, &SWEEP_DAMPINGS)?,
+                "--drive" => options.drive = within(&mut args, &arg, &DRIVES)?,
+                "--from" => options.from = within(&mut args, &arg, &RATIOS)?,
+                "--to" => options.to = within(&mut args, &arg, &RATIOS)?,
+                "--steps" => match integer(&mut args, &arg)? {
+                    0 => return Err("--steps expects at least one frequency".to_string()),
+                    steps => options.steps = steps as usize,
+                },
+                "--settle" => options.settle = positive(&mut args, &arg)?,
+                "--dt" => options.dt = within(&mut args, &arg, &STEPS)?,
+                "--hysteresis" => options.hysteresis = true,
+                "--out" => options.out = value(&mut args, &arg)?,
+                "--svg" => options.svg = value(&mut args, &arg)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        if options.from >= options.to {
+            return Err(format!(
+                "--to expects a frequency above --from's {}, got {}",
+                options.from, options.to
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// Options of the `compare` subcommand.
+pub struct CompareOptions {
+    pub controllers: [Law; 2],
+    // Kp, Ki and Kd of the PID
+    pub gains: [f64; 3],
+    // the disturbances' file, built-in ones without
+    pub schedule: Option<String>,
+    // the embedded hardware both run on
+    pub conditions: Conditions,
+    // s, of the run and of a step
+    pub duration: f64,
+    pub dt: f64,
+    // where the CSV goes
+    pub out: String,
+}
+
+impl CompareOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = CompareOptions {
+            controllers: [Law::Pid, Law::Lqr],
+            gains: GAINS,
+            schedule: None,
+            conditions: Conditions::default(),
+            duration: 20.0,
+            dt: 1.0 / 120.0,
+            out: "compare.csv".to_string(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--controllers" => {
+                    let raw = value(&mut args, &arg)?;
+                    let laws: Option<Vec<Law>> =
+                        raw.split(',').map(|it| Law::parse(it.trim())).collect();
+                    options.controllers = match laws.as_deref() {
+                        Some(&[a, b]) => [a, b],
+                        _ => {
+                            return Err(format!(
+                                "--controllers expects two of pid, lqr or mpc separated by a comma, got `{}`",
+                                raw
+                            ))
+                        }
+                    };
+                }
+                "--gains" => {
+                    let raw = value(&mut args, &arg)?;
+                    let gains: Option<Vec<f64>> = raw
+                        .split(',')
+                        .map(|it| {
+                            it.trim()
+                                .parse::<f64>()
+                                .ok()
+                                .filter(|it| *it >= 0.0 && it.is_finite())
+                        })
+                        .collect();
+                    options.gains = match gains.as_deref() {
+                        Some(&[kp, ki, kd]) => [kp, ki, kd],
+                        _ => {
+                            return Err(format!(
+                            "--gains expects Kp, Ki and Kd from 0 up separated by commas, got `{}`",
+                            raw
+                        ))
+                        }
+                    };
+                }
+                "--schedule" => options.schedule = Some(value(&mut args, &arg)?),
+                "--latency" => options.conditions.latency = within(&mut args, &arg, &DELAYS)?,
+                "--control-rate" => {
+                    options.conditions.rate = Some(within(&mut args, &arg, &CONTROL_RATES)?)
+                }
+                "--encoder-resolution" => match integer(&mut args, &arg)? {
+                    n @ 1..=0xffff_ffff => options.conditions.counts = Some(n as u32),
+                    _ => return Err(format!("{} expects a whole number from 1", arg)),
+                },
+                "--track-resolution" => {
+                    options.conditions.track = Some(within(&mut args, &arg, &TRACK_RESOLUTIONS)?)
+                }
+                "--duration" => options.duration = positive(&mut args, &arg)?,
+                "--dt" => options.dt = within(&mut args, &arg, &STEPS)?,
+                "--out" => options.out = value(&mut args, &arg)?,
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", name))
+}
+
+fn positive(args: &mut impl Iterator<Item = String>, name: &str) -> Result<f64, String> {
+    let raw = value(args, name)?;
+    match raw.parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => Ok(number),
+        _ => Err(format!("{} expects a positive number, got `{}`", name, raw)),
+    }
+}
+
+// a number in `range`, which a NaN never is
+fn within(
+    args: &mut impl Iterator<Item = String>,
+    name: &str,
+    range: &RangeInclusive<f64>,
+) -> Result<f64, String> {
+    let raw = value(args, name)?;
+    match raw.parse::<f64>() {
+        Ok(number) if range.contains(&number) => Ok(number),
+        _ => Err(format!(
+            "{} expects a number from {} to {}, got `{}`",
+            name,
+            range.start(),
+            range.end(),
+            raw
+        )),
+    }
+}
+
+// positive numbers separated by commas
+fn list(args: &mut impl Iterator<Item = String>, name: &str) -> Result<Vec<f64>, String> {
+    let raw = value(args, name)?;
+    raw.split(',')
+        .map(|it| {
+            it.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|it| *it > 0.0 && it.is_finite())
+        })
+        .collect::<Option<Vec<f64>>>()
+        .ok_or_else(|| {
+            format!(
+                "{} expects positive numbers separated by commas, got `{}`",
+                name, raw
+            )
+        })
+}
+
+fn integer(args: &mut impl Iterator<Item = String>, name: &str) -> Result<u64, String> {
+    let raw = value(args, name)?;
+    raw.parse()
+        .map_err(|_| format!("{} expects a whole number, got `{}`", name, raw))
+}
+
+// the letter of a Windows screensaver flag, `/c`, `/c:<HWND>` or upper case, the
+// window handle also coming as the next argument instead
+fn screensaver_flag(arg: &str) -> Option<char> {
+    let rest = arg.strip_prefix('/')?;
+    let mut chars = rest.chars();
+    let letter = chars.next()?.to_ascii_lowercase();
+    match chars.next() {
+        None | Some(':') => Some(letter),
+        _ => None,
+    }
+}
+
+// `<event>=<strength>,<ms>` or `<event>=off`
+fn rumble(raw: &str) -> Result<(SimEvent, Option<Pulse>), String> {
+    let invalid = || format!("--rumble expects <event>=<strength>,<ms>, got `{}`", raw);
+    let (name, setting) = raw.split_once('=').ok_or_else(invalid)?;
+    let event = SimEvent::parse(name).ok_or_else(|| format!("unknown event `{}`", name))?;
+    if setting == "off" {
+        return Ok((event, None));
+    }
+    let (strength, duration) = setting.split_once(',').ok_or_else(invalid)?;
+    match (strength.parse::<f64>(), duration.parse::<u32>()) {
+        (Ok(strength), Ok(duration_ms)) if (0.0..=1.0).contains(&strength) => Ok((
+            event,
+            Some(Pulse {
+                strength,
+                duration_ms,
+            }),
+        )),
+        _ => Err(invalid()),
+    }
+}
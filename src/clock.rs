@@ -0,0 +1,103 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use chrono::{Local, Timelike};
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::physics::{self, Params};
+
+const DAY: f64 = 24.0 * 3600.0;
+// a disciplined clock corrects at most this fraction of a beat per beat
+const MAX_SLEW: f64 = 0.1;
+// and removes this fraction of its error per beat
+const GAIN: f64 = 0.05;
+
+/// Clock face driven by the pendulum: every swing through the bottom releases
+/// the escapement and moves the hands on by half a period.
+///
+/// The simulation's own error makes it drift from the wall clock. A disciplined
+/// clock slews back towards it a little on every beat instead of jumping.
+pub struct PendulumClock {
+    // seconds since midnight on the face
+    shown: f64,
+    // seconds the hands move per beat
+    beat: f64,
+    pub disciplined: bool,
+}
+
+impl PendulumClock {
+    /// Sets the face to the local time, with beats lasting half the period of
+    /// the pendulum's current swing.
+    pub fn new(params: &Params<f64>, theta: f64, w: f64, disciplined: bool) -> Self {
+        let amplitude = physics::amplitude(params, theta, w);
+        PendulumClock {
+            shown: wall_clock(),
+            beat: physics::period(params, amplitude) / 2.0,
+            disciplined,
+        }
+    }
+
+    // one swing of the pendulum through the bottom
+    pub fn tick(&mut self) {
+        let mut advance = self.beat;
+        if self.disciplined {
+            advance -= (self.error() * GAIN).clamp(-MAX_SLEW * self.beat, MAX_SLEW * self.beat);
+        }
+        self.shown = (self.shown + advance).rem_euclid(DAY);
+    }
+
+    /// How far ahead of the wall clock the face is, in s.
+    pub fn error(&self) -> f64 {
+        let error = (self.shown - wall_clock()).rem_euclid(DAY);
+        if error > DAY / 2.0 {
+            error - DAY
+        } else {
+            error
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut WindowCanvas, center: (i16, i16), radius: i16) {
+        canvas
+            .filled_circle(center.0, center.1, radius, Color::RGB(250, 250, 245))
+            .expect("Unable to draw circle");
+        canvas
+            .aa_circle(center.0, center.1, radius, Color::BLACK)
+            .expect("Unable to draw circle");
+        for hour in 0..12 {
+            let inner = if hour % 3 == 0 { 0.8 } else { 0.88 };
+            let (x0, y0) = point(center, radius as f64 * inner, hour as f64 / 12.0);
+            let (x1, y1) = point(center, radius as f64, hour as f64 / 12.0);
+            canvas
+                .aa_line(x0, y0, x1, y1, Color::BLACK)
+                .expect("Unable to draw line");
+        }
+        // hour, minute and second hands, as a fraction of a turn
+        let hands = [
+            (self.shown / (12.0 * 3600.0), 0.5, Color::BLACK),
+            (self.shown / 3600.0, 0.8, Color::BLACK),
+            (self.shown / 60.0, 0.9, Color::RED),
+        ];
+        for (turns, length, color) in hands {
+            let (x, y) = point(center, radius as f64 * length, turns.fract());
+            canvas
+                .aa_line(center.0, center.1, x, y, color)
+                .expect("Unable to draw line");
+        }
+    }
+}
+
+// point at `distance` from the center, `turns` clockwise from 12 o'clock
+fn point(center: (i16, i16), distance: f64, turns: f64) -> (i16, i16) {
+    let angle = turns * TAU - FRAC_PI_2;
+    (
+        center.0 + (angle.cos() * distance).round() as i16,
+        center.1 + (angle.sin() * distance).round() as i16,
+    )
+}
+
+// local time in seconds since midnight
+fn wall_clock() -> f64 {
+    let now = Local::now();
+    now.num_seconds_from_midnight() as f64 + now.nanosecond() as f64 / 1e9
+}
@@ -0,0 +1,251 @@
+use std::f64::consts::FRAC_PI_2;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+use crate::cart::{self, TILT};
+use crate::cli::CompareOptions;
+use crate::control::{Conditions, Controller, Embedded, Law, MAX_CONTROL};
+use crate::events;
+use crate::schedule::{Disturbance, Schedule};
+
+// rad from upright the pole counts as settled within
+const SETTLED: f64 = 0.01;
+// what the cart-poles are put through without a --schedule
+const DISTURBANCES: &str = "2 kick 0.5\n8 kick -1\n14 noise 20 2\n";
+
+/// How one controller coped with one disturbance, up to the next.
+struct Response {
+    // s, since the disturbance, past which the pole stayed settled; none
+    // if it hadn't by the next
+    settling: Option<f64>,
+    // rad, the furthest from upright, and past upright the other way
+    peak: f64,
+    overshoot: f64,
+    // the side of upright it first went to, 0 until it's left the band
+    side: f64,
+    // cm²/s³, the push squared summed over the time, and the most of it
+    effort: f64,
+    push: f64,
+}
+
+impl Response {
+    fn new() -> Self {
+        Response {
+            settling: Some(0.0),
+            peak: 0.0,
+            overshoot: 0.0,
+            side: 0.0,
+            effort: 0.0,
+            push: 0.0,
+        }
+    }
+}
+
+/// One of the two, its cart-pole and how it has done so far.
+struct Contender {
+    law: Law,
+    controller: Box<dyn Controller>,
+    // cm and cm/s of the cart, θ from upright and ω
+    state: [f64; 4],
+    responses: Vec<Response>,
+    // s, when the pole fell past horizontal, after which it is left lying
+    fell: Option<f64>,
+}
+
+/// Runs the `compare` subcommand: two cart-poles side by side, each balanced
+/// by one of the `--controllers`, both started from the same tilt and put
+/// through the same disturbances at the same times, from `--schedule`. The
+/// table on stdout gives how each coped with each disturbance: how long it
+/// took to settle, how far the pole went and overshot, and the effort of the
+/// pushes, and is written to `--out` as CSV.
+pub fn run(options: &CompareOptions) {
+    let (source, name) = match &options.schedule {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(source) => (source, path.as_str()),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            }
+        },
+        None => (DISTURBANCES.to_string(), "built-in disturbances"),
+    };
+    let mut schedule = Schedule::parse(&source, name).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let mut contenders: Vec<Contender> = options
+        .controllers
+        .iter()
+        .map(|&law| Contender {
+            law,
+            controller: Box::new(Embedded::new(
+                law.build(options.gains, options.conditions.period(options.dt)),
+                options.conditions,
+            )),
+            state: [0.0, 0.0, TILT, 0.0],
+            responses: vec![Response::new()],
+            fell: None,
+        })
+        .collect();
+    // s each disturbance came at, and what it was
+    let mut disturbances = vec![(0.0, format!("tilt {} rad", TILT))];
+    let steps = (options.duration / options.dt).round() as usize;
+    for i in 0..steps {
+        let time = i as f64 * options.dt;
+        let mut dw = 0.0;
+        for disturbance in schedule.due(time) {
+            // the cart-poles keep their length and gravity
+            let what = match disturbance {
+                Disturbance::Kick(kick) => {
+                    dw += kick;
+                    format!("kick {} rad/s", kick)
+                }
+                Disturbance::Noise { strength, duration } => {
+                    format!("noise {} rad/s² for {} s", strength, duration)
+                }
+                Disturbance::Length(_) | Disturbance::Gravity(_) => continue,
+            };
+            disturbances.push((time, what));
+            for contender in &mut contenders {
+                contender.responses.push(Response::new());
+            }
+        }
+        // the same noise for both
+        dw += schedule.noise(time, options.dt).unwrap_or(0.0);
+        let since = time - disturbances.last().map_or(0.0, |it| it.0);
+        for contender in &mut contenders {
+            if contender.fell.is_some() {
+                continue;
+            }
+            contender.state[3] += dw;
+            let push = contender
+                .controller
+                .control(&contender.state, options.dt)
+                .clamp(-MAX_CONTROL, MAX_CONTROL);
+            cart::advance(&mut contender.state, push, options.dt);
+            let theta = events::wrap(contender.state[2]);
+            if theta.abs() > FRAC_PI_2 {
+                contender.fell = Some(time + options.dt);
+            }
+            let response = contender.responses.last_mut().unwrap();
+            response.peak = response.peak.max(theta.abs());
+            response.effort += push * push * options.dt;
+            response.push = response.push.max(push.abs());
+            if theta.abs() > SETTLED {
+                if response.side == 0.0 {
+                    response.side = theta.signum();
+                }
+                response.settling = None;
+            } else if response.settling.is_none() {
+                response.settling = Some(since + options.dt);
+            }
+            response.overshoot = response.overshoot.max(-response.side * theta);
+        }
+    }
+
+    println!(
+        "{} against {} on the cart-pole over {} s, settled within {} rad of upright",
+        contenders[0].law.name(),
+        contenders[1].law.name(),
+        options.duration,
+        SETTLED
+    );
+    if let Some(conditions) = describe(&options.conditions) {
+        println!("both on embedded hardware: {}", conditions);
+    }
+    println!();
+    println!(
+        "{:<32} {:<10} {:>11}  {:>10}  {:>15}  {:>16}  {:>17}",
+        "disturbance",
+        "controller",
+        "settled (s)",
+        "peak (rad)",
+        "overshoot (rad)",
+        "rms push (cm/s²)",
+        "peak push (cm/s²)"
+    );
+    let end = |i: usize| disturbances.get(i + 1).map_or(options.duration, |it| it.0);
+    for (i, (at, what)) in disturbances.iter().enumerate() {
+        for contender in &contenders {
+            let Some(response) = contender.responses.get(i) else {
+                continue;
+            };
+            let settled = match (contender.fell, response.settling) {
+                (Some(fell), _) if fell <= end(i) => format!("fell at {:.2}", fell),
+                (_, Some(settling)) => format!("{:.2}", settling),
+                (_, None) => "not yet".to_string(),
+            };
+            println!(
+                "{:<32} {:<10} {:>11}  {:>10.4}  {:>15.4}  {:>16.1}  {:>17.1}",
+                format!("{} at {} s", what, at),
+                contender.law.name(),
+                settled,
+                response.peak,
+                response.overshoot,
+                (response.effort / (end(i) - at).max(f64::EPSILON)).sqrt(),
+                response.push
+            );
+        }
+    }
+    match export(&options.out, &disturbances, &contenders, end) {
+        Ok(()) => println!("Saved {}", options.out),
+        Err(error) => eprintln!("Unable to write {}: {}", options.out, error),
+    }
+}
+
+// what the controllers run under, if anything but ideal
+fn describe(conditions: &Conditions) -> Option<String> {
+    let mut parts = Vec::new();
+    if conditions.latency > 0.0 {
+        parts.push(format!("sensors {} s late", conditions.latency));
+    }
+    if let Some(rate) = conditions.rate {
+        parts.push(format!("pushing at {} Hz", rate));
+    }
+    if let Some(counts) = conditions.counts {
+        parts.push(format!("the pole read to 1/{} of a turn", counts));
+    }
+    if let Some(track) = conditions.track {
+        parts.push(format!("the cart read to {} cm", track));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+fn export(
+    path: &str,
+    disturbances: &[(f64, String)],
+    contenders: &[Contender],
+    end: impl Fn(usize) -> f64,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(
+        out,
+        "time_s,disturbance,controller,settling_s,peak_rad,overshoot_rad,rms_push_cm_s2,peak_push_cm_s2,fell_s"
+    )?;
+    for (i, (at, what)) in disturbances.iter().enumerate() {
+        for contender in contenders {
+            let Some(response) = contender.responses.get(i) else {
+                continue;
+            };
+            let fell = contender.fell.filter(|it| *it <= end(i));
+            writeln!(
+                out,
+                "{},{},{},{},{:.6},{:.6},{:.3},{:.3},{}",
+                at,
+                what,
+                contender.law.name(),
+                response
+                    .settling
+                    .filter(|_| fell.is_none())
+                    .map_or(String::new(), |it| format!("{:.4}", it)),
+                response.peak,
+                response.overshoot,
+                (response.effort / (end(i) - at).max(f64::EPSILON)).sqrt(),
+                response.push,
+                fell.map_or(String::new(), |it| format!("{:.4}", it))
+            )?;
+        }
+    }
+    out.flush()
+}
@@ -0,0 +1,275 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::mode::SUBSTEPS;
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm, of the rod and of the disk's and the ring's radius
+const ROD: f64 = 200.0;
+const RADIUS: f64 = 40.0;
+// rad it is let go from
+const TILT: f64 = 0.3;
+// cm of the body that fit below the pivot at full size, further ones drawn smaller
+const REACH: f64 = 200.0;
+// cm the pivot is moved by at a time
+const SHIFT: f64 = 5.0;
+/// How far from the centre of mass the pivot can be, in cm.
+pub const PIVOT_DISTANCES: RangeInclusive<f64> = 1.0..=200.0;
+
+/// What swings in the compound mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Body {
+    // a uniform rod, hung from a point along it
+    Rod,
+    // a uniform disk, or a ring with all its mass on the rim, on a light arm
+    Disk,
+    Ring,
+    // all the mass in one point, as in the single pendulum
+    Bob,
+    // a radius of gyration about the centre of mass, in cm, from --inertia
+    Custom(f64),
+}
+
+impl Body {
+    pub const PRESETS: [Body; 4] = [Body::Rod, Body::Disk, Body::Ring, Body::Bob];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Body::Rod => "rod",
+            Body::Disk => "disk",
+            Body::Ring => "ring",
+            Body::Bob => "bob",
+            Body::Custom(_) => "custom",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Body::PRESETS.into_iter().find(|it| it.name() == name)
+    }
+
+    // cm², the moment of inertia about the centre of mass over the mass
+    fn gyration(self) -> f64 {
+        match self {
+            Body::Rod => ROD * ROD / 12.0,
+            Body::Disk => RADIUS * RADIUS / 2.0,
+            Body::Ring => RADIUS * RADIUS,
+            Body::Bob => 0.0,
+            Body::Custom(radius) => radius * radius,
+        }
+    }
+
+    /// cm from the centre of mass the pivot is at by default: the rod's end,
+    /// and the others' centres as low as the single pendulum's bob.
+    pub fn pivot_distance(self) -> f64 {
+        match self {
+            Body::Rod => ROD / 2.0,
+            Body::Disk | Body::Ring => 200.0 - RADIUS,
+            Body::Bob => 200.0,
+            Body::Custom(_) => 100.0,
+        }
+    }
+
+    // cm the body reaches out from its centre of mass, along the arm
+    fn extent(self) -> f64 {
+        match self {
+            Body::Rod => ROD / 2.0,
+            Body::Disk | Body::Ring => RADIUS,
+            Body::Bob => 0.0,
+            // drawn as the disk of the same radius of gyration
+            Body::Custom(radius) => radius * 2f64.sqrt(),
+        }
+    }
+}
+
+/// The main window's compound mode: a rigid body swinging about a pivot d
+/// from its centre of mass, I·θ'' = -m·g·d·sin θ with I = m·(k² + d²) by
+/// the parallel axis theorem, k being its radius of gyration.
+///
+/// It swings like a point bob on a rod of I/(m·d), swinging fastest with
+/// the pivot at d = k, so the rod hung from its end swings like one 2/3 as
+/// long, and a pivot moved towards the centre of mass slows it down again.
+pub struct CompoundMode {
+    pub body: Body,
+    // of the bodies Home and End go through, which the custom one is on if given
+    bodies: Vec<Body>,
+    // cm from the centre of mass to the pivot
+    pub distance: f64,
+    // θ and ω
+    state: [f64; 2],
+    pub time: f64,
+    // when θ last went up through 0, and how long the swing before took
+    crossed: Option<f64>,
+    period: Option<f64>,
+}
+
+impl CompoundMode {
+    pub fn new(body: Body, distance: f64) -> Self {
+        let mut bodies = Body::PRESETS.to_vec();
+        if let Body::Custom(_) = body {
+            bodies.push(body);
+        }
+        CompoundMode {
+            body,
+            bodies,
+            distance,
+            state: [TILT, 0.0],
+            time: 0.0,
+            crossed: None,
+            period: None,
+        }
+    }
+
+    // cm, of the point bob swinging the same
+    fn equivalent_length(&self) -> f64 {
+        (self.body.gyration() + self.distance * self.distance) / self.distance
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        vec![("θ", self.state[0]), ("ω", self.state[1])]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let length = self.equivalent_length();
+        let before = self.state[0];
+        for _ in 0..SUBSTEPS {
+            self.state = physics::rk4(self.state, dt / SUBSTEPS as f64, |&[theta, w]| {
+                [w, -G / length * theta.sin()]
+            });
+        }
+        self.time += dt;
+
+        let [theta, w] = self.state;
+        if before < 0.0 && theta >= 0.0 {
+            // when between the steps it crossed, by the angular velocity
+            let crossed = self.time - theta / w.max(f64::EPSILON);
+            if let Some(last) = self.crossed {
+                self.period = Some(crossed - last);
+            }
+            self.crossed = Some(crossed);
+        }
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.state[1] += dw;
+    }
+
+    /// Goes `by` bodies on from this one, each hung where it is by
+    /// default, and moves the pivot `shift` times 5 cm further from the
+    /// centre of mass, swinging on as it was.
+    pub fn reshape(&mut self, by: i64, shift: f64) {
+        if by != 0 {
+            let i = self
+                .bodies
+                .iter()
+                .position(|it| *it == self.body)
+                .unwrap_or(0) as i64;
+            let count = self.bodies.len() as i64;
+            self.body = self.bodies[(i + by).rem_euclid(count) as usize];
+            self.distance = self.body.pivot_distance();
+        }
+        self.distance =
+            (self.distance + shift * SHIFT).clamp(*PIVOT_DISTANCES.start(), *PIVOT_DISTANCES.end());
+        self.crossed = None;
+        self.period = None;
+    }
+
+    /// Draws the body hanging from the pivot, a light arm joining them where
+    /// the pivot is off it, with its centre of mass marked.
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        let theta = self.state[0];
+        let extent = self.body.extent();
+        let scale = (REACH / (self.distance + extent)).min(1.0);
+        // px from the pivot, `along` cm down the body's axis through it
+        let point = |along: f64| {
+            (
+                CENTER.0 + (along * scale * theta.sin()).round() as i16,
+                CENTER.1 + (along * scale * theta.cos()).round() as i16,
+            )
+        };
+        let center = point(self.distance);
+        let light = Color::RGB(150, 150, 150);
+        let body = Color::RGB(200, 40, 40);
+        let radius = (extent * scale).round() as i16;
+        match self.body {
+            Body::Rod => {
+                let (top, end) = (point(self.distance - extent), point(self.distance + extent));
+                if self.distance > extent {
+                    canvas
+                        .aa_line(CENTER.0, CENTER.1, top.0, top.1, light)
+                        .expect("Unable to draw line");
+                }
+                canvas
+                    .thick_line(top.0, top.1, end.0, end.1, 8, body)
+                    .expect("Unable to draw line");
+            }
+            Body::Disk | Body::Custom(_) => {
+                canvas
+                    .aa_line(CENTER.0, CENTER.1, center.0, center.1, light)
+                    .expect("Unable to draw line");
+                canvas
+                    .filled_circle(center.0, center.1, radius, body)
+                    .expect("Unable to draw circle");
+            }
+            Body::Ring => {
+                canvas
+                    .aa_line(CENTER.0, CENTER.1, center.0, center.1, light)
+                    .expect("Unable to draw line");
+                for width in 0..4 {
+                    canvas
+                        .aa_circle(center.0, center.1, radius - width, body)
+                        .expect("Unable to draw circle");
+                }
+            }
+            Body::Bob => {
+                canvas
+                    .aa_line(CENTER.0, CENTER.1, center.0, center.1, Color::BLACK)
+                    .expect("Unable to draw line");
+                canvas
+                    .filled_circle(center.0, center.1, 6, body)
+                    .expect("Unable to draw circle");
+            }
+        }
+        canvas
+            .filled_circle(center.0, center.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 4, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [theta, w] = self.state;
+        let gyration = self.body.gyration();
+        let length = self.equivalent_length();
+        vec![
+            format!(
+                "{}: k = {:.1} cm, d = {:.1} cm, I/m = {:.0} cm²",
+                self.body.name(),
+                gyration.sqrt(),
+                self.distance,
+                gyration + self.distance * self.distance
+            ),
+            format!(
+                "θ: {:.3} rad, ω: {:.3} rad/s, swinging like a bob on {:.1} cm",
+                events::wrap(theta),
+                w,
+                length
+            ),
+            format!(
+                "period: {}, 2π√(I/(m·g·d)) = {:.3} s, fastest at d = k",
+                match self.period {
+                    Some(period) => format!("{:.3} s", period),
+                    None => "…".to_string(),
+                },
+                2.0 * PI * (length / G).sqrt()
+            ),
+        ]
+    }
+}
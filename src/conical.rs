@@ -0,0 +1,226 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::input::{self, Action};
+use crate::mode::{Trail, SUBSTEPS, TRAIL};
+use crate::physics::{Params, SphericalPendulum};
+use crate::spherical;
+use crate::{CENTER, G};
+
+const PARAMS: Params<f64> = Params {
+    length: 150.0,
+    gravity: G,
+    damping: 0.0,
+    drag: 0.0,
+    wind: 0.0,
+    drive: 0.0,
+    drive_frequency: 0.0,
+    drive2: 0.0,
+    drive_frequency2: 0.0,
+    torque: 0.0,
+};
+/// What the cone's half-angle can be set to, in degrees.
+pub const HALF_ANGLES: RangeInclusive<f64> = 1.0..=85.0;
+
+/// The main window's conical mode: the bob going steadily round a
+/// horizontal circle, its rod sweeping out a cone, seen from the side or,
+/// after V, in perspective as in the spherical mode.
+///
+/// Going round at ω, the rod's pull balances gravity along the vertical and
+/// turns the bob round the circle, so for a half-angle α the period is
+/// 2π·√(L·cos α/g) and the tension mg/cos α, both measured as it swings.
+pub struct ConicalMode {
+    pendulum: SphericalPendulum,
+    // degrees, of the cone it was started on
+    pub half_angle: f64,
+    pub time: f64,
+    // when the bob last went round past the x axis, and how long it took
+    rounded: Option<f64>,
+    period: Option<f64>,
+    trail: Trail,
+    perspective: bool,
+}
+
+impl ConicalMode {
+    pub fn new(half_angle: f64) -> Self {
+        ConicalMode {
+            pendulum: start(half_angle),
+            half_angle,
+            time: 0.0,
+            rounded: None,
+            period: None,
+            trail: Trail::new(TRAIL, Color::RED),
+            perspective: false,
+        }
+    }
+
+    /// Switches between the views from the side and in perspective on V,
+    /// returning whether `event` was that, which then does nothing else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        if input::action(event) != Some(Action::View) {
+            return false;
+        }
+        self.perspective = !self.perspective;
+        true
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        spherical::values(&self.pendulum)
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let before = self.pendulum.position[1];
+        for _ in 0..SUBSTEPS {
+            self.pendulum.step(&PARAMS, dt / SUBSTEPS as f64);
+        }
+        self.time += dt;
+
+        let [x, y, _] = self.pendulum.position;
+        if before < 0.0 && y >= 0.0 && x > 0.0 {
+            // when between the steps it crossed, by the sideways speed
+            let crossed = self.time - y / self.pendulum.velocity[1].max(f64::EPSILON);
+            if let Some(rounded) = self.rounded {
+                self.period = Some(crossed - rounded);
+            }
+            self.rounded = Some(crossed);
+        }
+    }
+
+    // rad/s added to the swing, away from the vertical, knocking it off the cone
+    pub fn kick(&mut self, dw: f64) {
+        let [x, y, z] = self.pendulum.position;
+        let theta = (z / PARAMS.length).clamp(-1.0, 1.0).acos();
+        let phi = y.atan2(x);
+        let direction = [
+            theta.cos() * phi.cos(),
+            theta.cos() * phi.sin(),
+            -theta.sin(),
+        ];
+        for (v, d) in self.pendulum.velocity.iter_mut().zip(direction) {
+            *v += dw * PARAMS.length * d;
+        }
+    }
+
+    // a cone `degrees` wider, started on afresh
+    pub fn widen(&mut self, degrees: f64) {
+        *self = ConicalMode {
+            perspective: self.perspective,
+            time: self.time,
+            ..ConicalMode::new(
+                (self.half_angle + degrees).clamp(*HALF_ANGLES.start(), *HALF_ANGLES.end()),
+            )
+        };
+    }
+
+    // measured from the vertical, in rad
+    fn alpha(&self) -> f64 {
+        (self.pendulum.position[2] / PARAMS.length)
+            .clamp(-1.0, 1.0)
+            .acos()
+    }
+
+    // over the weight, from what the rod pulls with to keep the bob on the sphere
+    fn tension(&self) -> f64 {
+        let speed = self.pendulum.speed();
+        (speed * speed + PARAMS.gravity * self.pendulum.position[2])
+            / (PARAMS.length * PARAMS.gravity)
+    }
+
+    /// Draws the circle the bob goes round and the pendulum, from the side
+    /// or in perspective.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let light = Color::RGB(200, 200, 200);
+        let alpha = self.half_angle.to_radians();
+        let (radius, depth) = (PARAMS.length * alpha.sin(), PARAMS.length * alpha.cos());
+        let [x, y, z] = self.pendulum.position;
+        let bob = if self.perspective {
+            let circle: Vec<(i16, i16)> = (0..=48)
+                .map(|i| {
+                    let angle = i as f64 / 48.0 * 2.0 * PI;
+                    spherical::project([radius * angle.cos(), radius * angle.sin(), depth])
+                })
+                .collect();
+            for pair in circle.windows(2) {
+                canvas
+                    .aa_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, light)
+                    .expect("Unable to draw line");
+            }
+            let middle = spherical::project([0.0, 0.0, depth]);
+            canvas
+                .aa_line(CENTER.0, CENTER.1, middle.0, middle.1, light)
+                .expect("Unable to draw line");
+            spherical::project([x, y, z])
+        } else {
+            // the circle edge on, between the cone's sides
+            let (left, right, level) = (
+                CENTER.0 - radius.round() as i16,
+                CENTER.0 + radius.round() as i16,
+                CENTER.1 + depth.round() as i16,
+            );
+            canvas
+                .hline(left, right, level, light)
+                .expect("Unable to draw line");
+            for side in [left, right] {
+                canvas
+                    .aa_line(CENTER.0, CENTER.1, side, level, light)
+                    .expect("Unable to draw line");
+            }
+            (CENTER.0 + x.round() as i16, CENTER.1 + z.round() as i16)
+        };
+        self.trail.push(bob);
+        self.trail.draw(canvas, detail);
+        canvas
+            .aa_line(CENTER.0, CENTER.1, bob.0, bob.1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        // nearer is bigger
+        let size = if self.perspective {
+            5.0 * spherical::scale([x, y, z])
+        } else {
+            5.0
+        };
+        canvas
+            .aa_circle(bob.0, bob.1, size.round() as i16, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let cos = self.half_angle.to_radians().cos();
+        vec![
+            format!(
+                "half-angle: {:.1}°, started at {:.1}°",
+                self.alpha().to_degrees(),
+                self.half_angle
+            ),
+            format!(
+                "period: {}, 2π√(L·cos α/g) = {:.3} s",
+                match self.period {
+                    Some(period) => format!("{:.3} s", period),
+                    None => "…".to_string(),
+                },
+                2.0 * PI * (PARAMS.length * cos / PARAMS.gravity).sqrt()
+            ),
+            format!(
+                "tension: {:.3} mg, 1/cos α = {:.3}",
+                self.tension(),
+                1.0 / cos
+            ),
+        ]
+    }
+}
+
+// on the cone of `half_angle` degrees, going round it as fast as keeps it there
+fn start(half_angle: f64) -> SphericalPendulum {
+    let alpha = half_angle.to_radians();
+    let radius = PARAMS.length * alpha.sin();
+    let speed = (PARAMS.gravity * radius * alpha.tan()).sqrt();
+    SphericalPendulum::new(&PARAMS, alpha, speed)
+}
@@ -0,0 +1,635 @@
+use std::f64::consts::TAU;
+use std::ops::RangeInclusive;
+
+use crate::bridge::Delay;
+use crate::events;
+use crate::physics;
+use crate::rng::Rng;
+use crate::G;
+
+/// cm of the cart-pole's pole, from the pivot to the bob.
+pub const POLE: f64 = 150.0;
+/// 1/s of drag slowing the cart down.
+pub const CART_DRAG: f64 = 0.5;
+/// cm the cart can go either way before it hits the end of the track.
+pub const TRACK: f64 = 250.0;
+/// The PID's gains from the start, in cm/s² per rad, per rad·s and per
+/// rad/s, which place all four roots of the balanced cart-pole around -2 /s.
+pub const GAINS: [f64; 3] = [5000.0, 500.0, 2000.0];
+// cm and cm/s of the cart per rad the PID leans the pole towards the middle
+// of the track by, without which it holds the pole but lets the cart run off
+const LEAN: (f64, f64) = (2000.0, 1000.0);
+// how far off each of x, v, θ and ω the LQR weighs as much as pushing with
+// `LQR_PUSH`, in cm, cm/s, rad and rad/s
+const LQR_SCALES: [f64; 4] = [50.0, 100.0, 0.1, 1.0];
+// cm/s²
+const LQR_PUSH: f64 = 3000.0;
+// Riccati iterations at most, and how little the gains may change by in
+// the last one
+const RICCATI_ITERATIONS: usize = 100_000;
+const RICCATI_TOLERANCE: f64 = 1e-9;
+// s between the MPC's plans, and of each of the steps of a plan
+const MPC_STEP: f64 = 0.02;
+// steps a plan looks ahead, the paths tried out around it, cm/s² of
+// randomness in their pushes, how much worse than the best a path can be
+// and still count towards the next plan, and the steps between the random
+// pushes
+const HORIZON: usize = 50;
+const PATHS: usize = 64;
+const SPREAD: f64 = 300.0;
+const TEMPERATURE: f64 = 1.0;
+const KNOT: usize = 5;
+// how much more the state at the end of a path weighs than along it, and
+// the cost of running into the end of the track
+const TERMINAL: f64 = 10.0;
+const CRASH: f64 = 1000.0;
+// the same paths every run, so comparisons repeat
+const MPC_SEED: u64 = 0x3bc;
+/// cm/s² a controller can push the cart with at most.
+pub const MAX_CONTROL: f64 = 3000.0;
+/// Hz an embedded controller can push at, and cm a count of the cart's
+/// encoder can be.
+pub const CONTROL_RATES: RangeInclusive<f64> = 1.0..=1000.0;
+pub const TRACK_RESOLUTIONS: RangeInclusive<f64> = 0.001..=10.0;
+
+/// Something working out each step what to push a plant with from the `N`
+/// values it observes of it: the cart-pole's thrust from its state, or the
+/// single pendulum's torque from its θ and ω.
+pub trait Controller<const N: usize = 4> {
+    /// The push to hold over the next `dt` s, from `observed`: for the
+    /// cart-pole cm and cm/s of the cart along the track, θ of the pole from
+    /// upright and ω.
+    fn control(&mut self, observed: &[f64; N], dt: f64) -> f64;
+}
+
+/// The control laws the cart-pole can be balanced with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Law {
+    Pid,
+    // linear-quadratic regulator, full state feedback
+    Lqr,
+    // model-predictive control, by path integrals
+    Mpc,
+}
+
+impl Law {
+    pub const ALL: [Law; 3] = [Law::Pid, Law::Lqr, Law::Mpc];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Law::Pid => "pid",
+            Law::Lqr => "lqr",
+            Law::Mpc => "mpc",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Law::ALL.into_iter().find(|it| it.name() == name)
+    }
+
+    /// A fresh controller of this law, the PID with `gains`, stepping every
+    /// `dt` s.
+    pub fn build(self, gains: [f64; 3], dt: f64) -> Box<dyn Controller> {
+        match self {
+            Law::Pid => Box::new(Pid::new(gains)),
+            Law::Lqr => Box::new(Lqr::new(dt)),
+            Law::Mpc => Box::new(Mpc::new()),
+        }
+    }
+}
+
+/// A PID controller on the error θ + x/X + v/V: balancing that leans the
+/// pole, and so the cart, back to the middle of the track.
+pub struct Pid {
+    // Kp, Ki and Kd
+    pub gains: [f64; 3],
+    // rad·s of error summed since it started
+    integral: f64,
+}
+
+impl Pid {
+    pub fn new(gains: [f64; 3]) -> Self {
+        Pid {
+            gains,
+            integral: 0.0,
+        }
+    }
+}
+
+impl Controller for Pid {
+    fn control(&mut self, observed: &[f64; 4], dt: f64) -> f64 {
+        let [x, v, theta, w] = *observed;
+        let error = events::wrap(theta) + x / LEAN.0 + v / LEAN.1;
+        self.integral += error * dt;
+        let [kp, ki, kd] = self.gains;
+        kp * error + ki * self.integral + kd * w
+    }
+}
+
+/// A linear-quadratic regulator: the push -K·state that keeps the sum of
+/// the squared state and push, each over the scale it matters at, least
+/// for the cart-pole linearized about upright.
+pub struct Lqr {
+    // cm/s² per cm, per cm/s, per rad and per rad/s
+    gains: [f64; 4],
+}
+
+impl Lqr {
+    pub fn new(dt: f64) -> Self {
+        Lqr {
+            gains: lqr_gains(dt),
+        }
+    }
+}
+
+impl Controller for Lqr {
+    fn control(&mut self, observed: &[f64; 4], _dt: f64) -> f64 {
+        feedback(&self.gains, observed)
+    }
+}
+
+/// Model-predictive control by path integrals: every `MPC_STEP` it tries
+/// out `PATHS` randomly varied plans for the next second on the cart-pole's
+/// full, nonlinear equations of motion, and makes its new plan the average
+/// of them weighted by how well each went, by the cost the LQR keeps least
+/// plus running into the end of the track. A plan is what to push with on
+/// top of the LQR's own push, so near upright, where the linearized
+/// cart-pole is right, it adds little, and further off it makes up for
+/// where the LQR is wrong.
+pub struct Mpc {
+    // cm/s² per cm, per cm/s, per rad and per rad/s, of the LQR under it
+    gains: [f64; 4],
+    // cm/s² on top of the LQR's push, of each step ahead
+    plan: Vec<f64>,
+    // s until the next plan is due
+    due: f64,
+    rng: Rng,
+    // cm of the cart and θ of the pole along each path tried, and along the
+    // plan made from them
+    pub fan: Vec<Vec<(f64, f64)>>,
+    pub predicted: Vec<(f64, f64)>,
+}
+
+impl Mpc {
+    pub fn new() -> Self {
+        Mpc {
+            gains: lqr_gains(MPC_STEP),
+            plan: vec![0.0; HORIZON],
+            due: 0.0,
+            rng: Rng::new(MPC_SEED),
+            fan: Vec::new(),
+            predicted: Vec::new(),
+        }
+    }
+
+    fn replan(&mut self, observed: &[f64; 4]) {
+        let mut plans = Vec::with_capacity(PATHS);
+        let mut costs = Vec::with_capacity(PATHS);
+        self.fan.clear();
+        for i in 0..PATHS {
+            // the plan itself among them, so a good one is kept, and the
+            // others varied smoothly, in straight lines between random
+            // pushes every `KNOT` steps, so their average doesn't chatter
+            let knots: Vec<f64> = (0..=HORIZON / KNOT + 1)
+                .map(|_| {
+                    if i == 0 {
+                        0.0
+                    } else {
+                        SPREAD * self.rng.gaussian()
+                    }
+                })
+                .collect();
+            let plan: Vec<f64> = (0..HORIZON)
+                .map(|h| {
+                    let along = (h % KNOT) as f64 / KNOT as f64;
+                    let noise = knots[h / KNOT] * (1.0 - along) + knots[h / KNOT + 1] * along;
+                    self.plan[h] + noise
+                })
+                .collect();
+            let (cost, path) = rollout(observed, &self.gains, &plan);
+            plans.push(plan);
+            costs.push(cost);
+            self.fan.push(path);
+        }
+        let best = costs.iter().copied().fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = costs
+            .iter()
+            .map(|it| (-(it - best) / TEMPERATURE).exp())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let average: Vec<f64> = (0..HORIZON)
+            .map(|h| (0..PATHS).map(|i| weights[i] * plans[i][h]).sum::<f64>() / total)
+            .collect();
+        // near upright the randomness averages out to a little noise that
+        // does worse than the plan it came from, and is left out
+        let (cost, path) = rollout(observed, &self.gains, &average);
+        if cost < costs[0] {
+            self.plan = average;
+            self.predicted = path;
+        } else {
+            self.plan = plans.swap_remove(0);
+            self.predicted = self.fan[0].clone();
+        }
+    }
+}
+
+impl Controller for Mpc {
+    fn control(&mut self, observed: &[f64; 4], dt: f64) -> f64 {
+        if self.due <= 0.0 {
+            self.replan(observed);
+            self.due += MPC_STEP;
+        }
+        self.due -= dt;
+        // the first step once it is over, the rest shifted along
+        let push = feedback(&self.gains, observed) + self.plan[0];
+        if self.due <= 0.0 {
+            self.plan.rotate_left(1);
+            self.plan[HORIZON - 1] = 0.0;
+        }
+        push
+    }
+}
+
+// cm/s², the LQR's push -K·state
+fn feedback(gains: &[f64; 4], state: &[f64; 4]) -> f64 {
+    let [x, v, theta, w] = *state;
+    let off = [x, v, events::wrap(theta), w];
+    -(0..4).map(|i| gains[i] * off[i]).sum::<f64>()
+}
+
+// what following `plan` on top of the LQR with `gains` from `state` costs,
+// and the cart's position and the pole's angle along the way
+fn rollout(state: &[f64; 4], gains: &[f64; 4], plan: &[f64]) -> (f64, Vec<(f64, f64)>) {
+    let mut state = *state;
+    let mut cost = 0.0;
+    let mut path = Vec::with_capacity(HORIZON + 1);
+    path.push((state[0], state[2]));
+    for (h, &extra) in plan.iter().enumerate() {
+        let push = (feedback(gains, &state) + extra).clamp(-MAX_CONTROL, MAX_CONTROL);
+        state = physics::rk4(state, MPC_STEP, |it| derivatives(it, push));
+        let [x, v, theta, w] = state;
+        let off = [x, v, events::wrap(theta), w];
+        let weight = if h == HORIZON - 1 { TERMINAL } else { 1.0 };
+        cost += weight
+            * (0..4)
+                .map(|i| (off[i] / LQR_SCALES[i]).powi(2))
+                .sum::<f64>()
+            + (push / LQR_PUSH).powi(2);
+        if x.abs() > TRACK {
+            cost += CRASH;
+        }
+        path.push((x, theta));
+    }
+    (cost, path)
+}
+
+/// What a controller on embedded hardware sees the cart-pole through and
+/// pushes it with, none of it by default: sensors reading `latency` s late,
+/// the pole's angle and the cart's position to the nearest count of their
+/// encoders, and a push worked out `rate` times a second and held between.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Conditions {
+    pub latency: f64,
+    // Hz, or every step
+    pub rate: Option<f64>,
+    // counts per revolution of the pole's encoder, and cm per count of the
+    // cart's
+    pub counts: Option<u32>,
+    pub track: Option<f64>,
+}
+
+impl Conditions {
+    /// s between the controller's pushes, at most one a step of `dt`.
+    pub fn period(&self, dt: f64) -> f64 {
+        self.rate.map_or(dt, |it| (1.0 / it).max(dt))
+    }
+}
+
+/// A controller under `Conditions`. Where a position is read by an encoder,
+/// its speed is worked out as embedded code would, from how far the reading
+/// moved since the push before, instead of being measured.
+pub struct Embedded {
+    controller: Box<dyn Controller>,
+    conditions: Conditions,
+    sensors: Delay<[f64; 4]>,
+    // s until the next push is worked out, and since the last was
+    due: f64,
+    since: f64,
+    // cm/s², held until then
+    push: f64,
+    // cm of the cart and θ of the pole as read for the last push
+    read: Option<(f64, f64)>,
+}
+
+impl Embedded {
+    pub fn new(controller: Box<dyn Controller>, conditions: Conditions) -> Self {
+        Embedded {
+            controller,
+            conditions,
+            sensors: Delay::new(conditions.latency, 0.0, 0),
+            due: 0.0,
+            since: 0.0,
+            push: 0.0,
+            read: None,
+        }
+    }
+}
+
+impl Controller for Embedded {
+    fn control(&mut self, observed: &[f64; 4], dt: f64) -> f64 {
+        self.sensors.send(*observed);
+        let [x, v, theta, w] = self.sensors.step(dt);
+        self.since += dt;
+        if self.due <= 0.0 {
+            let Conditions { counts, track, .. } = self.conditions;
+            let x_read = track.map_or(x, |it| (x / it).round() * it);
+            let theta_read = counts.map_or(theta, |it| {
+                let count = TAU / it as f64;
+                (events::wrap(theta) / count).round() * count
+            });
+            // 0 until there is a reading before to tell the speed from
+            let speed =
+                |now: f64, before: Option<f64>| before.map_or(0.0, |it| (now - it) / self.since);
+            let reading = [
+                if track.is_some() {
+                    speed(x_read, self.read.map(|it| it.0))
+                } else {
+                    v
+                },
+                if counts.is_some() {
+                    speed(theta_read, self.read.map(|it| it.1))
+                } else {
+                    w
+                },
+            ];
+            let period = self.conditions.period(dt);
+            self.push = self
+                .controller
+                .control(&[x_read, reading[0], theta_read, reading[1]], period);
+            self.read = Some((x_read, theta_read));
+            self.due += period;
+            self.since = 0.0;
+        }
+        self.due -= dt;
+        self.push
+    }
+}
+
+/// d/dt of the cart-pole's `state`, cm and cm/s of the cart, θ of the pole
+/// from upright and ω, with the cart pushed by `thrust` in cm/s². The cart
+/// follows the push whatever the pole does, so the pole only feels its
+/// acceleration a: θ'' = (g·sin θ - a·cos θ)/L.
+pub fn derivatives(state: &[f64; 4], thrust: f64) -> [f64; 4] {
+    derivatives_of(state, thrust, POLE, CART_DRAG)
+}
+
+/// `derivatives` of a cart-pole with a pole of `pole` cm and `drag` 1/s of
+/// drag on its cart.
+pub fn derivatives_of(state: &[f64; 4], thrust: f64, pole: f64, drag: f64) -> [f64; 4] {
+    let [_, v, theta, w] = *state;
+    let a = thrust - drag * v;
+    [v, a, w, (G * theta.sin() - a * theta.cos()) / pole]
+}
+
+/// The cart-pole linearized about upright, d/dt [x, v, θ, ω] = A·state +
+/// B·a for a push a: the cart's drag slows it, and the pole falls with
+/// g·θ/L less what the cart's acceleration pulls out from under it.
+pub fn linearized() -> ([[f64; 4]; 4], [f64; 4]) {
+    let a = [
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, -CART_DRAG, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+        [0.0, CART_DRAG / POLE, G / POLE, 0.0],
+    ];
+    (a, [0.0, 1.0, 0.0, -1.0 / POLE])
+}
+
+/// The poles of the cart-pole balanced by a PID with `gains`, linearized
+/// about upright, in 1/s as real and imaginary parts: the roots of its
+/// characteristic polynomial in x, v, θ, ω and the error's integral. They
+/// are all in the left half plane when it balances, the further left the
+/// quicker, and further off the real axis the more it rings.
+pub fn pid_poles(gains: [f64; 3]) -> [(f64, f64); 5] {
+    let (a, b) = linearized();
+    let [kp, ki, kd] = gains;
+    // the push, on [x, v, θ, ω, ∫e], and the error it integrates
+    let push = [kp / LEAN.0, kp / LEAN.1, kp, kd, ki];
+    let error = [1.0 / LEAN.0, 1.0 / LEAN.1, 1.0, 0.0, 0.0];
+    let closed: [[f64; 5]; 5] = std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            if i == 4 {
+                error[j]
+            } else {
+                let open = if j < 4 { a[i][j] } else { 0.0 };
+                open + b[i] * push[j]
+            }
+        })
+    });
+    eigenvalues(closed)
+}
+
+// the eigenvalues of `m`, the roots of its characteristic polynomial from
+// Faddeev-LeVerrier found all at once by Durand-Kerner
+fn eigenvalues<const N: usize>(m: [[f64; N]; N]) -> [(f64, f64); N] {
+    // c[k] of λ^k, monic
+    let mut c = [0.0; N];
+    let mut last = [[0.0; N]; N];
+    let mut coefficient = 1.0;
+    for k in 1..=N {
+        // M_k = A·M_(k-1) + c_(n-k+1)·I, c_(n-k) = -tr(A·M_k)/k
+        let mut next = [[0.0; N]; N];
+        for i in 0..N {
+            for j in 0..N {
+                next[i][j] = (0..N).map(|l| m[i][l] * last[l][j]).sum::<f64>()
+                    + if i == j { coefficient } else { 0.0 };
+            }
+        }
+        let trace: f64 = (0..N)
+            .map(|i| (0..N).map(|l| m[i][l] * next[l][i]).sum::<f64>())
+            .sum();
+        coefficient = -trace / k as f64;
+        c[N - k] = coefficient;
+        last = next;
+    }
+    let value = |z: (f64, f64)| {
+        let mut out = (1.0, 0.0);
+        for k in (0..N).rev() {
+            out = multiply(out, z);
+            out.0 += c[k];
+        }
+        out
+    };
+    let radius = 1.0 + c.iter().map(|it| it.abs()).fold(0.0, f64::max);
+    let mut roots: [(f64, f64); N] = std::array::from_fn(|k| {
+        let angle = 0.4 + std::f64::consts::TAU * k as f64 / N as f64;
+        (radius * angle.cos(), radius * angle.sin())
+    });
+    for _ in 0..1000 {
+        for i in 0..N {
+            let mut below = (1.0, 0.0);
+            for j in (0..N).filter(|j| *j != i) {
+                below = multiply(below, (roots[i].0 - roots[j].0, roots[i].1 - roots[j].1));
+            }
+            let step = divide(value(roots[i]), below);
+            roots[i] = (roots[i].0 - step.0, roots[i].1 - step.1);
+        }
+    }
+    roots
+}
+
+fn multiply(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn divide(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let norm = b.0 * b.0 + b.1 * b.1;
+    (
+        (a.0 * b.0 + a.1 * b.1) / norm,
+        (a.1 * b.0 - a.0 * b.1) / norm,
+    )
+}
+
+// the LQR's gains for steps of `dt`, from the discrete Riccati equation
+// iterated until it settles
+fn lqr_gains(dt: f64) -> [f64; 4] {
+    let (a, b) = linearized();
+    // the step, state' = F·state + H·push
+    let mut f = [[0.0; 4]; 4];
+    for i in 0..4 {
+        for j in 0..4 {
+            f[i][j] = if i == j { 1.0 } else { 0.0 } + a[i][j] * dt;
+        }
+    }
+    let h = b.map(|it| it * dt);
+    let q = LQR_SCALES.map(|it| 1.0 / (it * it));
+    let r = 1.0 / (LQR_PUSH * LQR_PUSH);
+    let mut p = [[0.0; 4]; 4];
+    for i in 0..4 {
+        p[i][i] = q[i];
+    }
+    let mut gains = [0.0; 4];
+    for _ in 0..RICCATI_ITERATIONS {
+        // P·H, and from it K = (R + Hᵀ·P·H)⁻¹·Hᵀ·P·F
+        let ph: [f64; 4] = std::array::from_fn(|i| (0..4).map(|k| p[i][k] * h[k]).sum());
+        let hph: f64 = (0..4).map(|k| h[k] * ph[k]).sum();
+        let next: [f64; 4] =
+            std::array::from_fn(|j| (0..4).map(|k| ph[k] * f[k][j]).sum::<f64>() / (r + hph));
+        // P = Q + Fᵀ·P·(F - H·K)
+        let closed: [[f64; 4]; 4] =
+            std::array::from_fn(|i| std::array::from_fn(|j| f[i][j] - h[i] * next[j]));
+        let mut updated = [[0.0; 4]; 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                let pc: f64 = (0..4)
+                    .map(|k| f[k][i] * (0..4).map(|l| p[k][l] * closed[l][j]).sum::<f64>())
+                    .sum();
+                updated[i][j] = pc + if i == j { q[i] } else { 0.0 };
+            }
+        }
+        p = updated;
+        let change = (0..4)
+            .map(|i| (next[i] - gains[i]).abs())
+            .fold(0.0, f64::max);
+        gains = next;
+        if change < RICCATI_TOLERANCE * gains.iter().map(|it| it.abs()).fold(1.0, f64::max) {
+            break;
+        }
+    }
+    gains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cart::{self, TILT};
+
+    #[test]
+    fn each_law_balances_a_tilted_pole() {
+        let dt = 1.0 / 120.0;
+        for law in Law::ALL {
+            let mut controller = law.build(GAINS, dt);
+            let mut state = [0.0, 0.0, TILT, 0.0];
+            for _ in 0..(10.0 / dt) as usize {
+                let push = controller
+                    .control(&state, dt)
+                    .clamp(-MAX_CONTROL, MAX_CONTROL);
+                cart::advance(&mut state, push, dt);
+            }
+            let [x, _, theta, w] = state;
+            assert!(
+                theta.abs() < 1e-3 && w.abs() < 1e-2,
+                "{:?} left {:?}",
+                law,
+                state
+            );
+            assert!(x.abs() < 100.0, "{:?} left {:?}", law, state);
+        }
+    }
+
+    #[test]
+    fn finds_the_poles_of_the_balanced_pole() {
+        // the default gains balance it, so every pole is on the left
+        let poles = pid_poles(GAINS);
+        assert!(poles.iter().all(|it| it.0 < 0.0), "{:?}", poles);
+        // without the derivative it can't damp the swing any more
+        let poles = pid_poles([GAINS[0], GAINS[1], 0.0]);
+        assert!(poles.iter().any(|it| it.0 >= -1e-9), "{:?}", poles);
+        // and without any gain at all, it falls at √(g/L)
+        let poles = pid_poles([0.0; 3]);
+        let falling = (G / POLE).sqrt();
+        assert!(
+            poles.iter().any(|it| (it.0 - falling).abs() < 1e-6),
+            "{:?}",
+            poles
+        );
+    }
+
+    #[test]
+    fn parses_the_names_of_the_laws() {
+        for law in Law::ALL {
+            assert_eq!(Law::parse(law.name()), Some(law));
+        }
+        assert_eq!(Law::parse("bang-bang"), None);
+    }
+
+    // pushes with one of the values it observes
+    struct Echo(usize);
+
+    impl Controller for Echo {
+        fn control(&mut self, observed: &[f64; 4], _dt: f64) -> f64 {
+            observed[self.0]
+        }
+    }
+
+    #[test]
+    fn holds_each_push_and_reads_late() {
+        let conditions = Conditions {
+            latency: 0.5,
+            rate: Some(2.0),
+            ..Conditions::default()
+        };
+        let mut embedded = Embedded::new(Box::new(Echo(2)), conditions);
+        let pushes: Vec<f64> = (1..=6)
+            .map(|theta| embedded.control(&[0.0, 0.0, theta as f64 / 10.0, 0.0], 0.25))
+            .collect();
+        // nothing read for the first half second, then what was half a
+        // second before, pushed with for every other step
+        assert_eq!(pushes, [0.0, 0.0, 0.1, 0.1, 0.3, 0.3]);
+    }
+
+    #[test]
+    fn reads_to_the_count_and_tells_the_speed_from_it() {
+        let conditions = Conditions {
+            counts: Some(360),
+            ..Conditions::default()
+        };
+        let mut theta = Embedded::new(Box::new(Echo(2)), conditions);
+        let degree = 1.0_f64.to_radians();
+        assert!((theta.control(&[0.0, 0.0, 0.1, 0.0], 0.25) - 6.0 * degree).abs() < 1e-12);
+        let mut w = Embedded::new(Box::new(Echo(3)), conditions);
+        // the ω observed is ignored for the counts' 6° and then 11°
+        assert_eq!(w.control(&[0.0, 0.0, 0.1, 5.0], 0.25), 0.0);
+        let speed = w.control(&[0.0, 0.0, 0.2, 5.0], 0.25);
+        assert!((speed - 5.0 * degree / 0.25).abs() < 1e-9, "{}", speed);
+    }
+}
@@ -0,0 +1,182 @@
+use std::f64::consts::PI;
+
+use crate::events;
+
+/// How angles are shown: where θ is measured from, which way it grows as
+/// seen on screen, and in what unit. The simulation itself always measures
+/// θ from the bottom, counterclockwise and in radians, as do snapshots,
+/// links and the files read at the start, so they work whatever a user's
+/// textbook has; everything shown or exported of the single pendulum goes
+/// through here instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Convention {
+    // zero straight up instead of straight down
+    pub from_top: bool,
+    pub clockwise: bool,
+    pub degrees: bool,
+}
+
+impl Convention {
+    pub const DEFAULT: Convention = Convention {
+        from_top: false,
+        clockwise: false,
+        degrees: false,
+    };
+
+    // a comma separated list of `bottom` or `top`, `counterclockwise` or
+    // `clockwise` and `radians` or `degrees`, the default for each left out
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut convention = Convention::DEFAULT;
+        for part in list.split(',') {
+            match part {
+                "bottom" => convention.from_top = false,
+                "top" => convention.from_top = true,
+                "counterclockwise" | "ccw" => convention.clockwise = false,
+                "clockwise" | "cw" => convention.clockwise = true,
+                "radians" | "rad" => convention.degrees = false,
+                "degrees" | "deg" => convention.degrees = true,
+                _ => return Err(format!("unknown angle convention `{}`", part)),
+            }
+        }
+        Ok(convention)
+    }
+
+    pub fn name(self) -> String {
+        format!(
+            "from the {}, {} positive, in {}",
+            if self.from_top { "top" } else { "bottom" },
+            if self.clockwise {
+                "clockwise"
+            } else {
+                "counterclockwise"
+            },
+            if self.degrees { "degrees" } else { "radians" }
+        )
+    }
+
+    /// +1 or -1, what a rate, a torque or anything else signed along θ is
+    /// multiplied by.
+    pub fn sign(self) -> f64 {
+        if self.clockwise {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// What an angle in rad is multiplied by to be in the unit, and the unit.
+    pub fn scale(self) -> f64 {
+        if self.degrees {
+            180.0 / PI
+        } else {
+            1.0
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        if self.degrees {
+            "°"
+        } else {
+            " rad"
+        }
+    }
+
+    /// The simulation's θ as the convention measures it, in rad, wrapped
+    /// to within half a turn of its zero the way the angle at the pivot is
+    /// drawn.
+    pub fn angle(self, theta: f64) -> f64 {
+        events::wrap(self.unwrapped(theta))
+    }
+
+    /// The same, not wrapped, so it counts the turns the way θ does.
+    pub fn unwrapped(self, theta: f64) -> f64 {
+        self.sign() * (theta - if self.from_top { PI } else { 0.0 })
+    }
+
+    /// The simulation's ω, or anything else in rad/s, as the convention's.
+    pub fn rate(self, w: f64) -> f64 {
+        self.sign() * w
+    }
+
+    /// The simulation's θ written out in the convention.
+    pub fn show_angle(self, theta: f64) -> String {
+        self.show_span(self.angle(theta))
+    }
+
+    /// A rate in rad/s written out in the convention, per second.
+    pub fn show_rate(self, w: f64) -> String {
+        format!("{}/s", self.show_span(self.rate(w)))
+    }
+
+    /// A size of an angle in rad, an amplitude or a difference with no
+    /// direction, written out in the unit, to the precision of the HUD.
+    pub fn show_span(self, span: f64) -> String {
+        if self.degrees {
+            format!("{:.1}°", span * self.scale())
+        } else {
+            format!("{:.3} rad", span)
+        }
+    }
+
+    /// The direction straight down or up the zero is in, in the degrees
+    /// of SDL_gfx's arcs, clockwise from the right.
+    pub fn zero_degrees(self) -> i16 {
+        if self.from_top {
+            270
+        } else {
+            90
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::THETA_0;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn reads_a_list_of_choices() {
+        assert_eq!(Convention::parse("bottom"), Ok(Convention::DEFAULT));
+        assert_eq!(
+            Convention::parse("top,cw,deg"),
+            Ok(Convention {
+                from_top: true,
+                clockwise: true,
+                degrees: true,
+            })
+        );
+        // a later choice of the same overrides an earlier
+        assert_eq!(
+            Convention::parse("clockwise,radians,counterclockwise"),
+            Ok(Convention::DEFAULT)
+        );
+        for list in ["sideways", "top,", "top, cw", ""] {
+            assert!(Convention::parse(list).is_err(), "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn measures_from_its_zero_and_way() {
+        let angle = |list: &str, theta: f64| Convention::parse(list).unwrap().angle(theta);
+        let close = |a: f64, b: f64| assert!((a - b).abs() < 1e-12, "{} against {}", a, b);
+        let theta = 0.25;
+        close(angle("bottom", theta), theta);
+        close(angle("cw", theta), -theta);
+        close(angle("top", theta), theta - PI);
+        close(angle("top,cw", theta), PI - theta);
+        // hanging straight down is half a turn from the top, either way
+        close(angle("top", 0.0).abs(), PI);
+        close(angle("top,cw", 0.0).abs(), PI);
+        // standing straight up is the top's zero
+        close(angle("top", PI), 0.0);
+        close(angle("top,cw", PI), 0.0);
+        // the default start, past a quarter turn from the bottom, is shown
+        // the short way round from the top
+        close(angle("top,deg", THETA_0).to_degrees(), 63.0);
+        close(angle("top", THETA_0 + TAU), angle("top", THETA_0));
+        // while the turns are still counted, alike
+        let turn = Convention::parse("top,cw").unwrap();
+        close(turn.unwrapped(theta + TAU) - turn.unwrapped(theta), -TAU);
+    }
+}
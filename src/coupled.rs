@@ -0,0 +1,216 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::mode::SUBSTEPS;
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm of each rod, and between the pivots, which the spring is as long as
+const LENGTH: f64 = 150.0;
+const GAP: f64 = 120.0;
+// rad the first is pulled aside and let go from, the second hanging still
+const TILT: f64 = 0.3;
+// zigzags of the drawn spring, and how far they stick out, in px
+const COILS: usize = 8;
+const COIL_WIDTH: f64 = 5.0;
+// px, of an energy bar holding all of it
+const BAR: f64 = 100.0;
+/// What the spring's stiffness over a bob's mass can be set to, in 1/s².
+pub const COUPLINGS: RangeInclusive<f64> = 0.0..=20.0;
+
+/// The main window's coupled mode: two pendulums side by side, their bobs
+/// joined by a spring as long as the gap between the pivots. Let go with
+/// the second still, the first slowly hands its swing over to it and
+/// takes it back, the beats the HUD and the bars under them show.
+///
+/// For small swings the spring makes the two normal modes, swinging
+/// together at ω0 = √(g/L) and against each other at √(ω0² + 2·k/m), and
+/// the swing goes from one to the other and back once a beat, 2π over
+/// their difference.
+pub struct CoupledMode {
+    // k/m, in 1/s²
+    pub coupling: f64,
+    // θ and ω of each, from the left
+    state: [f64; 4],
+    pub time: f64,
+}
+
+impl CoupledMode {
+    pub fn new(coupling: f64) -> Self {
+        CoupledMode {
+            coupling,
+            state: [TILT, 0.0, 0.0, 0.0],
+            time: 0.0,
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let [theta1, w1, theta2, w2] = self.state;
+        vec![("θ1", theta1), ("ω1", w1), ("θ2", theta2), ("ω2", w2)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let coupling = self.coupling;
+        for _ in 0..SUBSTEPS {
+            self.state = physics::rk4(self.state, dt / SUBSTEPS as f64, |&[a, wa, b, wb]| {
+                let (stretch, (ux, uy)) = spring(a, b);
+                // the spring's pull along each bob's path, over its length
+                let pull = coupling * stretch / LENGTH;
+                [
+                    wa,
+                    -G / LENGTH * a.sin() + pull * (ux * a.cos() - uy * a.sin()),
+                    wb,
+                    -G / LENGTH * b.sin() - pull * (ux * b.cos() - uy * b.sin()),
+                ]
+            });
+        }
+        self.time += dt;
+    }
+
+    // rad/s added to the left one's swing
+    pub fn kick(&mut self, dw: f64) {
+        self.state[1] += dw;
+    }
+
+    // a spring `by` 1/s² stiffer, the swings carrying on
+    pub fn stiffen(&mut self, by: f64) {
+        self.coupling = (self.coupling + by).clamp(*COUPLINGS.start(), *COUPLINGS.end());
+    }
+
+    // per unit mass, in cm²/s², of each pendulum and of the spring
+    fn energies(&self) -> [f64; 3] {
+        let [a, wa, b, wb] = self.state;
+        let swing =
+            |theta: f64, w: f64| 0.5 * (LENGTH * w).powi(2) + G * LENGTH * (1.0 - theta.cos());
+        let stretch = spring(a, b).0;
+        [
+            swing(a, wa),
+            swing(b, wb),
+            0.5 * self.coupling * stretch * stretch,
+        ]
+    }
+
+    // s, of one beat, for small swings
+    fn beat(&self) -> f64 {
+        let slow = (G / LENGTH).sqrt();
+        let fast = (G / LENGTH + 2.0 * self.coupling).sqrt();
+        2.0 * PI / (fast - slow)
+    }
+
+    /// Draws the pendulums, the spring between their bobs and under each a
+    /// bar of its share of the energy.
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        let [a, _, b, _] = self.state;
+        let pivots = [-GAP / 2.0, GAP / 2.0];
+        let bobs = [bob(a, pivots[0]), bob(b, pivots[1])];
+        canvas
+            .thick_line(
+                CENTER.0 - GAP as i16,
+                CENTER.1,
+                CENTER.0 + GAP as i16,
+                CENTER.1,
+                3,
+                Color::BLACK,
+            )
+            .expect("Unable to draw line");
+
+        // a zigzag from one bob to the other
+        let (dx, dy) = (bobs[1].0 - bobs[0].0, bobs[1].1 - bobs[0].1);
+        let distance = dx.hypot(dy).max(f64::EPSILON);
+        let across = (-dy / distance, dx / distance);
+        let mut points = vec![bobs[0]];
+        for i in 0..2 * COILS {
+            let side = if i % 2 == 0 { COIL_WIDTH } else { -COIL_WIDTH };
+            let along = (i as f64 + 0.5) / (2 * COILS) as f64;
+            points.push((
+                bobs[0].0 + dx * along + across.0 * side,
+                bobs[0].1 + dy * along + across.1 * side,
+            ));
+        }
+        points.push(bobs[1]);
+        for pair in points.windows(2) {
+            let (from, to) = (screen(pair[0]), screen(pair[1]));
+            canvas
+                .aa_line(from.0, from.1, to.0, to.1, Color::RGB(150, 150, 150))
+                .expect("Unable to draw line");
+        }
+
+        let energies = self.energies();
+        let total = energies.iter().sum::<f64>().max(f64::EPSILON);
+        let colors = [Color::RGB(200, 40, 40), Color::RGB(40, 40, 200)];
+        for ((&pivot, &point), (&energy, &color)) in
+            pivots.iter().zip(&bobs).zip(energies.iter().zip(&colors))
+        {
+            let (top, end) = (screen((pivot, 0.0)), screen(point));
+            canvas
+                .aa_line(top.0, top.1, end.0, end.1, Color::BLACK)
+                .expect("Unable to draw line");
+            canvas
+                .filled_circle(end.0, end.1, 6, color)
+                .expect("Unable to draw circle");
+            let level = CENTER.1 + LENGTH as i16 + 30;
+            let left = top.0 - (BAR / 2.0) as i16;
+            let width = (BAR * energy / total).round() as i16;
+            canvas
+                .box_(left, level, left + width, level + 6, color)
+                .expect("Unable to draw box");
+            canvas
+                .rectangle(left, level, left + BAR as i16, level + 6, Color::BLACK)
+                .expect("Unable to draw rectangle");
+        }
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [a, wa, b, wb] = self.state;
+        let energies = self.energies();
+        let total = energies.iter().sum::<f64>().max(f64::EPSILON);
+        let share = |energy: f64| 100.0 * energy / total;
+        vec![
+            format!(
+                "left θ: {:.3} rad, ω: {:.3} rad/s, right θ: {:.3} rad, ω: {:.3} rad/s",
+                events::wrap(a),
+                wa,
+                events::wrap(b),
+                wb
+            ),
+            format!(
+                "energy: left {:.0}%, right {:.0}%, spring {:.0}%",
+                share(energies[0]),
+                share(energies[1]),
+                share(energies[2])
+            ),
+            if self.coupling > 0.0 {
+                format!(
+                    "k/m: {:.2} /s², a beat every {:.1} s",
+                    self.coupling,
+                    self.beat()
+                )
+            } else {
+                "k/m: 0 /s², no spring to pass the swing on".to_string()
+            },
+        ]
+    }
+}
+
+// cm from the left pivot's bob to the right's, less the gap, and which way
+fn spring(a: f64, b: f64) -> (f64, (f64, f64)) {
+    let (left, right) = (bob(a, -GAP / 2.0), bob(b, GAP / 2.0));
+    let (dx, dy) = (right.0 - left.0, right.1 - left.1);
+    let distance = dx.hypot(dy).max(f64::EPSILON);
+    (distance - GAP, (dx / distance, dy / distance))
+}
+
+// cm right of and below the middle of the bar, of the bob swung `theta` from `pivot`
+fn bob(theta: f64, pivot: f64) -> (f64, f64) {
+    (pivot + LENGTH * theta.sin(), LENGTH * theta.cos())
+}
+
+fn screen((x, y): (f64, f64)) -> (i16, i16) {
+    (CENTER.0 + x.round() as i16, CENTER.1 + y.round() as i16)
+}
@@ -0,0 +1,134 @@
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+use crate::{CENTER, HEIGHT, L, WIDTH};
+
+// top of the floor, just below the lowest point of the swing
+const FLOOR: i16 = CENTER.1 + L as i16 + 8;
+// direction the light travels in, right and down
+const LIGHT: (f64, f64) = (0.4, 1.0);
+// where the wall shadow falls from what casts it, in px
+const WALL_OFFSET: (i16, i16) = (10, 14);
+// x of the upright of the stand, clear of the swing
+const POST: i16 = CENTER.0 + L as i16 + 40;
+
+const WALL: Color = Color::RGB(236, 232, 224);
+const FLOOR_COLOR: Color = Color::RGB(196, 178, 150);
+const WOOD: Color = Color::RGB(130, 92, 56);
+const SHADOW: Color = Color::RGBA(0, 0, 0, 40);
+
+/// Optional scenery drawn behind the pendulum to make recordings look less
+/// like a debug view, each part toggled separately.
+#[derive(Clone, Copy, Debug)]
+pub struct Decor {
+    pub wall: bool,
+    pub stand: bool,
+    pub shadows: bool,
+}
+
+impl Decor {
+    pub const ALL: Decor = Decor {
+        wall: true,
+        stand: true,
+        shadows: true,
+    };
+
+    // a comma separated list of parts, or `all`
+    pub fn parse(list: &str) -> Result<Self, String> {
+        if list == "all" {
+            return Ok(Decor::ALL);
+        }
+        let mut decor = Decor {
+            wall: false,
+            stand: false,
+            shadows: false,
+        };
+        for part in list.split(',') {
+            match part {
+                "wall" => decor.wall = true,
+                "stand" => decor.stand = true,
+                "shadows" => decor.shadows = true,
+                _ => return Err(format!("unknown decor part `{}`", part)),
+            }
+        }
+        Ok(decor)
+    }
+
+    /// Draws the scenery for the bob at `bob`, before the pendulum itself.
+    pub fn draw(&self, canvas: &mut WindowCanvas, bob: (i16, i16)) {
+        if self.wall {
+            canvas.set_draw_color(WALL);
+            canvas.clear();
+            canvas.set_draw_color(FLOOR_COLOR);
+            canvas
+                .fill_rect(Rect::new(0, FLOOR as i32, WIDTH, HEIGHT - FLOOR as u32))
+                .unwrap();
+            canvas
+                .hline(0, WIDTH as i16, FLOOR, Color::RGB(150, 130, 105))
+                .expect("Unable to draw line");
+        }
+        if self.shadows {
+            self.draw_shadows(canvas, bob);
+        }
+        if self.stand {
+            let beam = CENTER.1 - 12;
+            // base, upright and the beam reaching over the pivot
+            canvas
+                .box_(POST - 30, FLOOR - 10, POST + 30, FLOOR, WOOD)
+                .expect("Unable to draw box");
+            canvas
+                .box_(POST - 5, beam, POST + 5, FLOOR - 10, WOOD)
+                .expect("Unable to draw box");
+            canvas
+                .box_(CENTER.0 - 16, beam, POST + 5, beam + 8, WOOD)
+                .expect("Unable to draw box");
+            canvas
+                .box_(CENTER.0 - 2, beam + 8, CENTER.0 + 2, CENTER.1, WOOD)
+                .expect("Unable to draw box");
+        }
+    }
+
+    fn draw_shadows(&self, canvas: &mut WindowCanvas, bob: (i16, i16)) {
+        // on the wall, the pendulum shifted away from the light
+        if self.wall {
+            let (dx, dy) = WALL_OFFSET;
+            canvas
+                .thick_line(
+                    CENTER.0 + dx,
+                    CENTER.1 + dy,
+                    bob.0 + dx,
+                    bob.1 + dy,
+                    2,
+                    SHADOW,
+                )
+                .expect("Unable to draw line");
+            canvas
+                .filled_circle(bob.0 + dx, bob.1 + dy, 6, SHADOW)
+                .expect("Unable to draw circle");
+        }
+        // on the floor, where the light through the rod and the bob lands
+        let floor_x =
+            |(x, y): (i16, i16)| x + ((FLOOR - y) as f64 * LIGHT.0 / LIGHT.1).round() as i16;
+        let y = FLOOR + 3;
+        canvas
+            .thick_line(floor_x(CENTER), y, floor_x(bob), y, 2, SHADOW)
+            .expect("Unable to draw line");
+        // softer and wider the higher the bob is, from a few stacked translucent ellipses
+        let height = (FLOOR - bob.1) as f64;
+        let spread = 1.0 + height / 150.0;
+        for ring in 1..=3 {
+            let size = spread * ring as f64;
+            canvas
+                .filled_ellipse(
+                    floor_x(bob),
+                    y,
+                    (4.0 * size).round() as i16,
+                    (1.0 + size / 2.0).round() as i16,
+                    Color::RGBA(0, 0, 0, (60.0 / spread) as u8 / 2),
+                )
+                .expect("Unable to draw ellipse");
+        }
+    }
+}
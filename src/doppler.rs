@@ -0,0 +1,142 @@
+use std::f32::consts::TAU;
+
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+use sdl2::AudioSubsystem;
+
+// Hz, what the bob sounds like standing still
+const EMITTED: f64 = 440.0;
+// px around the listener that pick it up with the mouse
+const GRAB: i32 = 12;
+// fraction of the volume gap closed every sample, to keep changes click free
+const SMOOTHING: f32 = 0.002;
+
+/// Sine wave played by SDL's audio thread, its pitch and volume set from the
+/// main loop through the device lock.
+struct Tone {
+    sample_rate: f32,
+    // in turns
+    phase: f32,
+    frequency: f32,
+    volume: f32,
+    // volume actually playing, easing towards `volume`
+    level: f32,
+}
+
+impl AudioCallback for Tone {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            self.level += (self.volume - self.level) * SMOOTHING;
+            *sample = (self.phase * TAU).sin() * self.level;
+            self.phase = (self.phase + self.frequency / self.sample_rate).fract();
+        }
+    }
+}
+
+/// The bob as a source of sound heard from a listener that can be dragged
+/// around the window, its pitch shifted up while the bob comes closer and
+/// down while it moves away: f = f0·c / (c - v), v being the bob's speed
+/// towards the listener.
+///
+/// One pixel is one cm, as for the pendulum. The delay of the sound reaching
+/// the listener is left out, so pitch follows the bob without lag.
+pub struct Doppler {
+    device: AudioDevice<Tone>,
+    pub listener: (i16, i16),
+    dragging: bool,
+    // cm/s
+    sound_speed: f64,
+    // Hz, and from 0 to 1, last heard
+    pub heard: f64,
+    pub volume: f64,
+}
+
+impl Doppler {
+    /// Opens the default output device; `sound_speed` is in cm/s.
+    pub fn open(
+        audio: &AudioSubsystem,
+        listener: (i16, i16),
+        sound_speed: f64,
+    ) -> Result<Self, String> {
+        let desired = AudioSpecDesired {
+            freq: Some(44_100),
+            channels: Some(1),
+            samples: None,
+        };
+        let device = audio.open_playback(None, &desired, |spec| Tone {
+            sample_rate: spec.freq as f32,
+            phase: 0.0,
+            frequency: EMITTED as f32,
+            volume: 0.0,
+            level: 0.0,
+        })?;
+        device.resume();
+        Ok(Doppler {
+            device,
+            listener,
+            dragging: false,
+            sound_speed,
+            heard: EMITTED,
+            volume: 0.0,
+        })
+    }
+
+    /// Drags the listener with the left mouse button.
+    pub fn handle(&mut self, event: &Event) {
+        match *event {
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                self.dragging = (x - self.listener.0 as i32).abs() <= GRAB
+                    && (y - self.listener.1 as i32).abs() <= GRAB;
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => self.dragging = false,
+            Event::MouseMotion { x, y, .. } if self.dragging => {
+                self.listener = (x as i16, y as i16);
+            }
+            _ => {}
+        }
+    }
+
+    /// Retunes the tone for the bob at `bob` moving at `velocity`, in px and px/s.
+    pub fn update(&mut self, bob: (f64, f64), velocity: (f64, f64)) {
+        let (dx, dy) = (
+            self.listener.0 as f64 - bob.0,
+            self.listener.1 as f64 - bob.1,
+        );
+        let distance = dx.hypot(dy).max(1.0);
+        // the shift blows up as the bob nears the speed of sound, never let it reach it
+        let approach = ((velocity.0 * dx + velocity.1 * dy) / distance).min(0.9 * self.sound_speed);
+        self.heard = EMITTED * self.sound_speed / (self.sound_speed - approach);
+        let mut tone = self.device.lock();
+        tone.frequency = self.heard as f32;
+        // quieter further away, without getting deafening up close
+        self.volume = 0.25 * 100.0 / distance.max(100.0);
+        tone.volume = self.volume as f32;
+    }
+
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        let (x, y) = self.listener;
+        canvas
+            .filled_circle(x, y, 7, Color::RGB(0, 150, 0))
+            .expect("Unable to draw circle");
+        // sound arriving, as two arcs facing left
+        for radius in [11, 16] {
+            canvas
+                .arc(x, y, radius, 135, 225, Color::RGB(0, 150, 0))
+                .expect("Unable to draw arc");
+        }
+    }
+}
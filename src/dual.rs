@@ -0,0 +1,117 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use crate::scalar::Scalar;
+
+/// Forward-mode dual number `re + du·ε` with `ε² = 0`.
+///
+/// Carrying `du` through the equations gives the derivative of every quantity
+/// with respect to whichever input was seeded with `du = 1`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dual {
+    pub re: f64,
+    pub du: f64,
+}
+
+impl Dual {
+    pub fn constant(re: f64) -> Self {
+        Dual { re, du: 0.0 }
+    }
+
+    // the input we differentiate with respect to
+    pub fn variable(re: f64) -> Self {
+        Dual { re, du: 1.0 }
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, rhs: Dual) -> Dual {
+        Dual {
+            re: self.re + rhs.re,
+            du: self.du + rhs.du,
+        }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, rhs: Dual) -> Dual {
+        Dual {
+            re: self.re - rhs.re,
+            du: self.du - rhs.du,
+        }
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, rhs: Dual) -> Dual {
+        Dual {
+            re: self.re * rhs.re,
+            du: self.du * rhs.re + self.re * rhs.du,
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, rhs: Dual) -> Dual {
+        Dual {
+            re: self.re / rhs.re,
+            du: (self.du * rhs.re - self.re * rhs.du) / (rhs.re * rhs.re),
+        }
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        Dual {
+            re: -self.re,
+            du: -self.du,
+        }
+    }
+}
+
+impl AddAssign for Dual {
+    fn add_assign(&mut self, rhs: Dual) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Dual {
+    fn sub_assign(&mut self, rhs: Dual) {
+        *self = *self - rhs;
+    }
+}
+
+impl Scalar for Dual {
+    fn from_f64(value: f64) -> Self {
+        Dual::constant(value)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.re
+    }
+
+    fn sin(self) -> Self {
+        Dual {
+            re: self.re.sin(),
+            du: self.du * self.re.cos(),
+        }
+    }
+
+    // the derivative of |x| taken as the right one at nought
+    fn abs(self) -> Self {
+        if self.re < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::f64::consts::PI;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+use sdl2::rwops::RWops;
+
+use crate::bundle;
+use crate::cli::RngOptions;
+use crate::physics::{DoubleParams, DoublePendulum};
+use crate::rng::{self, Rng};
+use crate::{text, BORDER, HEIGHT, WIDTH};
+
+const PARAMS: DoubleParams = DoubleParams {
+    l1: 100.0,
+    l2: 100.0,
+    m1: 1.0,
+    m2: 1.0,
+    gravity: 981.0,
+};
+// s
+const DT: f64 = 0.001;
+// steps between samples, so consecutive outputs come from well separated states
+const STEPS_PER_SAMPLE: usize = 16;
+// side of the square of recent bytes in the visualization
+const GRID: u32 = 64;
+
+/// Toy random byte source hashing the state of a chaotic double pendulum.
+///
+/// NOT cryptographically secure: the state is small, the dynamics are public
+/// and the seed comes from the clock.
+pub struct PendulumRng {
+    pendulum: DoublePendulum,
+}
+
+impl PendulumRng {
+    // the seed picks the initial angles, both well above horizontal so the motion is chaotic
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        PendulumRng {
+            pendulum: DoublePendulum::new(rng.range(0.5 * PI, PI), rng.range(0.5 * PI, PI)),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        for _ in 0..STEPS_PER_SAMPLE {
+            self.pendulum.step(&PARAMS, DT);
+        }
+        let DoublePendulum {
+            theta1,
+            theta2,
+            w1,
+            w2,
+        } = self.pendulum;
+        rng::mix(
+            theta1.to_bits()
+                ^ theta2.to_bits().rotate_left(17)
+                ^ w1.to_bits().rotate_left(31)
+                ^ w2.to_bits().rotate_left(47),
+        )
+    }
+}
+
+/// Runs the `rng` subcommand.
+pub fn run(options: &RngOptions) {
+    let seed = options.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+    let mut rng = PendulumRng::new(seed);
+    if options.show {
+        show(&mut rng);
+        return;
+    }
+    let out = io::stdout();
+    let mut out = BufWriter::new(out.lock());
+    let mut written = 0;
+    while options.bytes.is_none_or(|it| written < it) {
+        for byte in rng.next_u64().to_le_bytes() {
+            if options.bytes.is_some_and(|it| written >= it) {
+                break;
+            }
+            let result = if options.hex {
+                write!(out, "{:02x}", byte)
+            } else {
+                out.write_all(&[byte])
+            };
+            // the reader went away, e.g. `pendulum rng | head -c 100`
+            if result.is_err() {
+                process::exit(0);
+            }
+            written += 1;
+        }
+    }
+    if options.hex {
+        let _ = writeln!(out);
+    }
+}
+
+// window showing the pendulum, the latest bytes as pixels and their histogram
+fn show(rng: &mut PendulumRng) {
+    let sdl_context = sdl2::init().unwrap();
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(bundle::font()).unwrap(), 18)
+        .unwrap();
+    let mut window = sdl_context
+        .video()
+        .unwrap()
+        .window("Pendulum RNG", WIDTH, HEIGHT)
+        .position_centered()
+        .build()
+        .expect("Window couldn't be created.");
+    bundle::set_icon(&mut window);
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    let mut grid = texture_creator
+        .create_texture_streaming(PixelFormatEnum::RGB24, GRID, GRID)
+        .unwrap();
+
+    let mut recent = vec![0u8; (GRID * GRID) as usize];
+    let mut next = 0;
+    let mut histogram = [0u64; 256];
+    let mut total: u64 = 0;
+    let mut events = sdl_context.event_pump().unwrap();
+    'main: loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'main,
+                _ => {}
+            }
+        }
+
+        for _ in 0..16 {
+            for byte in rng.next_u64().to_le_bytes() {
+                recent[next] = byte;
+                next = (next + 1) % recent.len();
+                histogram[byte as usize] += 1;
+                total += 1;
+            }
+        }
+
+        canvas.set_draw_color(Color::WHITE);
+        canvas.clear();
+
+        // the pendulum, scaled down to fit on the left
+        let pivot = (150i16, 150i16);
+        let scale = 0.6;
+        let p = rng.pendulum;
+        let x1 = pivot.0 + (p.theta1.sin() * PARAMS.l1 * scale).round() as i16;
+        let y1 = pivot.1 + (p.theta1.cos() * PARAMS.l1 * scale).round() as i16;
+        let x2 = x1 + (p.theta2.sin() * PARAMS.l2 * scale).round() as i16;
+        let y2 = y1 + (p.theta2.cos() * PARAMS.l2 * scale).round() as i16;
+        canvas
+            .aa_line(pivot.0, pivot.1, x1, y1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .aa_line(x1, y1, x2, y2, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(x1, y1, 4, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .filled_circle(x2, y2, 4, Color::RED)
+            .expect("Unable to draw circle");
+
+        // the latest bytes, one gray pixel each
+        grid.with_lock(None, |pixels, pitch| {
+            for (i, byte) in recent.iter().enumerate() {
+                let (row, column) = (i / GRID as usize, i % GRID as usize);
+                let offset = row * pitch + column * 3;
+                pixels[offset..offset + 3].copy_from_slice(&[*byte; 3]);
+            }
+        })
+        .unwrap();
+        canvas
+            .copy(
+                &grid,
+                None,
+                Rect::new(320, 2 * BORDER + 24, 4 * GRID, 4 * GRID),
+            )
+            .unwrap();
+
+        draw_histogram(&mut canvas, &histogram, Rect::new(BORDER, 320, 580, 70));
+
+        let lines = [
+            "Toy generator, NOT cryptographically secure".to_string(),
+            format!(
+                "{} bytes, entropy: {:.4} bits/byte",
+                total,
+                entropy(&histogram, total)
+            ),
+        ];
+        for (line, y) in lines.iter().zip([BORDER, HEIGHT as i32 - BORDER - 24]) {
+            let texture = text(&texture_creator, &font, line);
+            let query = texture.query();
+            canvas
+                .copy(
+                    &texture,
+                    None,
+                    Rect::new(BORDER, y, query.width, query.height),
+                )
+                .unwrap();
+        }
+
+        canvas.present();
+    }
+}
+
+fn draw_histogram(canvas: &mut WindowCanvas, histogram: &[u64; 256], rect: Rect) {
+    let max = histogram.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bar = rect.width() as f64 / 256.0;
+    for (value, count) in histogram.iter().enumerate() {
+        let height = (*count as f64 / max * rect.height() as f64).round() as i16;
+        let x = rect.left() as f64 + value as f64 * bar;
+        canvas
+            .box_(
+                x.round() as i16,
+                rect.bottom() as i16 - height,
+                (x + bar).round() as i16 - 1,
+                rect.bottom() as i16,
+                Color::RGB(80, 80, 200),
+            )
+            .expect("Unable to draw box");
+    }
+}
+
+// Shannon entropy of the observed byte frequencies, 8 bits/byte at best
+fn entropy(histogram: &[u64; 256], total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    histogram
+        .iter()
+        .filter(|it| **it > 0)
+        .map(|it| {
+            let p = *it as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
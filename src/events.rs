@@ -0,0 +1,85 @@
+use std::f64::consts::{PI, TAU};
+
+/// Something notable that happened in the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SimEvent {
+    // the bob passed through the bottom of the swing
+    ZeroCrossing,
+    // the bob went over the top
+    Flip,
+    // the swing turned back higher than ever since the last reset
+    AmplitudeRecord,
+}
+
+impl SimEvent {
+    pub const ALL: [SimEvent; 3] = [
+        SimEvent::ZeroCrossing,
+        SimEvent::Flip,
+        SimEvent::AmplitudeRecord,
+    ];
+
+    // name used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            SimEvent::ZeroCrossing => "zero-crossing",
+            SimEvent::Flip => "flip",
+            SimEvent::AmplitudeRecord => "amplitude-record",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        SimEvent::ALL.into_iter().find(|it| it.name() == name)
+    }
+}
+
+// rad a turning point has to beat the record by, so rounding doesn't set new ones
+const RECORD_MARGIN: f64 = 0.01;
+
+/// Compares consecutive states of the pendulum to find events.
+#[derive(Clone)]
+pub struct Detector {
+    theta: f64,
+    // |θ| was growing at the last update
+    rising: bool,
+    // rad, the highest turning point so far
+    pub record: f64,
+}
+
+impl Detector {
+    pub fn new(theta: f64) -> Self {
+        Detector {
+            theta: wrap(theta),
+            rising: false,
+            // starting at rest, the release is the first turning point
+            record: wrap(theta).abs(),
+        }
+    }
+
+    pub fn update(&mut self, theta: f64) -> Vec<SimEvent> {
+        let (before, after) = (self.theta, wrap(theta));
+        self.theta = after;
+        let mut events = Vec::new();
+        // a sign change near the bottom, or the jump from π to -π at the top
+        let flipped = (before < 0.0) != (after < 0.0) && (after - before).abs() >= PI;
+        if (before < 0.0) != (after < 0.0) && !flipped {
+            events.push(SimEvent::ZeroCrossing);
+        }
+        if flipped {
+            events.push(SimEvent::Flip);
+        }
+        if after.abs() != before.abs() {
+            let rising = after.abs() > before.abs();
+            if self.rising && !rising && !flipped && before.abs() > self.record + RECORD_MARGIN {
+                self.record = before.abs();
+                events.push(SimEvent::AmplitudeRecord);
+            }
+            self.rising = rising;
+        }
+        events
+    }
+}
+
+// angle in [-π, π), 0 at the bottom
+pub fn wrap(theta: f64) -> f64 {
+    (theta + PI).rem_euclid(TAU) - PI
+}
@@ -0,0 +1,239 @@
+use std::f64::consts::PI;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+use crate::physics::{DoubleParams, DoublePendulum};
+
+// px of the map on a side, and the finest squares on a side of it
+pub const SIZE: i32 = 96;
+const CELLS: i32 = 48;
+// finest squares on a side of the first, coarsest pass, each pass after
+// halving them
+const COARSEST: i32 = 16;
+// s and step of the trial swings, a flip any later counting as none
+const TRIAL: f64 = 10.0;
+const TRIAL_DT: f64 = 0.01;
+// shades from the quickest flips to the slowest, on a log scale from
+// the first step to the end of the trial
+const SHADES: usize = 12;
+
+/// When a double pendulum let go from rest from a square of the map first
+/// flips either rod over the top.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Flip {
+    // not enough energy to ever get over the top
+    Never,
+    // enough, but still not over by the end of the trial
+    Later,
+    After(f64),
+}
+
+/// The flip-time fractal of the double pendulum: over every pair of start
+/// angles from -π to π, θ1 across and θ2 up, how long it takes from rest
+/// for either rod to flip, darker the sooner. It is worked out a few
+/// squares at a time, coarse to fine, each pass filling in between the
+/// squares of the one before, so a rough picture comes up at once and
+/// sharpens meanwhile.
+pub struct FlipMap {
+    params: DoubleParams,
+    // squares in the order they are worked out, as the index of the finest
+    // at their corner and how many finest they cover on a side
+    order: Vec<(usize, i32)>,
+    flips: Vec<Flip>,
+}
+
+impl FlipMap {
+    pub fn new(params: DoubleParams) -> Self {
+        let mut order = Vec::new();
+        let mut side = COARSEST;
+        while side >= 1 {
+            for row in (0..CELLS).step_by(side as usize) {
+                for column in (0..CELLS).step_by(side as usize) {
+                    // the corners of the pass before are done already
+                    let done = side < COARSEST && row % (2 * side) == 0 && column % (2 * side) == 0;
+                    if !done {
+                        order.push(((row * CELLS + column) as usize, side));
+                    }
+                }
+            }
+            side /= 2;
+        }
+        FlipMap {
+            params,
+            order,
+            flips: Vec::new(),
+        }
+    }
+
+    // fraction of the squares worked out
+    pub fn mapped(&self) -> f64 {
+        self.flips.len() as f64 / self.order.len() as f64
+    }
+
+    /// The start angles θ1 and θ2 of the square at `(x, y)` px from the
+    /// map's top left corner, if that's on it.
+    pub fn angles(x: i32, y: i32) -> Option<(f64, f64)> {
+        let cell = SIZE / CELLS;
+        ((0..SIZE).contains(&x) && (0..SIZE).contains(&y))
+            .then(|| (angle(x / cell), -angle(y / cell)))
+    }
+
+    // `cells` more squares of the map, each by letting a double pendulum
+    // go from its corner
+    fn map(&mut self, cells: usize) {
+        for _ in 0..cells.min(self.order.len() - self.flips.len()) {
+            let (i, _) = self.order[self.flips.len()];
+            let (column, row) = (i as i32 % CELLS, i as i32 / CELLS);
+            self.flips
+                .push(flip(&self.params, angle(column), -angle(row)));
+        }
+    }
+
+    /// Works out `cells` more squares, then draws the map with its top left
+    /// corner at `corner` and a cross where `start` is on it.
+    pub fn draw(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        corner: (i32, i32),
+        cells: usize,
+        start: (f64, f64),
+    ) {
+        self.map(cells);
+        let cell = SIZE / CELLS;
+        canvas.set_draw_color(Color::RGB(225, 225, 225));
+        canvas
+            .fill_rect(Rect::new(corner.0, corner.1, SIZE as u32, SIZE as u32))
+            .expect("Unable to draw squares");
+        // each pass in one go per shade, later passes over the earlier
+        let mut from = 0;
+        while from < self.flips.len() {
+            let side = self.order[from].1;
+            let to = from
+                + self.order[from..self.flips.len()]
+                    .iter()
+                    .take_while(|it| it.1 == side)
+                    .count();
+            let mut shades: Vec<Vec<Rect>> = vec![Vec::new(); SHADES + 2];
+            for (&(i, _), &flip) in self.order[from..to].iter().zip(&self.flips[from..to]) {
+                let (column, row) = (i as i32 % CELLS, i as i32 / CELLS);
+                shades[shade(flip)].push(Rect::new(
+                    corner.0 + column * cell,
+                    corner.1 + row * cell,
+                    (side * cell) as u32,
+                    (side * cell) as u32,
+                ));
+            }
+            for (i, squares) in shades.iter().enumerate() {
+                canvas.set_draw_color(color(i));
+                canvas.fill_rects(squares).expect("Unable to draw squares");
+            }
+            from = to;
+        }
+
+        // the start, wrapped onto the map
+        let px = |theta: f64| ((theta + PI).rem_euclid(2.0 * PI) / (2.0 * PI) * SIZE as f64) as i32;
+        let (x, y) = (corner.0 + px(start.0), corner.1 + SIZE - 1 - px(start.1));
+        canvas.set_draw_color(Color::BLACK);
+        canvas
+            .draw_line((x - 3, y), (x + 3, y))
+            .and_then(|_| canvas.draw_line((x, y - 3), (x, y + 3)))
+            .expect("Unable to draw line");
+    }
+}
+
+// rad, of the middle of the `index`th finest square from the left
+fn angle(index: i32) -> f64 {
+    -PI + (index as f64 + 0.5) / CELLS as f64 * 2.0 * PI
+}
+
+// when a double pendulum let go from rest at these angles first flips
+fn flip(params: &DoubleParams, theta1: f64, theta2: f64) -> Flip {
+    let DoubleParams { l1, l2, m1, m2, .. } = *params;
+    // height of the bobs' weight from rest, which the one over the top
+    // with the other hanging straight down from it is the lowest of
+    let height = |theta1: f64, theta2: f64| -(m1 + m2) * l1 * theta1.cos() - m2 * l2 * theta2.cos();
+    if height(theta1, theta2) < height(PI, 0.0).min(height(0.0, PI)) {
+        return Flip::Never;
+    }
+    let mut pendulum = DoublePendulum::new(theta1, theta2);
+    for i in 0..(TRIAL / TRIAL_DT) as usize {
+        pendulum.step(params, TRIAL_DT);
+        if pendulum.theta1.abs() > PI || pendulum.theta2.abs() > PI {
+            return Flip::After((i + 1) as f64 * TRIAL_DT);
+        }
+    }
+    Flip::Later
+}
+
+// which of the colors `flip` is drawn in
+fn shade(flip: Flip) -> usize {
+    match flip {
+        Flip::Never => SHADES,
+        Flip::Later => SHADES + 1,
+        Flip::After(time) => {
+            let fraction = (time / TRIAL_DT).ln() / (TRIAL / TRIAL_DT).ln();
+            ((fraction * SHADES as f64) as usize).min(SHADES - 1)
+        }
+    }
+}
+
+// dark red for the quickest flips to pale yellow for the slowest, white
+// for none and grey for none yet
+fn color(shade: usize) -> Color {
+    if shade == SHADES {
+        return Color::RGB(250, 250, 250);
+    }
+    if shade > SHADES {
+        return Color::RGB(200, 200, 200);
+    }
+    let t = shade as f64 / (SHADES - 1) as f64;
+    let mix = |from: f64, to: f64| (from + (to - from) * t) as u8;
+    Color::RGB(mix(110.0, 250.0), mix(0.0, 230.0), mix(40.0, 150.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: DoubleParams = DoubleParams {
+        l1: 100.0,
+        l2: 100.0,
+        m1: 1.0,
+        m2: 1.0,
+        gravity: 981.0,
+    };
+
+    #[test]
+    fn works_out_every_square_once_coarse_first() {
+        let map = FlipMap::new(PARAMS);
+        let mut seen = vec![false; (CELLS * CELLS) as usize];
+        for &(i, _) in &map.order {
+            assert!(!seen[i]);
+            seen[i] = true;
+        }
+        assert!(seen.iter().all(|it| *it));
+        assert!(map.order.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(map.order[0], (0, COARSEST));
+    }
+
+    #[test]
+    fn flips_only_with_the_energy_to() {
+        // 3·cos θ1 + cos θ2 > 2 keeps equal rods and bobs from ever flipping
+        assert_eq!(flip(&PARAMS, 0.5, 0.5), Flip::Never);
+        assert_eq!(flip(&PARAMS, -1.0, 0.8), Flip::Never);
+        // even a rod nearly upright over one hanging straight down
+        assert_eq!(flip(&PARAMS, 0.0, PI - 0.1), Flip::Never);
+        // both high up have enough, bent against each other the sooner
+        let time = |theta1, theta2| match flip(&PARAMS, theta1, theta2) {
+            Flip::After(time) => time,
+            other => panic!("{:?}", other),
+        };
+        assert!(time(-2.0, 2.8) < 1.0);
+        assert!(time(2.5, 2.5) > time(-2.0, 2.8));
+        assert_eq!(FlipMap::angles(SIZE, 0), None);
+        let (theta1, theta2) = FlipMap::angles(0, 0).unwrap();
+        assert!(theta1 < -3.0 && theta2 > 3.0);
+    }
+}
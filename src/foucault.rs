@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::input::{self, Action};
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm of wire, and cm the bob is pulled aside and let go from, at rest, due east
+const LENGTH: f64 = 200.0;
+const AMPLITUDE: f64 = 40.0;
+// rad/s the Earth turns at, once a sidereal day
+const EARTH: f64 = 7.2921e-5;
+// rad of swing a substep may take at most, however fast time is run
+const MAX_PHASE: f64 = 0.1;
+// swings drawn from above, one kept each time the plane has turned by
+// another half degree, so that they cover half a turn, where they repeat
+const TRACE: usize = 360;
+const TRACE_TURN: f64 = PI / 360.0;
+// px per cm, looking down on the swing
+const SCALE: f64 = 4.0;
+/// What the latitude, in degrees north, and the speed-up of time can be set to.
+pub const LATITUDES: RangeInclusive<f64> = -90.0..=90.0;
+pub const WARPS: RangeInclusive<f64> = 1.0..=10000.0;
+
+/// The main window's Foucault mode: a long pendulum on the turning Earth,
+/// the plane it swings in turning clockwise in the north, once every
+/// sidereal day over the sine of the latitude. Time runs faster so that
+/// the plane can be seen turning, and V looks down on it, where the swings
+/// drawn one after the other make a rosette.
+///
+/// The swings are small next to the wire, so the bob moves in the plane
+/// and only the upward part Ω·sin φ of the Earth's turning matters:
+/// x'' = -g/L·x + 2·Ω·sin φ·y', y'' = -g/L·y - 2·Ω·sin φ·x'.
+pub struct FoucaultMode {
+    // degrees north, and how many times faster than real time it swings
+    pub latitude: f64,
+    pub warp: f64,
+    // cm and cm/s, x east and y north
+    state: [f64; 4],
+    // simulated seconds since the last reset, and those the pendulum swung
+    // for, sped up
+    pub time: f64,
+    elapsed: f64,
+    // rad the plane has turned clockwise as measured at the ends of the
+    // swings, and as the Earth's turning says it should
+    turned: f64,
+    expected: f64,
+    // cm from below the pivot at the last two substeps, to catch the ends
+    // of the swings, and the last end
+    rho: [f64; 2],
+    end: (f64, f64),
+    // ends of the swings kept, and how far the plane had turned at the last
+    trace: VecDeque<((f64, f64), (f64, f64))>,
+    traced: f64,
+    top_down: bool,
+}
+
+impl FoucaultMode {
+    pub fn new(latitude: f64, warp: f64) -> Self {
+        FoucaultMode {
+            latitude,
+            warp,
+            state: [AMPLITUDE, 0.0, 0.0, 0.0],
+            time: 0.0,
+            elapsed: 0.0,
+            turned: 0.0,
+            expected: 0.0,
+            rho: [AMPLITUDE; 2],
+            end: (AMPLITUDE, 0.0),
+            trace: VecDeque::with_capacity(TRACE),
+            traced: 0.0,
+            top_down: false,
+        }
+    }
+
+    /// Switches between the views from the side and from above on V,
+    /// returning whether `event` was that, which then does nothing else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        if input::action(event) != Some(Action::View) {
+            return false;
+        }
+        self.top_down = !self.top_down;
+        true
+    }
+
+    // rad/s the swing plane turns at, clockwise seen from above
+    fn precession(&self) -> f64 {
+        EARTH * self.latitude.to_radians().sin()
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let [x, y, vx, vy] = self.state;
+        vec![("x", x), ("y", y), ("vx", vx), ("vy", vy)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let warped = dt * self.warp;
+        let substeps = ((G / LENGTH).sqrt() * warped / MAX_PHASE).ceil().max(1.0) as usize;
+        let spin = self.precession();
+        for _ in 0..substeps {
+            self.state = physics::rk4(self.state, warped / substeps as f64, |&[x, y, vx, vy]| {
+                [
+                    vx,
+                    vy,
+                    -G / LENGTH * x + 2.0 * spin * vy,
+                    -G / LENGTH * y - 2.0 * spin * vx,
+                ]
+            });
+            self.end_of_swing();
+        }
+        self.time += dt;
+        self.elapsed += warped;
+        self.expected += spin * warped;
+    }
+
+    // the plane measured again if the bob has just reached the end of a swing
+    fn end_of_swing(&mut self) {
+        let [x, y, ..] = self.state;
+        let rho = x.hypot(y);
+        if self.rho[1] > self.rho[0] && self.rho[1] > rho {
+            // a line, the same either way round
+            let turn = y.atan2(x) - self.end.1.atan2(self.end.0);
+            self.turned -= (turn + PI / 2.0).rem_euclid(PI) - PI / 2.0;
+            if self.trace.is_empty() || (self.turned - self.traced).abs() >= TRACE_TURN {
+                if self.trace.len() == TRACE {
+                    self.trace.pop_front();
+                }
+                self.trace.push_back((self.end, (x, y)));
+                self.traced = self.turned;
+            }
+            self.end = (x, y);
+        }
+        self.rho = [self.rho[1], rho];
+    }
+
+    // rad/s added to the swing, away from below the pivot
+    pub fn kick(&mut self, dw: f64) {
+        let [x, y, ..] = self.state;
+        let distance = x.hypot(y);
+        let (along_x, along_y) = if distance > 0.0 {
+            (x / distance, y / distance)
+        } else {
+            (1.0, 0.0)
+        };
+        self.state[2] += dw * LENGTH * along_x;
+        self.state[3] += dw * LENGTH * along_y;
+    }
+
+    // `degrees` further north, and `factor` times faster
+    pub fn retune(&mut self, degrees: f64, factor: f64) {
+        self.latitude = (self.latitude + degrees).clamp(*LATITUDES.start(), *LATITUDES.end());
+        self.warp = (self.warp * factor).clamp(*WARPS.start(), *WARPS.end());
+    }
+
+    /// Draws the pendulum from the south, or from above with the swings
+    /// traced and the plane the Earth's turning says they should be in.
+    pub fn draw(&self, canvas: &mut WindowCanvas, detail: f64) {
+        let [x, y, ..] = self.state;
+        if !self.top_down {
+            let drop = (LENGTH * LENGTH - x * x - y * y).max(0.0).sqrt();
+            let bob = (CENTER.0 + x.round() as i16, CENTER.1 + drop.round() as i16);
+            canvas
+                .hline(CENTER.0 - 30, CENTER.0 + 30, CENTER.1, Color::BLACK)
+                .expect("Unable to draw line");
+            canvas
+                .aa_line(CENTER.0, CENTER.1, bob.0, bob.1, Color::BLACK)
+                .expect("Unable to draw line");
+            canvas
+                .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+                .expect("Unable to draw circle");
+            return;
+        }
+
+        canvas
+            .aa_circle(
+                CENTER.0,
+                CENTER.1,
+                (AMPLITUDE * SCALE).round() as i16,
+                Color::RGB(200, 200, 200),
+            )
+            .expect("Unable to draw circle");
+        let shown = ((TRACE as f64 * detail).round() as usize).max(1);
+        for (age, &(a, b)) in self.trace.iter().rev().take(shown).enumerate() {
+            let (a, b) = (screen(a), screen(b));
+            let alpha = 160 - 160 * age / shown;
+            canvas
+                .aa_line(a.0, a.1, b.0, b.1, Color::RGBA(0, 0, 255, alpha as u8))
+                .expect("Unable to draw line");
+        }
+        let (sin, cos) = self.expected.sin_cos();
+        let (a, b) = (
+            screen((AMPLITUDE * cos, -AMPLITUDE * sin)),
+            screen((-AMPLITUDE * cos, AMPLITUDE * sin)),
+        );
+        canvas
+            .aa_line(a.0, a.1, b.0, b.1, Color::RGBA(255, 0, 0, 160))
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 2, Color::BLACK)
+            .expect("Unable to draw circle");
+        let bob = screen((x, y));
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let spin = self.precession();
+        vec![
+            format!(
+                "latitude: {:.1}° {}, time ×{:.0}, {:.1} h swung",
+                self.latitude.abs(),
+                if self.latitude < 0.0 { "S" } else { "N" },
+                self.warp,
+                self.elapsed / 3600.0
+            ),
+            format!(
+                "plane turned {:+.1}° clockwise, {:+.1}° expected",
+                self.turned.to_degrees(),
+                self.expected.to_degrees()
+            ),
+            if spin.abs() < 1e-9 {
+                "the plane never turns on the equator".to_string()
+            } else {
+                format!(
+                    "a turn every {:.1} h, {}",
+                    2.0 * PI / spin.abs() / 3600.0,
+                    if spin > 0.0 {
+                        "clockwise"
+                    } else {
+                        "counterclockwise"
+                    }
+                )
+            },
+        ]
+    }
+}
+
+// where a point in cm east and north of below the pivot is seen from above, north up
+fn screen((x, y): (f64, f64)) -> (i16, i16) {
+    (
+        CENTER.0 + (x * SCALE).round() as i16,
+        CENTER.1 - (y * SCALE).round() as i16,
+    )
+}
@@ -0,0 +1,76 @@
+use crate::bundle;
+use crate::snapshot::Snapshot;
+
+// examples listed, as many as the digits open
+const SHOWN: usize = 9;
+
+/// One of the example scenes, each a file of a `# <title>` line and a
+/// `# <what to watch for>` line above the snapshot or link it starts from.
+pub struct Example {
+    pub title: String,
+    pub about: String,
+    pub snapshot: Snapshot,
+}
+
+impl Example {
+    fn parse(source: &str, name: &str, fresh: &Snapshot) -> Result<Self, String> {
+        let mut comments = source
+            .lines()
+            .filter_map(|it| it.trim().strip_prefix('#'))
+            .map(|it| it.trim().to_string());
+        let title = comments
+            .next()
+            .ok_or_else(|| format!("{}: expected a `# <title>` line first", name))?;
+        let about = comments.next().unwrap_or_default();
+        let rest: Vec<&str> = source
+            .lines()
+            .filter(|it| !it.trim().starts_with('#'))
+            .collect();
+        let snapshot = Snapshot::parse(&rest.join("\n"), fresh)
+            .map_err(|error| format!("{}: {}", name, error))?;
+        Ok(Example {
+            title,
+            about,
+            snapshot,
+        })
+    }
+}
+
+/// The examples, one keypress away on O: the packaged `examples` folder's
+/// if there is one, or the built-in ones.
+pub struct Gallery {
+    pub examples: Vec<Example>,
+}
+
+impl Gallery {
+    /// Reads the examples, in the order of their file names, leaving out
+    /// the ones that don't parse with a warning, and taking what they leave
+    /// out from `fresh`.
+    pub fn load(fresh: &Snapshot) -> Self {
+        let examples = bundle::examples()
+            .into_iter()
+            .filter_map(|(name, source)| {
+                Example::parse(&source, &name, fresh)
+                    .map_err(|error| eprintln!("Skipping example {}", error))
+                    .ok()
+            })
+            .take(SHOWN)
+            .collect();
+        Gallery { examples }
+    }
+
+    /// The lines of the dialog listing them.
+    pub fn overlay(&self) -> Vec<String> {
+        let mut lines = vec!["Examples, O to close:".to_string()];
+        for (i, example) in self.examples.iter().enumerate() {
+            lines.push(format!("{}: {}", i + 1, example.title));
+            if !example.about.is_empty() {
+                lines.push(format!("    {}", example.about));
+            }
+        }
+        if self.examples.is_empty() {
+            lines.push("none found".to_string());
+        }
+        lines
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use sdl2::controller::GameController;
+use sdl2::GameControllerSubsystem;
+
+use crate::events::SimEvent;
+
+/// Rumble played on a controller for one event.
+#[derive(Clone, Copy, Debug)]
+pub struct Pulse {
+    // 0 to 1
+    pub strength: f64,
+    pub duration_ms: u32,
+}
+
+impl Pulse {
+    pub fn default_for(event: SimEvent) -> Option<Pulse> {
+        match event {
+            SimEvent::ZeroCrossing => Some(Pulse {
+                strength: 0.4,
+                duration_ms: 50,
+            }),
+            SimEvent::Flip | SimEvent::AmplitudeRecord => None,
+        }
+    }
+}
+
+/// Rumbles the connected game controllers when simulation events happen.
+pub struct Haptics {
+    subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+    pulses: HashMap<SimEvent, Pulse>,
+}
+
+impl Haptics {
+    pub fn new(subsystem: GameControllerSubsystem, pulses: HashMap<SimEvent, Pulse>) -> Self {
+        Haptics {
+            subsystem,
+            controllers: Vec::new(),
+            pulses,
+        }
+    }
+
+    // `index` is the joystick device index of a ControllerDeviceAdded event
+    pub fn connect(&mut self, index: u32) {
+        if let Ok(controller) = self.subsystem.open(index) {
+            self.controllers.push(controller);
+        }
+    }
+
+    // `which` is the instance id of a ControllerDeviceRemoved event
+    pub fn disconnect(&mut self, which: u32) {
+        self.controllers.retain(|it| it.instance_id() != which);
+    }
+
+    pub fn play(&mut self, event: SimEvent) {
+        let Some(pulse) = self.pulses.get(&event) else {
+            return;
+        };
+        let strength = (pulse.strength.clamp(0.0, 1.0) * u16::MAX as f64) as u16;
+        for controller in &mut self.controllers {
+            // controllers without rumble just report an error
+            let _ = controller.set_rumble(strength, strength, pulse.duration_ms);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::f64::consts::TAU;
+use std::fmt::Write as _;
+use std::fs;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::rwops::RWops;
+
+use crate::bundle;
+use crate::cli::ArtOptions;
+use crate::physics::{self, Params, Pendulum};
+use crate::rng::Rng;
+use crate::{text, BORDER, G, HEIGHT, WIDTH};
+
+// rad, small enough that the swings look sinusoidal
+const AMPLITUDE: f64 = 0.3;
+// Hz, the slowest pendulum of a figure
+const BASE_FREQUENCY: f64 = 1.0;
+// simulated seconds per second, so a figure takes a couple of minutes
+const SPEED: f64 = 4.0;
+// s
+const DT: f64 = 1.0 / 600.0;
+// simulated seconds a harmonograph figure runs for before it has faded
+const DURATION: f64 = 300.0;
+// degrees of hue per simulated second
+const HUE_RATE: f64 = 6.0;
+// points per polyline in the SVG, each one stroked in a single color
+const CHUNK: usize = 64;
+// frequency ratios of the two axes that make pleasant figures
+const RATIOS: [(f64, f64); 7] = [
+    (1.0, 1.0),
+    (1.0, 2.0),
+    (2.0, 3.0),
+    (3.0, 4.0),
+    (1.0, 3.0),
+    (3.0, 5.0),
+    (4.0, 5.0),
+];
+
+/// One pendulum of the harmonograph, stepped with the same physics as the
+/// main simulation and losing amplitude to friction.
+struct Oscillator {
+    params: Params<f64>,
+    pendulum: Pendulum<f64>,
+    // 1/s, how fast the swing dies down
+    damping: f64,
+    // share of its axis' deflection
+    weight: f64,
+}
+
+impl Oscillator {
+    fn new(frequency: f64, phase: f64, damping: f64, weight: f64) -> Self {
+        // the length that gives `frequency` for small swings, corrected for the
+        // slightly longer period of a real pendulum at AMPLITUDE
+        let mut params = Params {
+            length: G / (TAU * frequency).powi(2),
+            gravity: G,
+            damping: 0.0,
+            drag: 0.0,
+            wind: 0.0,
+            drive: 0.0,
+            drive_frequency: 0.0,
+            drive2: 0.0,
+            drive_frequency2: 0.0,
+            torque: 0.0,
+        };
+        params.length *= (1.0 / frequency / physics::period(&params, AMPLITUDE)).powi(2);
+        Oscillator {
+            params,
+            pendulum: Pendulum {
+                theta: AMPLITUDE * phase.cos(),
+                w: -AMPLITUDE * TAU * frequency * phase.sin(),
+            },
+            damping,
+            weight,
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        self.pendulum.step(&self.params, dt);
+        self.pendulum.w *= (-self.damping * dt).exp();
+    }
+
+    fn deflection(&self) -> f64 {
+        self.pendulum.theta / AMPLITUDE * self.weight
+    }
+}
+
+/// Two pendulums or more per axis, the pen position being the sum of their
+/// deflections. Without damping and with one pendulum per axis it traces
+/// closed Lissajous curves.
+struct Figure {
+    x: Vec<Oscillator>,
+    y: Vec<Oscillator>,
+    time: f64,
+    // simulated seconds until the figure is complete
+    duration: f64,
+}
+
+impl Figure {
+    fn random(rng: &mut Rng, lissajous: bool) -> Self {
+        let (a, b) = RATIOS[rng.below(RATIOS.len())];
+        let (fx, fy) = (BASE_FREQUENCY * a, BASE_FREQUENCY * b);
+        if lissajous {
+            return Figure {
+                x: vec![Oscillator::new(fx, rng.range(0.0, TAU), 0.0, 1.0)],
+                y: vec![Oscillator::new(fy, rng.range(0.0, TAU), 0.0, 1.0)],
+                time: 0.0,
+                // both axes are back where they started after one base period
+                duration: 1.0 / BASE_FREQUENCY,
+            };
+        }
+        let second = rng.range(0.2, 0.5);
+        // the slight detuning is what makes the figure precess instead of repeating
+        let mut oscillator = |frequency: f64, weight: f64| {
+            Oscillator::new(
+                frequency * (1.0 + rng.range(-0.005, 0.005)),
+                rng.range(0.0, TAU),
+                rng.range(0.01, 0.03),
+                weight,
+            )
+        };
+        Figure {
+            x: vec![oscillator(fx, 1.0 - second), oscillator(fy, second)],
+            y: vec![oscillator(fy, 1.0 - second), oscillator(fx, second)],
+            time: 0.0,
+            duration: DURATION,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.time >= self.duration
+    }
+
+    fn step(&mut self, dt: f64) {
+        for oscillator in self.x.iter_mut().chain(self.y.iter_mut()) {
+            oscillator.step(dt);
+        }
+        self.time += dt;
+    }
+
+    // pen position, each coordinate in [-1, 1]
+    fn pen(&self) -> (f64, f64) {
+        let sum = |axis: &[Oscillator]| axis.iter().map(Oscillator::deflection).sum::<f64>();
+        (sum(&self.x), sum(&self.y))
+    }
+
+    fn color(&self) -> Color {
+        hue(self.time * HUE_RATE)
+    }
+}
+
+/// Runs the `harmonograph` subcommand.
+pub fn run(options: &ArtOptions) {
+    let mut rng = options.seed.map_or_else(Rng::from_time, Rng::new);
+    let sdl_context = sdl2::init().unwrap();
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(bundle::font()).unwrap(), 16)
+        .unwrap();
+    let mut window = sdl_context
+        .video()
+        .unwrap()
+        .window("Harmonograph", WIDTH, HEIGHT)
+        .position_centered()
+        .build()
+        .expect("Window couldn't be created.");
+    bundle::set_icon(&mut window);
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    // the paper, only ever drawn on so the figure builds up
+    let mut paper = texture_creator
+        .create_texture_target(PixelFormatEnum::RGBA8888, WIDTH, HEIGHT)
+        .expect("Unable to create paper texture");
+    let hint = text(&texture_creator, &font, "R: new figure, E: export SVG");
+
+    let mut figure = Figure::random(&mut rng, options.lissajous);
+    let mut strokes: Vec<((f64, f64), Color)> = Vec::new();
+    let mut clear = true;
+    let mut events = sdl_context.event_pump().unwrap();
+    'main: loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'main,
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    ..
+                } => {
+                    figure = Figure::random(&mut rng, options.lissajous);
+                    strokes.clear();
+                    clear = true;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::E),
+                    ..
+                } => match fs::write(&options.svg, svg(&strokes)) {
+                    Ok(()) => println!("Saved {}", options.svg),
+                    Err(error) => eprintln!("Unable to write {}: {}", options.svg, error),
+                },
+                _ => {}
+            }
+        }
+
+        // the new stretch of line, starting from where the last frame ended
+        let mut segment = vec![strokes
+            .last()
+            .copied()
+            .unwrap_or((screen(figure.pen()), figure.color()))];
+        let mut elapsed = 0.0;
+        while elapsed < SPEED / 60.0 && !figure.is_done() {
+            figure.step(DT);
+            elapsed += DT;
+            segment.push((screen(figure.pen()), figure.color()));
+        }
+        canvas
+            .with_texture_canvas(&mut paper, |paper| {
+                if clear {
+                    paper.set_draw_color(Color::WHITE);
+                    paper.clear();
+                }
+                for pair in segment.windows(2) {
+                    let ((x0, y0), _) = pair[0];
+                    let ((x1, y1), color) = pair[1];
+                    paper
+                        .aa_line(
+                            x0.round() as i16,
+                            y0.round() as i16,
+                            x1.round() as i16,
+                            y1.round() as i16,
+                            color,
+                        )
+                        .expect("Unable to draw line");
+                }
+            })
+            .expect("Unable to draw on paper");
+        clear = false;
+        let skip = if strokes.is_empty() { 0 } else { 1 };
+        strokes.extend_from_slice(&segment[skip..]);
+
+        canvas.copy(&paper, None, None).unwrap();
+        let query = hint.query();
+        canvas
+            .copy(
+                &hint,
+                None,
+                Rect::new(
+                    BORDER,
+                    HEIGHT as i32 - BORDER - query.height as i32,
+                    query.width,
+                    query.height,
+                ),
+            )
+            .unwrap();
+        canvas.present();
+    }
+}
+
+fn screen((x, y): (f64, f64)) -> (f64, f64) {
+    let radius = (HEIGHT as f64 / 2.0) - 2.0 * BORDER as f64;
+    (
+        WIDTH as f64 / 2.0 + x * radius,
+        HEIGHT as f64 / 2.0 + y * radius,
+    )
+}
+
+fn svg(strokes: &[((f64, f64), Color)]) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        WIDTH, HEIGHT
+    );
+    // consecutive chunks share their end points so the line stays unbroken
+    let mut start = 0;
+    while start + 1 < strokes.len() {
+        let end = (start + CHUNK).min(strokes.len() - 1);
+        let color = strokes[start + 1].1;
+        let _ = write!(
+            out,
+            "<polyline fill=\"none\" stroke=\"rgb({},{},{})\" points=\"",
+            color.r, color.g, color.b
+        );
+        for ((x, y), _) in &strokes[start..=end] {
+            let _ = write!(out, "{:.1},{:.1} ", x, y);
+        }
+        out.push_str("\"/>\n");
+        start = end;
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+// saturated color at `degrees` around the color wheel, dark enough to stand out on white
+fn hue(degrees: f64) -> Color {
+    const VALUE: f64 = 200.0;
+    let h = degrees.rem_euclid(360.0) / 60.0;
+    let (v, x) = (
+        VALUE as u8,
+        ((1.0 - (h % 2.0 - 1.0).abs()) * VALUE).round() as u8,
+    );
+    match h as u32 {
+        0 => Color::RGB(v, x, 0),
+        1 => Color::RGB(x, v, 0),
+        2 => Color::RGB(0, v, x),
+        3 => Color::RGB(0, x, v),
+        4 => Color::RGB(x, 0, v),
+        _ => Color::RGB(v, 0, x),
+    }
+}
@@ -0,0 +1,232 @@
+use sdl2::controller::Button;
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+
+/// Something the user asked the main window to do, whichever device it came
+/// from. What an action does can still depend on the state the window is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Reset,
+    Pause,
+    // one frame forward while paused
+    Step,
+    Sensitivity,
+    Bounds,
+    Precision,
+    // the next integration scheme
+    Integrator,
+    // angular momentum and torque
+    Channels,
+    // off -> free running -> disciplined -> off
+    Clock,
+    Decor,
+    Projection,
+    Phasor,
+    Doppler,
+    // the rod by 10 cm, gravity by 0.5 m/s²
+    Longer,
+    Shorter,
+    Heavier,
+    Lighter,
+    // Earth's -> the Moon's -> Mars's -> Jupiter's -> next to none -> Earth's
+    World,
+    // viscous drag, by 0.05 /s
+    MoreDamping,
+    LessDamping,
+    // the driving torque's amplitude by 0.25 rad/s², its frequency by 0.02 rad/s
+    StrongerDrive,
+    WeakerDrive,
+    FasterDrive,
+    SlowerDrive,
+    // what the changes above keep constant, cycling through them
+    Conserve,
+    // the events log panel, and writing the log to a file
+    Log,
+    ExportLog,
+    // peak-hold markers, and clearing them without resetting the pendulum
+    Peaks,
+    ClearPeaks,
+    // the next of the single pendulum, the chain, the spring, the spherical and
+    // Kapitza pendulums, the cart-pole, the magnetic, Foucault and conical
+    // pendulums, the pendulum wave, the coupled pendulums, the compound
+    // pendulum, the bob on a string and the swing
+    Mode,
+    Quiz,
+    // the examples to open, on the digits while it's shown
+    Gallery,
+    // the usage statistics and achievements, over every session
+    Stats,
+    // the kinetic, potential and total energy, and the bar of them
+    Energy,
+    // the cart-pole's controller, off -> PID -> LQR -> MPC -> off
+    Controller,
+    // the mode's other view, the Foucault pendulum's from above and the
+    // conical pendulum's in perspective
+    View,
+    // back to the last checkpoint of a pendulum that blew up
+    Rollback,
+    // the mode, parameters and state to the clipboard as JSON, or as a
+    // pendulum:// link with Shift, and back from either
+    Copy { link: bool },
+    Paste,
+    // a quiz choice or an example, from 0
+    Answer(usize),
+    // a blow to the bob, backward against the positive direction of θ
+    Kick { backward: bool },
+    // the wind 0.25 m/s² stronger to the right, or to the left
+    Wind { leftward: bool },
+    // a clean one leaves out the HUD, plots and overlays
+    Screenshot { clean: bool },
+}
+
+const KEYS: [(Keycode, Action); 47] = [
+    (Keycode::Escape, Action::Quit),
+    (Keycode::R, Action::Reset),
+    (Keycode::Space, Action::Pause),
+    (Keycode::Period, Action::Step),
+    (Keycode::S, Action::Sensitivity),
+    (Keycode::B, Action::Bounds),
+    (Keycode::P, Action::Precision),
+    (Keycode::T, Action::Channels),
+    (Keycode::Tab, Action::Integrator),
+    (Keycode::C, Action::Clock),
+    (Keycode::D, Action::Decor),
+    (Keycode::H, Action::Projection),
+    (Keycode::Z, Action::Phasor),
+    (Keycode::A, Action::Doppler),
+    (Keycode::Up, Action::Longer),
+    (Keycode::Down, Action::Shorter),
+    (Keycode::Right, Action::Heavier),
+    (Keycode::Left, Action::Lighter),
+    (Keycode::G, Action::World),
+    (Keycode::PageUp, Action::MoreDamping),
+    (Keycode::PageDown, Action::LessDamping),
+    (Keycode::Home, Action::StrongerDrive),
+    (Keycode::End, Action::WeakerDrive),
+    (Keycode::Insert, Action::FasterDrive),
+    (Keycode::Delete, Action::SlowerDrive),
+    (Keycode::K, Action::Conserve),
+    (Keycode::L, Action::Log),
+    (Keycode::E, Action::ExportLog),
+    (Keycode::M, Action::Peaks),
+    (Keycode::N, Action::ClearPeaks),
+    (Keycode::J, Action::Mode),
+    (Keycode::Q, Action::Quiz),
+    (Keycode::O, Action::Gallery),
+    (Keycode::U, Action::Stats),
+    (Keycode::Y, Action::Energy),
+    (Keycode::Backspace, Action::Rollback),
+    (Keycode::F, Action::Controller),
+    (Keycode::V, Action::View),
+    (Keycode::Num1, Action::Answer(0)),
+    (Keycode::Num2, Action::Answer(1)),
+    (Keycode::Num3, Action::Answer(2)),
+    (Keycode::Num4, Action::Answer(3)),
+    (Keycode::Num5, Action::Answer(4)),
+    (Keycode::Num6, Action::Answer(5)),
+    (Keycode::Num7, Action::Answer(6)),
+    (Keycode::Num8, Action::Answer(7)),
+    (Keycode::Num9, Action::Answer(8)),
+];
+
+// the face buttons answer the quiz, so it can be played from the couch
+const BUTTONS: [(Button, Action); 12] = [
+    (Button::Start, Action::Pause),
+    (Button::Back, Action::Reset),
+    (Button::RightShoulder, Action::Step),
+    (Button::LeftShoulder, Action::Quiz),
+    (Button::A, Action::Answer(0)),
+    (Button::B, Action::Answer(1)),
+    (Button::X, Action::Answer(2)),
+    (Button::Y, Action::Answer(3)),
+    (Button::DPadUp, Action::Longer),
+    (Button::DPadDown, Action::Shorter),
+    (Button::DPadRight, Action::Heavier),
+    (Button::DPadLeft, Action::Lighter),
+];
+
+/// The action `event` is bound to, if any. Pointer dragging isn't an action:
+/// the overlays that can be dragged look at the mouse events themselves.
+pub fn action(event: &Event) -> Option<Action> {
+    match *event {
+        Event::Quit { .. } => Some(Action::Quit),
+        Event::KeyDown {
+            keycode: Some(Keycode::F12),
+            keymod,
+            ..
+        } => Some(Action::Screenshot {
+            clean: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        }),
+        // Ctrl, or Cmd on a Mac, before C and V do anything else
+        Event::KeyDown {
+            keycode: Some(keycode @ (Keycode::C | Keycode::V)),
+            keymod,
+            ..
+        } if shortcut(keymod) => Some(if keycode == Keycode::C {
+            Action::Copy {
+                link: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            }
+        } else {
+            Action::Paste
+        }),
+        Event::KeyDown {
+            keycode: Some(Keycode::I),
+            keymod,
+            ..
+        } => Some(Action::Kick {
+            backward: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        }),
+        Event::KeyDown {
+            keycode: Some(Keycode::W),
+            keymod,
+            ..
+        } => Some(Action::Wind {
+            leftward: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        }),
+        Event::KeyDown {
+            keycode: Some(keycode),
+            ..
+        } => lookup(&KEYS, keycode),
+        Event::ControllerButtonDown { button, .. } => lookup(&BUTTONS, button),
+        // a tap on a touch screen, where there is no keyboard to pause with
+        Event::FingerDown { .. } => Some(Action::Pause),
+        _ => None,
+    }
+}
+
+/// The arrow or d-pad direction `event` pressed, with true, or released,
+/// with false, as the action it is bound to, for the modes that go by how
+/// long one is held rather than by the press.
+pub fn held(event: &Event) -> Option<(Action, bool)> {
+    let (action, down) = match *event {
+        Event::KeyDown {
+            keycode: Some(keycode),
+            ..
+        } => (lookup(&KEYS, keycode)?, true),
+        Event::KeyUp {
+            keycode: Some(keycode),
+            ..
+        } => (lookup(&KEYS, keycode)?, false),
+        Event::ControllerButtonDown { button, .. } => (lookup(&BUTTONS, button)?, true),
+        Event::ControllerButtonUp { button, .. } => (lookup(&BUTTONS, button)?, false),
+        _ => return None,
+    };
+    matches!(
+        action,
+        Action::Longer | Action::Shorter | Action::Heavier | Action::Lighter
+    )
+    .then_some((action, down))
+}
+
+/// Whether `keymod` holds the key shortcuts are made with, Ctrl or Cmd.
+pub fn shortcut(keymod: Mod) -> bool {
+    keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD | Mod::LGUIMOD | Mod::RGUIMOD)
+}
+
+fn lookup<T: PartialEq>(table: &[(T, Action)], wanted: T) -> Option<Action> {
+    table
+        .iter()
+        .find(|(it, _)| *it == wanted)
+        .map(|(_, action)| *action)
+}
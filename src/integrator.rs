@@ -0,0 +1,148 @@
+use std::f64::consts::FRAC_PI_2;
+use std::ops::RangeInclusive;
+
+use crate::physics::{Params, Pendulum};
+use crate::scalar::Scalar;
+
+/// s a step can be, `--dt`: much longer steps miss events and go unstable in
+/// the fast modes, much shorter ones can't keep up with the frame rate.
+pub const STEPS: RangeInclusive<f64> = 0.0001..=0.05;
+
+/// A numerical method stepping the pendulum's equation of motion,
+/// dθ/dt = ω, dω/dt = -g/l·sin θ - b/m·ω - k·l·ω·|ω| + w/l·cos θ + A·cos(Ω·t)
+/// + A2·cos(Ω2·t), through time.
+pub trait Integrator {
+    /// Advances `pendulum` by `dt` s from `time`.
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S);
+}
+
+/// dω/dt at `time`, `theta` and `w`.
+pub fn acceleration<S: Scalar>(params: &Params<S>, time: S, theta: S, w: S) -> S {
+    // cos x = sin(x + π/2), scalars only have a sine
+    let quarter = S::from_f64(FRAC_PI_2);
+    let drive = params.drive * (params.drive_frequency * time + quarter).sin()
+        + params.drive2 * (params.drive_frequency2 * time + quarter).sin();
+    let wind = params.wind / params.length * (theta + quarter).sin();
+    -(params.gravity / params.length) * theta.sin()
+        - params.damping * w
+        - params.drag * params.length * w * w.abs()
+        + wind
+        + drive
+        + params.torque
+}
+
+/// Explicit Euler, both updates from the old state. First order, and it
+/// gains energy on every swing until the pendulum goes over the top.
+pub struct Euler;
+
+impl Integrator for Euler {
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S) {
+        let Pendulum { theta, w } = *pendulum;
+        pendulum.w += acceleration(params, time, theta, w) * dt;
+        pendulum.theta += w * dt;
+    }
+}
+
+/// Semi-implicit Euler, the angle moving with the updated velocity. Still
+/// first order, but symplectic: the energy wobbles instead of growing.
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S) {
+        pendulum.w += acceleration(params, time, pendulum.theta, pendulum.w) * dt;
+        pendulum.theta += pendulum.w * dt;
+    }
+}
+
+/// Classic fourth order Runge-Kutta, accurate enough for the amplitude to
+/// hold steady over minutes of swinging, though its energy slowly drains.
+pub struct RungeKutta4;
+
+impl Integrator for RungeKutta4 {
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S) {
+        let Pendulum { theta, w } = *pendulum;
+        let two = S::from_f64(2.0);
+        let half = dt / two;
+        let (k1_theta, k1_w) = (w, acceleration(params, time, theta, w));
+        let k2_theta = w + k1_w * half;
+        let k2_w = acceleration(params, time + half, theta + k1_theta * half, k2_theta);
+        let k3_theta = w + k2_w * half;
+        let k3_w = acceleration(params, time + half, theta + k2_theta * half, k3_theta);
+        let k4_theta = w + k3_w * dt;
+        let k4_w = acceleration(params, time + dt, theta + k3_theta * dt, k4_theta);
+        let sixth = dt / S::from_f64(6.0);
+        pendulum.theta += (k1_theta + two * k2_theta + two * k3_theta + k4_theta) * sixth;
+        pendulum.w += (k1_w + two * k2_w + two * k3_w + k4_w) * sixth;
+    }
+}
+
+/// Velocity Verlet: second order and symplectic, so the energy error stays
+/// bounded on long runs, at the cost of a phase that drifts faster than RK4's.
+pub struct Verlet;
+
+impl Integrator for Verlet {
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S) {
+        let half = dt / S::from_f64(2.0);
+        // with damping, the drag at the end is taken at the half step's velocity
+        let midway = pendulum.w + acceleration(params, time, pendulum.theta, pendulum.w) * half;
+        pendulum.theta += midway * dt;
+        pendulum.w = midway + acceleration(params, time + dt, pendulum.theta, midway) * half;
+    }
+}
+
+/// The integrator picked on the command line or at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    Euler,
+    SemiImplicitEuler,
+    RungeKutta4,
+    Verlet,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 4] = [
+        Scheme::Euler,
+        Scheme::SemiImplicitEuler,
+        Scheme::RungeKutta4,
+        Scheme::Verlet,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Euler => "euler",
+            Scheme::SemiImplicitEuler => "semi-implicit",
+            Scheme::RungeKutta4 => "rk4",
+            Scheme::Verlet => "verlet",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Scheme::ALL.into_iter().find(|it| it.name() == name)
+    }
+
+    /// The order in dt its error is meant to shrink with.
+    pub fn order(self) -> u32 {
+        match self {
+            Scheme::Euler | Scheme::SemiImplicitEuler => 1,
+            Scheme::RungeKutta4 => 4,
+            Scheme::Verlet => 2,
+        }
+    }
+
+    // the one after this in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        let i = Scheme::ALL.iter().position(|it| *it == self).unwrap_or(0);
+        Scheme::ALL[(i + 1) % Scheme::ALL.len()]
+    }
+}
+
+impl Integrator for Scheme {
+    fn step<S: Scalar>(&self, pendulum: &mut Pendulum<S>, params: &Params<S>, time: S, dt: S) {
+        match self {
+            Scheme::Euler => Euler.step(pendulum, params, time, dt),
+            Scheme::SemiImplicitEuler => SemiImplicitEuler.step(pendulum, params, time, dt),
+            Scheme::RungeKutta4 => RungeKutta4.step(pendulum, params, time, dt),
+            Scheme::Verlet => Verlet.step(pendulum, params, time, dt),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use crate::scalar::Scalar;
+
+/// Closed interval `[lo, hi]` guaranteed to contain the exact result.
+///
+/// Every operation rounds its bounds outwards by one ulp, so stepping the
+/// pendulum with intervals encloses what exact arithmetic would have produced
+/// from the same inputs: its width is the accumulated floating point error.
+/// It does not account for the discretization error of the integrator itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub fn point(value: f64) -> Self {
+        Interval {
+            lo: value,
+            hi: value,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    // rounds outwards, covering the rounding error of the operation that produced lo and hi
+    fn widened(lo: f64, hi: f64) -> Self {
+        Interval {
+            lo: lo.next_down(),
+            hi: hi.next_up(),
+        }
+    }
+
+    // whether c + 2kπ falls inside the interval for some integer k
+    fn contains_periodic(&self, c: f64) -> bool {
+        let k = ((self.lo - c) / TAU).ceil();
+        c + k * TAU <= self.hi
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, rhs: Interval) -> Interval {
+        Interval::widened(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, rhs: Interval) -> Interval {
+        Interval::widened(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, rhs: Interval) -> Interval {
+        let products = [
+            self.lo * rhs.lo,
+            self.lo * rhs.hi,
+            self.hi * rhs.lo,
+            self.hi * rhs.hi,
+        ];
+        Interval::widened(
+            products.iter().copied().fold(f64::INFINITY, f64::min),
+            products.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        )
+    }
+}
+
+impl Div for Interval {
+    type Output = Interval;
+
+    fn div(self, rhs: Interval) -> Interval {
+        if rhs.lo <= 0.0 && rhs.hi >= 0.0 {
+            return Interval {
+                lo: f64::NEG_INFINITY,
+                hi: f64::INFINITY,
+            };
+        }
+        self * Interval::widened(1.0 / rhs.hi, 1.0 / rhs.lo)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl AddAssign for Interval {
+    fn add_assign(&mut self, rhs: Interval) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Interval {
+    fn sub_assign(&mut self, rhs: Interval) {
+        *self = *self - rhs;
+    }
+}
+
+impl Scalar for Interval {
+    fn from_f64(value: f64) -> Self {
+        Interval::point(value)
+    }
+
+    // midpoint of the enclosure
+    fn to_f64(self) -> f64 {
+        self.lo + (self.hi - self.lo) / 2.0
+    }
+
+    fn sin(self) -> Self {
+        // also covers infinite or NaN bounds
+        if self.width().is_nan() || self.width() >= TAU {
+            return Interval { lo: -1.0, hi: 1.0 };
+        }
+        let (a, b) = (self.lo.sin(), self.hi.sin());
+        // sin reaches its extremes inside the interval if it spans π/2 or -π/2
+        let hi = if self.contains_periodic(FRAC_PI_2) {
+            1.0
+        } else {
+            a.max(b).next_up().min(1.0)
+        };
+        let lo = if self.contains_periodic(-FRAC_PI_2) {
+            -1.0
+        } else {
+            a.min(b).next_down().max(-1.0)
+        };
+        Interval { lo, hi }
+    }
+
+    fn abs(self) -> Self {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Interval {
+                lo: 0.0,
+                hi: self.hi.max(-self.lo),
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm
+const LENGTH: f64 = 100.0;
+// 1/s, a little drag so the pendulum settles where it's stable
+const DAMPING: f64 = 0.1;
+// rad from upright at the start
+const TILT: f64 = 0.2;
+// rad of vibration a substep may take at most, keeping RK4 on the fast pivot
+const MAX_PHASE: f64 = 0.1;
+/// What the shaking can be set to, in cm and rad/s.
+pub const PIVOT_DEPTHS: RangeInclusive<f64> = 0.0..=15.0;
+pub const PIVOT_FREQUENCIES: RangeInclusive<f64> = 10.0..=300.0;
+
+/// The main window's Kapitza mode: the pivot shaken up and down, a·cos(Ω·t),
+/// fast enough that the pendulum stands upside down.
+///
+/// Averaged over the shaking, the pendulum moves in the effective potential
+/// -g·L·cos θ + (a·Ω·sin θ)²/4 per unit mass, which has a dip at the top as
+/// well as the bottom once a²·Ω² > 2·g·L.
+pub struct KapitzaMode {
+    // cm and rad/s
+    pub depth: f64,
+    pub frequency: f64,
+    // θ, ω and t
+    state: [f64; 3],
+}
+
+impl KapitzaMode {
+    pub fn new(depth: f64, frequency: f64) -> Self {
+        KapitzaMode {
+            depth,
+            frequency,
+            state: [PI - TILT, 0.0, 0.0],
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.state[2]
+    }
+
+    // cm, up from the pivot's middle position
+    fn pivot(&self) -> f64 {
+        self.depth * (self.frequency * self.state[2]).cos()
+    }
+
+    // a²·Ω² / 2·g·L, upright being stable above 1
+    pub fn stability(&self) -> f64 {
+        (self.depth * self.frequency).powi(2) / (2.0 * G * LENGTH)
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        vec![("θ", self.state[0]), ("ω", self.state[1])]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let substeps = (self.frequency * dt / MAX_PHASE).ceil().max(1.0) as usize;
+        let (depth, frequency) = (self.depth, self.frequency);
+        for _ in 0..substeps {
+            self.state = physics::rk4(self.state, dt / substeps as f64, |&[theta, w, t]| {
+                // gravity as felt by the shaken pivot, as in `pendulum parametric`
+                let gravity = G - frequency * frequency * depth * (frequency * t).cos();
+                [w, -gravity / LENGTH * theta.sin() - DAMPING * w, 1.0]
+            });
+        }
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.state[1] += dw;
+    }
+
+    // shakes harder or softer by `depth` cm, faster or slower by `frequency` rad/s
+    pub fn retune(&mut self, depth: f64, frequency: f64) {
+        self.depth = (self.depth + depth).clamp(*PIVOT_DEPTHS.start(), *PIVOT_DEPTHS.end());
+        self.frequency = (self.frequency + frequency)
+            .clamp(*PIVOT_FREQUENCIES.start(), *PIVOT_FREQUENCIES.end());
+    }
+
+    /// Draws the pivot's track and the pendulum hanging from where the
+    /// pivot is.
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        let theta = self.state[0];
+        let pivot = (CENTER.0, CENTER.1 - self.pivot().round() as i16);
+        canvas
+            .vline(
+                CENTER.0,
+                CENTER.1 - self.depth.round() as i16,
+                CENTER.1 + self.depth.round() as i16,
+                Color::RGB(200, 200, 200),
+            )
+            .expect("Unable to draw line");
+        let bob = (
+            pivot.0 + (theta.sin() * LENGTH).round() as i16,
+            pivot.1 + (theta.cos() * LENGTH).round() as i16,
+        );
+        canvas
+            .aa_line(pivot.0, pivot.1, bob.0, bob.1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(pivot.0, pivot.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [theta, w, _] = self.state;
+        let stability = self.stability();
+        vec![
+            format!(
+                "pivot: ±{:.1} cm at {:.0} rad/s",
+                self.depth, self.frequency
+            ),
+            format!(
+                "θ from upright: {:.3} rad, ω: {:.3} rad/s",
+                events::wrap(theta - PI),
+                w
+            ),
+            format!(
+                "a²Ω²/2gL: {:.2}, upright is {}",
+                stability,
+                if stability > 1.0 {
+                    "stable"
+                } else {
+                    "unstable"
+                }
+            ),
+        ]
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+
+use crate::text;
+
+// entries kept for exporting, the oldest are dropped past this
+const KEPT: usize = 1000;
+
+/// Notable events in order, each with the simulation time it happened at,
+/// shown as a panel of the latest ones and exported as text.
+pub struct Logbook {
+    entries: VecDeque<(f64, String)>,
+}
+
+impl Logbook {
+    pub fn new() -> Self {
+        Logbook {
+            entries: VecDeque::new(),
+        }
+    }
+
+    // `time` in simulated seconds
+    pub fn push(&mut self, time: f64, entry: String) {
+        if self.entries.len() == KEPT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((time, entry));
+    }
+
+    /// Writes every entry as a line of `<time> s  <entry>`.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for (time, entry) in &self.entries {
+            writeln!(out, "{:.2} s  {}", time, entry)?;
+        }
+        out.flush()
+    }
+
+    /// Draws as many of the latest entries as fit in `rect`, newest at the bottom.
+    pub fn draw(
+        &self,
+        canvas: &mut WindowCanvas,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: &Font,
+        rect: Rect,
+    ) {
+        canvas
+            .box_(
+                rect.left() as i16,
+                rect.top() as i16,
+                rect.right() as i16,
+                rect.bottom() as i16,
+                Color::RGBA(255, 255, 255, 200),
+            )
+            .expect("Unable to draw box");
+        canvas
+            .rectangle(
+                rect.left() as i16,
+                rect.top() as i16,
+                rect.right() as i16,
+                rect.bottom() as i16,
+                Color::RGBA(0, 0, 0, 100),
+            )
+            .expect("Unable to draw rectangle");
+        let shown = (rect.height() / font.height().max(1) as u32) as usize;
+        let mut y = rect.top() + 1;
+        for (time, entry) in self
+            .entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(shown))
+        {
+            let texture = text(texture_creator, font, &format!("{:.2} s  {}", time, entry));
+            let query = texture.query();
+            // long entries are cut at the edge rather than spilling out
+            let width = query.width.min(rect.width() - 4);
+            canvas
+                .copy(
+                    &texture,
+                    Rect::new(0, 0, width, query.height),
+                    Rect::new(rect.left() + 2, y, width, query.height),
+                )
+                .unwrap();
+            y += query.height as i32;
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::f64::consts::PI;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+use crate::mode::{Trail, SUBSTEPS};
+use crate::physics;
+use crate::quality::Tier;
+use crate::{CENTER, G};
+
+// cm of rod, so the bob's own pull back to the middle is g/L per cm off it
+const LENGTH: f64 = 200.0;
+// cm from the middle the magnets are on a circle at, and cm the bob swings
+// above them
+const RADIUS: f64 = 40.0;
+const HEIGHT: f64 = 10.0;
+// cm³/s², each magnet's pull on the bob times its distance squared: strong
+// enough to hold the bob off the middle, which it makes unstable
+const STRENGTH: f64 = 3.0e5;
+// 1/s of air drag, which lets the bob settle on a magnet at last
+const DAMPING: f64 = 0.4;
+// cm from the middle the bob starts from, at rest
+const START: (f64, f64) = (90.0, 60.0);
+// frames of trail behind the bob, longer than the other modes' as it
+// wanders between the magnets a while before settling
+const TRAIL: usize = 600;
+// px per cm on screen, looking down on the magnets
+const SCALE: f64 = 2.0;
+// px of a square of the basin map, and the squares on either side of it
+const CELL: i32 = 5;
+const CELLS: i32 = 80;
+// squares are mapped in a scrambled order so the whole map fills in
+// together: any step that shares no factor with CELLS² visits each once
+const STRIDE: usize = 2897;
+// s and step of the trial swings mapping the basins, and how slow and how
+// near a magnet the bob has to be to count as settled on it, in cm/s and cm
+const TRIAL: f64 = 40.0;
+const TRIAL_DT: f64 = 0.01;
+const SETTLED: (f64, f64) = (4.0, 20.0);
+/// The magnets' colors, paler for their basins, one each at most.
+pub const COLORS: [Color; 6] = [
+    Color::RGB(220, 50, 50),
+    Color::RGB(40, 150, 60),
+    Color::RGB(50, 80, 220),
+    Color::RGB(230, 150, 20),
+    Color::RGB(150, 60, 190),
+    Color::RGB(20, 160, 170),
+];
+
+/// Which magnet swings from a square of the map end up on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Basin {
+    Unmapped,
+    // still swinging at the end of the trial, or stuck in the exact middle
+    Undecided,
+    Magnet(usize),
+}
+
+/// The main window's magnetic mode: the bob of a long pendulum swinging, seen
+/// from above, over magnets on a circle that pull it with an inverse-square
+/// force. Which one it settles on depends so sensitively on where it is let
+/// go that the map of it, filling in behind the bob in the magnets' colors,
+/// is a fractal. A click lets the bob go from there.
+///
+/// The swings are small next to the rod, so the bob moves in the plane
+/// above the magnets: r'' = -g/L·r - b·r' + Σ S·(m - r)/(|m - r|² + h²)^(3/2).
+pub struct MagneticMode {
+    magnets: Vec<(f64, f64)>,
+    // cm and cm/s, x and y right and down the screen
+    state: [f64; 4],
+    // simulated seconds since the last reset or release
+    pub time: f64,
+    // the magnet it settled on, and when
+    settled: Option<(usize, f64)>,
+    basins: Vec<Basin>,
+    // squares mapped so far
+    mapped: usize,
+    trail: Trail,
+}
+
+impl MagneticMode {
+    pub fn new(magnets: usize) -> Self {
+        let magnets = (0..magnets)
+            .map(|i| {
+                // the first one straight up
+                let angle = 2.0 * PI * i as f64 / magnets as f64 - PI / 2.0;
+                (RADIUS * angle.cos(), RADIUS * angle.sin())
+            })
+            .collect();
+        MagneticMode {
+            magnets,
+            state: [START.0, START.1, 0.0, 0.0],
+            time: 0.0,
+            settled: None,
+            basins: vec![Basin::Unmapped; (CELLS * CELLS) as usize],
+            mapped: 0,
+            trail: Trail::new(TRAIL, Color::RGB(80, 80, 80)),
+        }
+    }
+
+    /// Lets the bob go from where the window is clicked, returning whether
+    /// `event` was a click, which then does nothing else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        let Event::MouseButtonDown {
+            mouse_btn: MouseButton::Left,
+            x,
+            y,
+            ..
+        } = *event
+        else {
+            return false;
+        };
+        self.state = [
+            (x - CENTER.0 as i32) as f64 / SCALE,
+            (y - CENTER.1 as i32) as f64 / SCALE,
+            0.0,
+            0.0,
+        ];
+        self.time = 0.0;
+        self.settled = None;
+        true
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let [x, y, vx, vy] = self.state;
+        vec![("x", x), ("y", y), ("vx", vx), ("vy", vy)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        for _ in 0..SUBSTEPS {
+            self.state = step(&self.magnets, self.state, dt / SUBSTEPS as f64);
+        }
+        self.time += dt;
+        if self.settled.is_none() {
+            self.settled = settled(&self.magnets, &self.state).map(|it| (it, self.time));
+        }
+    }
+
+    // rad/s added to the swing, away from the middle
+    pub fn kick(&mut self, dw: f64) {
+        let [x, y, ..] = self.state;
+        let distance = x.hypot(y);
+        let (along_x, along_y) = if distance > 0.0 {
+            (x / distance, y / distance)
+        } else {
+            (1.0, 0.0)
+        };
+        self.state[2] += dw * LENGTH * along_x;
+        self.state[3] += dw * LENGTH * along_y;
+        self.settled = None;
+    }
+
+    // `cells` more squares of the map, each by letting a bob go from its middle
+    fn map(&mut self, cells: usize) {
+        let total = self.basins.len();
+        for _ in 0..cells.min(total - self.mapped) {
+            let i = self.mapped * STRIDE % total;
+            let (column, row) = (i as i32 % CELLS, i as i32 / CELLS);
+            let at = |index: i32| ((index - CELLS / 2) * CELL + CELL / 2) as f64 / SCALE;
+            let mut state = [at(column), at(row), 0.0, 0.0];
+            let mut basin = Basin::Undecided;
+            for _ in 0..(TRIAL / TRIAL_DT) as usize {
+                state = step(&self.magnets, state, TRIAL_DT);
+                if let Some(magnet) = settled(&self.magnets, &state) {
+                    basin = Basin::Magnet(magnet);
+                    break;
+                }
+            }
+            self.basins[i] = basin;
+            self.mapped += 1;
+        }
+    }
+
+    /// Maps as many more squares of the basins as the `tier` of detail
+    /// allows, then draws the map, the magnets and the bob with its path.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, tier: Tier) {
+        self.map(tier.basins);
+        let detail = tier.trail;
+        let corner = (
+            CENTER.0 as i32 - CELLS / 2 * CELL,
+            CENTER.1 as i32 - CELLS / 2 * CELL,
+        );
+        for basin in (0..self.magnets.len())
+            .map(Basin::Magnet)
+            .chain([Basin::Undecided])
+        {
+            let squares: Vec<Rect> = self
+                .basins
+                .iter()
+                .enumerate()
+                .filter(|(_, it)| **it == basin)
+                .map(|(i, _)| {
+                    let (column, row) = (i as i32 % CELLS, i as i32 / CELLS);
+                    Rect::new(
+                        corner.0 + column * CELL,
+                        corner.1 + row * CELL,
+                        CELL as u32,
+                        CELL as u32,
+                    )
+                })
+                .collect();
+            canvas.set_draw_color(match basin {
+                Basin::Magnet(magnet) => pale(COLORS[magnet]),
+                _ => Color::RGB(225, 225, 225),
+            });
+            canvas.fill_rects(&squares).expect("Unable to draw squares");
+        }
+
+        for (i, &magnet) in self.magnets.iter().enumerate() {
+            let (x, y) = screen(magnet);
+            canvas
+                .filled_circle(x, y, 6, COLORS[i])
+                .expect("Unable to draw circle");
+        }
+        let [x, y, ..] = self.state;
+        let bob = screen((x, y));
+        self.trail.push(bob);
+        self.trail.draw(canvas, detail);
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 2, Color::BLACK)
+            .expect("Unable to draw circle");
+        let color = match self.settled {
+            Some((magnet, _)) => COLORS[magnet],
+            None => Color::BLACK,
+        };
+        canvas
+            .aa_circle(bob.0, bob.1, 5, color)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [x, y, vx, vy] = self.state;
+        vec![
+            format!("r: {:.1} cm, v: {:.1} cm/s", x.hypot(y), vx.hypot(vy)),
+            match self.settled {
+                Some((magnet, time)) => {
+                    format!("settled on magnet {} after {:.1} s", magnet + 1, time)
+                }
+                None => "swinging…".to_string(),
+            },
+            format!(
+                "basins mapped: {:.0}%",
+                100.0 * self.mapped as f64 / self.basins.len() as f64
+            ),
+        ]
+    }
+}
+
+// one RK4 step of the bob over `magnets`
+fn step(magnets: &[(f64, f64)], state: [f64; 4], dt: f64) -> [f64; 4] {
+    physics::rk4(state, dt, |&[x, y, vx, vy]| {
+        let (mut ax, mut ay) = (
+            -G / LENGTH * x - DAMPING * vx,
+            -G / LENGTH * y - DAMPING * vy,
+        );
+        for &(mx, my) in magnets {
+            let (dx, dy) = (mx - x, my - y);
+            let squared = dx * dx + dy * dy + HEIGHT * HEIGHT;
+            let pull = STRENGTH / (squared * squared.sqrt());
+            ax += pull * dx;
+            ay += pull * dy;
+        }
+        [vx, vy, ax, ay]
+    })
+}
+
+// the magnet a bob in `state` has come to rest over, if any
+fn settled(magnets: &[(f64, f64)], state: &[f64; 4]) -> Option<usize> {
+    let [x, y, vx, vy] = *state;
+    if vx.hypot(vy) > SETTLED.0 {
+        return None;
+    }
+    magnets
+        .iter()
+        .position(|&(mx, my)| (mx - x).hypot(my - y) < SETTLED.1)
+}
+
+// where a point in cm from the middle is on the screen
+fn screen((x, y): (f64, f64)) -> (i16, i16) {
+    (
+        CENTER.0 + (x * SCALE).round() as i16,
+        CENTER.1 + (y * SCALE).round() as i16,
+    )
+}
+
+// most of the way to white, for a basin behind its magnet
+fn pale(color: Color) -> Color {
+    let pale = |it: u8| 255 - (255 - it) / 3;
+    Color::RGB(pale(color.r), pale(color.g), pale(color.b))
+}
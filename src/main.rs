@@ -1,17 +1,87 @@
-use std::cmp;
 use std::f64::consts::PI;
+use std::process;
 use std::time::Instant;
 
-use sdl2::event::Event;
 use sdl2::gfx::primitives::DrawRenderer;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use sdl2::pixels::{Color, PixelFormatEnum};
 use sdl2::rect::Rect;
-use sdl2::render::{drivers, WindowCanvas};
+use sdl2::render::{drivers, Texture, TextureCreator, WindowCanvas};
 use sdl2::rwops::RWops;
-use sdl2::video::Window;
+use sdl2::surface::Surface;
+use sdl2::ttf::Font;
+use sdl2::video::{Window, WindowContext};
 use sdl2::{Sdl, VideoSubsystem};
 
+use crate::app::App;
+use crate::cli::Command;
+
+mod app;
+mod audit;
+mod bridge;
+mod bundle;
+mod cart;
+mod chain;
+mod cli;
+mod clock;
+mod compare;
+mod compound;
+mod conical;
+mod control;
+mod convention;
+mod coupled;
+mod decor;
+mod doppler;
+mod dual;
+mod entropy;
+mod events;
+mod flips;
+mod foucault;
+mod gallery;
+mod haptics;
+mod harmonograph;
+mod input;
+mod integrator;
+mod interval;
+mod kapitza;
+mod logbook;
+mod magnetic;
+mod mode;
+mod montecarlo;
+mod net;
+mod panel;
+mod parametric;
+mod peaks;
+mod phasor;
+mod physics;
+mod plot;
+mod projection;
+mod quality;
+mod quiz;
+mod randomize;
+mod recorder;
+mod resonance;
+#[cfg(feature = "serialport")]
+mod rig;
+mod rng;
+mod sand;
+mod scalar;
+mod schedule;
+mod sensors;
+mod simulation;
+mod snapshot;
+mod spherical;
+mod spring;
+mod stats;
+mod string;
+mod swing;
+mod sync;
+mod thermal;
+mod traces;
+#[cfg(feature = "serialport")]
+mod twin;
+mod wave;
+mod wind;
+
 const WIDTH: u32 = 600;
 const HEIGHT: u32 = 440;
 const BORDER: i32 = 10;
@@ -21,11 +91,54 @@ const L: f64 = 200.0;
 const G: f64 = 981.0;
 // cm/s2
 const CENTER: (i16, i16) = (300, 220);
-const THETA_0: f64 = -1.0 * PI * 0.65;
+const THETA_0: f64 = -PI * 0.65;
 
 fn main() {
-    let font_bytes = include_bytes!("../Roboto.ttf");
+    bundle::hint();
+    let options = match Command::from_args() {
+        Command::Simulate(options) => *options,
+        Command::Rng(options) => {
+            entropy::run(&options);
+            return;
+        }
+        Command::Harmonograph(options) => {
+            harmonograph::run(&options);
+            return;
+        }
+        Command::Sand(options) => {
+            sand::run(&options);
+            return;
+        }
+        Command::Parametric(options) => {
+            parametric::run(&options);
+            return;
+        }
+        Command::MonteCarlo(options) => {
+            montecarlo::run(&options);
+            return;
+        }
+        Command::Audit(options) => {
+            audit::run(&options);
+            return;
+        }
+        Command::Resonance(options) => {
+            resonance::run(&options);
+            return;
+        }
+        Command::Compare(options) => {
+            compare::run(&options);
+            return;
+        }
+    };
+    let font_bytes = bundle::font();
+    // files from the command line are checked before any window opens
+    let quiz = app::load_quiz(&options);
+    let schedule = app::load_schedule(&options);
+    let randomizer = app::load_randomizer(&options);
+    let hud_out = app::open_hud_out(&options);
+    let sensors = app::open_sensors_out(&options);
 
+    bundle::high_dpi();
     let sdl_context: Sdl = sdl2::init().unwrap();
     let ttf_context = sdl2::ttf::init().unwrap();
 
@@ -34,38 +147,61 @@ fn main() {
     let font = ttf_context
         .load_font_from_rwops(RWops::from_bytes(font_bytes).unwrap(), 24)
         .unwrap();
-
-    let window: Window = video_subsystem
-        .window("Pendulum", WIDTH, HEIGHT)
-        .opengl()
-        .position_centered()
-        .build()
-        .expect("Window couldn't be created.");
-
-    let mut canvas: WindowCanvas = window
-        .into_canvas()
-        .accelerated()
-        .present_vsync()
-        .index(
-            drivers()
-                .enumerate()
-                .filter(|it| it.1.name == "opengl")
-                .map(|it| it.0 as u32)
-                .next()
-                .unwrap(),
-        )
-        .build()
+    let small_font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(font_bytes).unwrap(), 14)
         .unwrap();
 
+    let mut window_builder = video_subsystem.window("Pendulum", WIDTH, HEIGHT);
+    window_builder.opengl().position_centered().allow_highdpi();
+    // without X or Wayland, as on a Raspberry Pi's console, there is only the whole screen
+    let console = video_subsystem
+        .current_video_driver()
+        .eq_ignore_ascii_case("kmsdrm");
+    if options.screensaver || options.kiosk || console {
+        window_builder.fullscreen_desktop().borderless();
+    }
+    if options.screensaver || options.kiosk {
+        sdl_context.mouse().show_cursor(false);
+    }
+    let mut window: Window = window_builder.build().expect("Window couldn't be created.");
+    bundle::set_icon(&mut window);
+
+    // desktop OpenGL, else OpenGL ES 2 as on a Raspberry Pi, else whatever SDL picks
+    let renderer = options.renderer.as_deref().or_else(|| {
+        ["opengl", "opengles2"]
+            .into_iter()
+            .find(|name| drivers().any(|it| it.name == *name))
+    });
+    let mut canvas_builder = window.into_canvas().present_vsync();
+    if renderer != Some("software") {
+        canvas_builder = canvas_builder.accelerated();
+    }
+    if let Some(name) = renderer {
+        let Some(index) = drivers().position(|it| it.name == name) else {
+            let names: Vec<&str> = drivers().map(|it| it.name).collect();
+            eprintln!("No renderer {}, only {}", name, names.join(", "));
+            process::exit(1);
+        };
+        canvas_builder = canvas_builder.index(index as u32);
+    }
+    let mut canvas: WindowCanvas = canvas_builder.build().unwrap();
+    // everything is drawn for a WIDTH x HEIGHT window, scaled up in fullscreen
+    canvas.set_logical_size(WIDTH, HEIGHT).unwrap();
+
     let texture_creator = canvas.texture_creator();
+    let mut app = App::new(
+        &options,
+        quiz,
+        schedule,
+        randomizer,
+        hud_out,
+        sensors,
+        &sdl_context,
+        &texture_creator,
+    );
 
-    // pendulum angle
-    let mut theta: f64 = THETA_0;
     // instant to calculate dt -> t0
     let mut now = Instant::now();
-    // angular velocity -> w0
-    let mut w: f64 = 0.0;
-
     let mut events = sdl_context.event_pump().unwrap();
     let mut elapsed: u64 = 1;
     'main: loop {
@@ -75,173 +211,102 @@ fn main() {
         canvas.clear();
 
         for event in events.poll_iter() {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => {
-                    break 'main;
-                }
-                _ => {}
+            if !app.handle(&event) {
+                break 'main;
             }
         }
 
-        // calculating the new angular velocity using the approximation to the differential equation
-        // Δω = -g/l * sin(θ) * Δt
         // elapsed time from last iteration
         let delta_t = Instant::now().duration_since(now);
-        w += -1.0 * G / L * theta.sin() * delta_t.as_secs_f64();
-        // calculating the new angle
-        theta += w * delta_t.as_secs_f64();
+        app.update(delta_t.as_secs_f64());
+        app.draw(
+            &mut canvas,
+            &texture_creator,
+            &font,
+            &small_font,
+            timer.performance_frequency() as f64 / elapsed as f64,
+        );
 
-        // calculating position of the pendulum
-        let x: i16 = CENTER.0 + (theta.sin() * L).round() as i16;
-        let y: i16 = CENTER.1 + (theta.cos() * L).round() as i16;
-
-        // drawing pendulum
-        canvas
-            .aa_line(CENTER.0, CENTER.1, x, y, Color::BLACK)
-            .expect("Unable to draw line");
-        canvas
-            .aa_circle(x, y, 5, Color::BLACK)
-            .expect("Unable to draw circle");
-
-        // calculating speed vector of the pendulum
-        let vx: i16 = x + (theta.cos() * L * w / 10.0).round() as i16;
-        let vy: i16 = y - (theta.sin() * L * w / 10.0).round() as i16;
-        canvas
-            .aa_line(x, y, vx, vy, Color::RED)
-            .expect("Unable to draw line");
-
-        let angle = 90 - (theta * 180.0 / PI) as i16;
-        canvas
-            .filled_pie(
-                CENTER.0,
-                CENTER.1,
-                50,
-                cmp::min(angle, 90),
-                cmp::max(angle, 90),
-                Color::RGBA(0, 0, 255, 100),
-            )
-            .unwrap();
-
-        // drawing axis
-        canvas
-            .aa_line(
-                CENTER.0,
-                CENTER.1,
-                CENTER.0,
-                CENTER.1 + 100,
-                Color::RGBA(0, 0, 255, 100),
-            )
-            .expect("Unable to draw line");
-        canvas
-            .aa_line(
-                CENTER.0,
-                CENTER.1,
-                CENTER.0 + 100,
-                CENTER.1,
-                Color::RGBA(0, 0, 255, 100),
-            )
-            .expect("Unable to draw line");
-
-        let radians_per_sec = texture_creator
-            .create_texture_from_surface(
-                &font
-                    .render(format!("ω: {:.3} rad/s", w).as_str())
-                    .blended(Color::BLACK)
-                    .unwrap(),
-            )
-            .unwrap();
+        now = Instant::now();
+        // drawing frame
+        canvas.present();
 
-        let radians = texture_creator
-            .create_texture_from_surface(
-                &font
-                    .render(format!("θ: {:.3} rad", theta).as_str())
-                    .blended(Color::BLACK)
-                    .unwrap(),
-            )
-            .unwrap();
+        elapsed = timer.performance_counter() - start;
+    }
+    app.save_stats();
+}
 
-        let speed = texture_creator
-            .create_texture_from_surface(
-                &font
-                    .render(format!("v: {:.3} m/s", w * L / 100.0).as_str())
-                    .blended(Color::BLACK)
-                    .unwrap(),
-            )
-            .unwrap();
-        let fps = texture_creator
-            .create_texture_from_surface(
-                &font
-                    .render(
-                        format!(
-                            "FPS: {:.2}",
-                            timer.performance_frequency() as f64 / elapsed as f64
-                        )
-                        .as_str(),
-                    )
-                    .blended(Color::BLACK)
-                    .unwrap(),
-            )
-            .unwrap();
+/// Renders a line of HUD text into a texture.
+fn text<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    font: &Font,
+    line: &str,
+) -> Texture<'a> {
+    texture_creator
+        .create_texture_from_surface(font.render(line).blended(Color::BLACK).unwrap())
+        .unwrap()
+}
 
-        let fps_query = fps.query();
+/// Writes RGB24 `pixels` as a BMP, telling the user where it went or why it failed.
+fn save_bmp(pixels: &mut [u8], (width, height): (u32, u32), path: &str) {
+    let saved = Surface::from_data(pixels, width, height, width * 3, PixelFormatEnum::RGB24)
+        .and_then(|surface| surface.save_bmp(path));
+    match saved {
+        Ok(()) => println!("Saved {}", path),
+        Err(error) => eprintln!("Unable to write {}: {}", path, error),
+    }
+}
 
+/// Draws `lines` in a box in the middle of the window, on top of everything else.
+fn draw_dialog(
+    canvas: &mut WindowCanvas,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    lines: &[String],
+) {
+    let textures: Vec<Texture> = lines
+        .iter()
+        .map(|line| text(texture_creator, font, line))
+        .collect();
+    let width = textures
+        .iter()
+        .map(|it| it.query().width)
+        .max()
+        .unwrap_or(0);
+    let height: u32 = textures.iter().map(|it| it.query().height).sum();
+    let frame = Rect::from_center(
+        (WIDTH as i32 / 2, HEIGHT as i32 / 2),
+        width + 2 * BORDER as u32,
+        height + 2 * BORDER as u32,
+    );
+    canvas
+        .box_(
+            frame.left() as i16,
+            frame.top() as i16,
+            frame.right() as i16,
+            frame.bottom() as i16,
+            Color::RGBA(255, 255, 255, 230),
+        )
+        .expect("Unable to draw box");
+    canvas
+        .rectangle(
+            frame.left() as i16,
+            frame.top() as i16,
+            frame.right() as i16,
+            frame.bottom() as i16,
+            Color::BLACK,
+        )
+        .expect("Unable to draw rectangle");
+    let mut y = frame.top() + BORDER;
+    for texture in &textures {
+        let query = texture.query();
         canvas
             .copy(
-                &radians_per_sec,
-                None,
-                Rect::new(
-                    BORDER,
-                    BORDER,
-                    radians_per_sec.query().width,
-                    radians_per_sec.query().height,
-                ),
-            )
-            .unwrap();
-        canvas
-            .copy(
-                &radians,
-                None,
-                Rect::new(
-                    BORDER,
-                    radians_per_sec.query().height as i32 + BORDER,
-                    radians.query().width,
-                    radians.query().height,
-                ),
-            )
-            .unwrap();
-        canvas
-            .copy(
-                &speed,
-                None,
-                Rect::new(
-                    BORDER,
-                    radians_per_sec.query().height as i32 + radians.query().height as i32 + BORDER,
-                    speed.query().width,
-                    speed.query().height,
-                ),
-            )
-            .unwrap();
-        canvas
-            .copy(
-                &fps,
+                texture,
                 None,
-                Rect::new(
-                    WIDTH as i32 - BORDER - fps_query.width as i32,
-                    HEIGHT as i32 - BORDER - fps_query.height as i32,
-                    fps_query.width,
-                    fps_query.height,
-                ),
+                Rect::new(frame.left() + BORDER, y, query.width, query.height),
             )
             .unwrap();
-
-        now = Instant::now();
-        // drawing frame
-        canvas.present();
-
-        elapsed = timer.performance_counter() - start;
+        y += query.height as i32;
     }
 }
@@ -0,0 +1,470 @@
+use std::collections::VecDeque;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::cart::CartMode;
+use crate::chain::ChainMode;
+use crate::cli::Options;
+use crate::compound::CompoundMode;
+use crate::conical::ConicalMode;
+use crate::coupled::CoupledMode;
+use crate::foucault::FoucaultMode;
+use crate::kapitza::KapitzaMode;
+use crate::magnetic::MagneticMode;
+use crate::physics::{ChainParams, SpringParams};
+use crate::quality::Tier;
+use crate::spherical::SphericalMode;
+use crate::spring::SpringMode;
+use crate::string::StringMode;
+use crate::swing::SwingMode;
+#[cfg(feature = "serialport")]
+use crate::twin::Twin;
+use crate::wave::WaveMode;
+use crate::G;
+
+/// What swings in the main window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Single,
+    // rods hanging one from the other, a double pendulum by default
+    Chain,
+    // a bob on a spring instead of a rod
+    Spring,
+    // free to swing in any direction, seen in 3D
+    Spherical,
+    // the pivot shaken up and down, holding the pendulum upside down
+    Kapitza,
+    // standing on a cart, to be balanced with the arrows
+    Cart,
+    // swinging over magnets, settling on one of them
+    Magnetic,
+    // on the turning Earth, the plane of its swing turning with it
+    Foucault,
+    // going steadily round a horizontal circle
+    Conical,
+    // a row of pendulums of graded lengths, swinging in and out of step
+    Wave,
+    // two side by side, a spring between their bobs
+    Coupled,
+    // a rigid body swinging about a pivot, instead of a point bob
+    Compound,
+    // on a string, which goes slack when it would have to push
+    String,
+    // a swing, its rider standing up and squatting to pump it
+    Swing,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 14] = [
+        Mode::Single,
+        Mode::Chain,
+        Mode::Spring,
+        Mode::Spherical,
+        Mode::Kapitza,
+        Mode::Cart,
+        Mode::Magnetic,
+        Mode::Foucault,
+        Mode::Conical,
+        Mode::Wave,
+        Mode::Coupled,
+        Mode::Compound,
+        Mode::String,
+        Mode::Swing,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Single => "single",
+            Mode::Chain => "chain",
+            Mode::Spring => "spring",
+            Mode::Spherical => "spherical",
+            Mode::Kapitza => "kapitza",
+            Mode::Cart => "cart",
+            Mode::Magnetic => "magnetic",
+            Mode::Foucault => "foucault",
+            Mode::Conical => "conical",
+            Mode::Wave => "wave",
+            Mode::Coupled => "coupled",
+            Mode::Compound => "compound",
+            Mode::String => "string",
+            Mode::Swing => "swing",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Mode::ALL.into_iter().find(|it| it.name() == name)
+    }
+
+    // the one after this in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        let i = Mode::ALL.iter().position(|it| *it == self).unwrap_or(0);
+        Mode::ALL[(i + 1) % Mode::ALL.len()]
+    }
+}
+
+/// The pendulum swinging in place of the single one in the other modes,
+/// the single one waiting where it was meanwhile.
+pub enum Alternate {
+    Chain(ChainMode),
+    Spring(SpringMode),
+    Spherical(SphericalMode),
+    Kapitza(KapitzaMode),
+    Cart(CartMode),
+    Magnetic(MagneticMode),
+    Foucault(FoucaultMode),
+    Conical(ConicalMode),
+    Wave(WaveMode),
+    Coupled(CoupledMode),
+    Compound(CompoundMode),
+    String(StringMode),
+    Swing(SwingMode),
+}
+
+impl Alternate {
+    /// A fresh pendulum of `mode` set up from the command line, none for
+    /// the single one. A chain starts from `theta`, a spring from a bounce
+    /// that slowly turns into a swing, a spherical pendulum from a swing
+    /// pushed sideways, a Kapitza pendulum and a cart's pole from a little
+    /// off upright, a bob over magnets from beside them, a Foucault
+    /// pendulum from due east of below its pivot, a conical one already
+    /// going round its circle, a pendulum wave from all in line, the
+    /// coupled pendulums from the left one pulled aside, a compound one from
+    /// a small swing, the bob on a string launched from the bottom and the
+    /// swing from a small push, its rider squatting.
+    pub fn new(mode: Mode, options: &Options, theta: f64) -> Option<Self> {
+        match mode {
+            Mode::Single => None,
+            Mode::Chain => Some(Alternate::Chain(ChainMode::new(
+                ChainParams {
+                    lengths: options.link_lengths.clone(),
+                    masses: options.link_masses.clone(),
+                    gravity: G,
+                },
+                theta,
+                options.traces_out.clone(),
+            ))),
+            Mode::Spring => Some(Alternate::Spring(SpringMode::new(SpringParams {
+                stiffness: options.stiffness,
+                rest_length: options.rest_length,
+                gravity: G,
+            }))),
+            Mode::Spherical => Some(Alternate::Spherical(SphericalMode::new())),
+            Mode::Kapitza => Some(Alternate::Kapitza(KapitzaMode::new(
+                options.pivot_depth,
+                options.pivot_frequency,
+            ))),
+            Mode::Cart => {
+                let cart = CartMode::new(options.dt);
+                #[cfg(feature = "serialport")]
+                let cart = cart.with_twin(options.twin.then(|| Twin::new(options.refit)));
+                Some(Alternate::Cart(cart))
+            }
+            Mode::Magnetic => Some(Alternate::Magnetic(MagneticMode::new(options.magnets))),
+            Mode::Foucault => Some(Alternate::Foucault(FoucaultMode::new(
+                options.latitude,
+                options.warp,
+            ))),
+            Mode::Conical => Some(Alternate::Conical(ConicalMode::new(options.half_angle))),
+            Mode::Wave => Some(Alternate::Wave(WaveMode::new(
+                options.wave_pendulums,
+                options.wave_cycle,
+            ))),
+            Mode::Coupled => Some(Alternate::Coupled(CoupledMode::new(options.coupling))),
+            Mode::Compound => Some(Alternate::Compound(CompoundMode::new(
+                options.body,
+                options
+                    .pivot_distance
+                    .unwrap_or_else(|| options.body.pivot_distance()),
+            ))),
+            Mode::String => Some(Alternate::String(StringMode::new(options.launch))),
+            Mode::Swing => Some(Alternate::Swing(SwingMode::new(
+                options.pump_depth,
+                options.auto_pump,
+            ))),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        match self {
+            Alternate::Chain(_) => Mode::Chain,
+            Alternate::Spring(_) => Mode::Spring,
+            Alternate::Spherical(_) => Mode::Spherical,
+            Alternate::Kapitza(_) => Mode::Kapitza,
+            Alternate::Cart(_) => Mode::Cart,
+            Alternate::Magnetic(_) => Mode::Magnetic,
+            Alternate::Foucault(_) => Mode::Foucault,
+            Alternate::Conical(_) => Mode::Conical,
+            Alternate::Wave(_) => Mode::Wave,
+            Alternate::Coupled(_) => Mode::Coupled,
+            Alternate::Compound(_) => Mode::Compound,
+            Alternate::String(_) => Mode::String,
+            Alternate::Swing(_) => Mode::Swing,
+        }
+    }
+
+    // simulated seconds since the last reset
+    pub fn time(&self) -> f64 {
+        match self {
+            Alternate::Chain(it) => it.time,
+            Alternate::Spring(it) => it.time,
+            Alternate::Spherical(it) => it.time,
+            Alternate::Kapitza(it) => it.time(),
+            Alternate::Cart(it) => it.time,
+            Alternate::Magnetic(it) => it.time,
+            Alternate::Foucault(it) => it.time,
+            Alternate::Conical(it) => it.time,
+            Alternate::Wave(it) => it.time,
+            Alternate::Coupled(it) => it.time,
+            Alternate::Compound(it) => it.time,
+            Alternate::String(it) => it.time,
+            Alternate::Swing(it) => it.time,
+        }
+    }
+
+    /// Passes `event` to the modes that take input of their own, returning
+    /// whether it was theirs.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match self {
+            Alternate::Chain(it) => it.handle(event),
+            Alternate::Cart(it) => it.handle(event),
+            Alternate::Magnetic(it) => it.handle(event),
+            Alternate::Foucault(it) => it.handle(event),
+            Alternate::Conical(it) => it.handle(event),
+            Alternate::Swing(it) => it.handle(event),
+            _ => false,
+        }
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        match self {
+            Alternate::Chain(it) => it.step(dt),
+            Alternate::Spring(it) => it.step(dt),
+            Alternate::Spherical(it) => it.step(dt),
+            Alternate::Kapitza(it) => it.step(dt),
+            Alternate::Cart(it) => it.step(dt),
+            Alternate::Magnetic(it) => it.step(dt),
+            Alternate::Foucault(it) => it.step(dt),
+            Alternate::Conical(it) => it.step(dt),
+            Alternate::Wave(it) => it.step(dt),
+            Alternate::Coupled(it) => it.step(dt),
+            Alternate::Compound(it) => it.step(dt),
+            Alternate::String(it) => it.step(dt),
+            Alternate::Swing(it) => it.step(dt),
+        }
+    }
+
+    // rad/s added to the swing, of the top rod for a chain, away from the vertical for a sphere
+    pub fn kick(&mut self, dw: f64) {
+        match self {
+            Alternate::Chain(it) => it.kick(dw),
+            Alternate::Spring(it) => it.kick(dw),
+            Alternate::Spherical(it) => it.kick(dw),
+            Alternate::Kapitza(it) => it.kick(dw),
+            Alternate::Cart(it) => it.kick(dw),
+            Alternate::Magnetic(it) => it.kick(dw),
+            Alternate::Foucault(it) => it.kick(dw),
+            Alternate::Conical(it) => it.kick(dw),
+            Alternate::Wave(it) => it.kick(dw),
+            Alternate::Coupled(it) => it.kick(dw),
+            Alternate::Compound(it) => it.kick(dw),
+            Alternate::String(it) => it.kick(dw),
+            Alternate::Swing(it) => it.kick(dw),
+        }
+    }
+
+    /// Takes the drive keys in the modes that retune something else with
+    /// them, `amplitude` and `frequency` being which way each was pressed,
+    /// -1, 0 or 1, returning when and what to log. The other modes, none.
+    pub fn retune(&mut self, amplitude: f64, frequency: f64) -> Option<(f64, String)> {
+        match self {
+            // the Kapitza mode's drive is its pivot
+            Alternate::Kapitza(kapitza) => {
+                kapitza.retune(amplitude * 0.5, frequency * 5.0);
+                let message = format!(
+                    "pivot: ±{:.1} cm at {:.0} rad/s",
+                    kapitza.depth, kapitza.frequency
+                );
+                Some((kapitza.time(), message))
+            }
+            // and the Foucault mode's its latitude and the speed of time
+            Alternate::Foucault(foucault) => {
+                foucault.retune(amplitude * 5.0, 2f64.powf(frequency));
+                let message = format!(
+                    "latitude: {:.1}°, time ×{:.0}",
+                    foucault.latitude, foucault.warp
+                );
+                Some((foucault.time, message))
+            }
+            // and the conical mode's its cone, 5° at a time or 1°
+            Alternate::Conical(conical) => {
+                conical.widen(amplitude * 5.0 + frequency);
+                let message = format!("half-angle: {:.1}°", conical.half_angle);
+                Some((conical.time, message))
+            }
+            // and the pendulum wave's its count and cycle
+            Alternate::Wave(wave) => {
+                wave.retune(amplitude as i64, frequency * 10.0);
+                let message = format!("{} pendulums, every {:.0} s", wave.pendulums, wave.cycle);
+                Some((wave.time, message))
+            }
+            // and the coupled pendulums' spring, by 0.5 /s² or 0.1
+            Alternate::Coupled(coupled) => {
+                coupled.stiffen(amplitude * 0.5 + frequency * 0.1);
+                let message = format!("k/m: {:.2} /s²", coupled.coupling);
+                Some((coupled.time, message))
+            }
+            // and the compound pendulum's body and where it hangs from, 5 cm
+            // at a time
+            Alternate::Compound(compound) => {
+                compound.reshape(amplitude as i64, frequency);
+                let message = format!(
+                    "{} hung {:.0} cm from its centre",
+                    compound.body.name(),
+                    compound.distance
+                );
+                Some((compound.time, message))
+            }
+            // and the string's launch, by 0.25 g·L or 0.05
+            Alternate::String(string) => {
+                string.relaunch(amplitude * 0.25 + frequency * 0.05);
+                let message = format!("launched with v²/(g·L) = {:.2}", string.launch);
+                Some((string.time, message))
+            }
+            // and the swing's rider, standing up 5 cm further, and pumping by
+            // themselves or not
+            Alternate::Swing(swing) => {
+                swing.pump(amplitude, (frequency != 0.0).then_some(frequency > 0.0));
+                let message = format!(
+                    "standing up {:.0} cm, {}",
+                    swing.depth,
+                    if swing.auto {
+                        "by themselves"
+                    } else {
+                        "by hand"
+                    }
+                );
+                Some((swing.time, message))
+            }
+            _ => None,
+        }
+    }
+
+    // in as much detail as `tier` has
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, tier: Tier) {
+        let detail = tier.trail;
+        match self {
+            Alternate::Chain(it) => it.draw(canvas, tier),
+            Alternate::Spring(it) => it.draw(canvas, detail),
+            Alternate::Spherical(it) => it.draw(canvas, detail),
+            Alternate::Kapitza(it) => it.draw(canvas),
+            Alternate::Cart(it) => it.draw(canvas, detail),
+            Alternate::Magnetic(it) => it.draw(canvas, tier),
+            Alternate::Foucault(it) => it.draw(canvas, detail),
+            Alternate::Conical(it) => it.draw(canvas, detail),
+            Alternate::Wave(it) => it.draw(canvas),
+            Alternate::Coupled(it) => it.draw(canvas),
+            Alternate::Compound(it) => it.draw(canvas),
+            Alternate::String(it) => it.draw(canvas, detail),
+            Alternate::Swing(it) => it.draw(canvas, detail),
+        }
+    }
+
+    /// What of the mode's state is infinite, NaN or absurd after a step gone
+    /// wrong, if any, named.
+    pub fn nonsense(&self) -> Option<(&'static str, f64)> {
+        let values = match self {
+            Alternate::Chain(it) => it.values(),
+            Alternate::Spring(it) => it.values(),
+            Alternate::Spherical(it) => it.values(),
+            Alternate::Kapitza(it) => it.values(),
+            Alternate::Cart(it) => it.values(),
+            Alternate::Magnetic(it) => it.values(),
+            Alternate::Foucault(it) => it.values(),
+            Alternate::Conical(it) => it.values(),
+            Alternate::Wave(it) => it.values(),
+            Alternate::Coupled(it) => it.values(),
+            Alternate::Compound(it) => it.values(),
+            Alternate::String(it) => it.values(),
+            Alternate::Swing(it) => it.values(),
+        };
+        values
+            .into_iter()
+            .find(|(_, value)| !value.is_finite() || value.abs() > ABSURD)
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        match self {
+            Alternate::Chain(it) => it.lines(),
+            Alternate::Spring(it) => it.lines(),
+            Alternate::Spherical(it) => it.lines(),
+            Alternate::Kapitza(it) => it.lines(),
+            Alternate::Cart(it) => it.lines(),
+            Alternate::Magnetic(it) => it.lines(),
+            Alternate::Foucault(it) => it.lines(),
+            Alternate::Conical(it) => it.lines(),
+            Alternate::Wave(it) => it.lines(),
+            Alternate::Coupled(it) => it.lines(),
+            Alternate::Compound(it) => it.lines(),
+            Alternate::String(it) => it.lines(),
+            Alternate::Swing(it) => it.lines(),
+        }
+    }
+}
+
+// cm, cm/s, rad or rad/s past which a mode's state can only come from an
+// integration gone wrong
+const ABSURD: f64 = 1e6;
+
+/// RK4 steps the other modes take per step of `--dt`, unless theirs need
+/// shorter ones.
+pub const SUBSTEPS: usize = 4;
+/// Frames of trail behind a bob, unless a mode wants a longer one.
+pub const TRAIL: usize = 240;
+
+/// The fading path of a bob over the last few seconds.
+pub struct Trail {
+    points: VecDeque<(i16, i16)>,
+    // frames it lasts
+    length: usize,
+    color: Color,
+}
+
+impl Trail {
+    pub fn new(length: usize, color: Color) -> Self {
+        Trail {
+            points: VecDeque::with_capacity(length),
+            length,
+            color,
+        }
+    }
+
+    pub fn push(&mut self, point: (i16, i16)) {
+        if self.points.len() == self.length {
+            self.points.pop_front();
+        }
+        self.points.push_back(point);
+    }
+
+    // the newest `detail` of it, fading out over that
+    pub fn draw(&self, canvas: &mut WindowCanvas, detail: f64) {
+        let shown = ((self.length as f64 * detail).round() as usize).max(1);
+        for (age, (a, b)) in self
+            .points
+            .iter()
+            .rev()
+            .zip(self.points.iter().rev().skip(1))
+            .take(shown)
+            .enumerate()
+        {
+            let alpha = 160 - 160 * age / shown;
+            let Color { r, g, b: blue, .. } = self.color;
+            canvas
+                .aa_line(a.0, a.1, b.0, b.1, Color::RGBA(r, g, blue, alpha as u8))
+                .expect("Unable to draw line");
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::process;
+use std::time::{Duration, Instant};
+
+use crate::cli::{MonteCarloOptions, Options};
+use crate::events::{Detector, SimEvent};
+use crate::integrator::{Integrator, Scheme};
+use crate::physics::{DoubleParams, DoublePendulum, Params, Pendulum};
+use crate::randomize::{Randomizer, Start};
+use crate::rng::{self, Rng};
+use crate::{G, L, THETA_0};
+
+// bars of each histogram, and the characters of the longest
+const BINS: usize = 20;
+const BAR: usize = 40;
+// how often the estimate on stderr is brought up to date
+const LIVE: Duration = Duration::from_millis(100);
+// how often the checkpoint is written over
+const SAVE: Duration = Duration::from_secs(30);
+// first byte of a checkpoint, bumped whenever its layout changes
+const VERSION: u8 = 1;
+// bytes of each run in a checkpoint, and of what comes before them
+const RUN: usize = 24;
+const HEAD: usize = 51;
+// standard normal quantile of the 95% confidence intervals
+const Z: f64 = 1.96;
+
+/// What one run came to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Outcome {
+    // s, from the first zero crossing to the third, if there were three
+    period: Option<f64>,
+    // over the bob's weight, the most the rod pulled with
+    tension: f64,
+    // s, when it first went over the top, of the second rod with --double
+    flipped: Option<f64>,
+}
+
+/// Runs the `montecarlo` subcommand: many runs of the single pendulum, or
+/// the double one with `--double`, each from a start drawn from the
+/// `--randomize` constraints, and the spread of what they came to written
+/// to stdout. The flip probability so far and its 95% confidence interval
+/// are kept up to date on stderr as the runs go, and with `--checkpoint`
+/// how far they have got is written every so often, for `--resume-from`
+/// to carry on from after an interruption with the same draws.
+pub fn run(options: &MonteCarloOptions) {
+    let source = fs::read_to_string(&options.randomize).unwrap_or_else(|error| {
+        eprintln!("{}: {}", options.randomize, error);
+        process::exit(1);
+    });
+    let randomizer = Randomizer::parse(&source, &options.randomize).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let (seed, mut rng, mut outcomes) = match &options.resume_from {
+        Some(path) => fs::read(path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| resume(&bytes, options, &source))
+            .unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            }),
+        None => {
+            let seed = options.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+            (seed, Rng::new(seed), Vec::new())
+        }
+    };
+    let checkpoint = options.checkpoint.as_ref().or(options.resume_from.as_ref());
+    let head = header(options, seed, &source);
+    let defaults = Options::default();
+    let mut flips = outcomes.iter().filter(|it| it.flipped.is_some()).count();
+    let mut shown = Instant::now();
+    let mut saved = Instant::now();
+    for i in outcomes.len() as u64..options.runs {
+        let outcome = draw(options, &randomizer, &defaults, &mut rng);
+        flips += outcome.flipped.is_some() as usize;
+        outcomes.push(outcome);
+        let done = i + 1 == options.runs;
+        if done || shown.elapsed() >= LIVE {
+            let (lo, hi) = interval(flips, outcomes.len());
+            eprint!(
+                "\r{} of {} runs, flip probability {:.1}%, 95% between {:.1}% and {:.1}%   ",
+                outcomes.len(),
+                options.runs,
+                100.0 * flips as f64 / outcomes.len() as f64,
+                100.0 * lo,
+                100.0 * hi
+            );
+            shown = Instant::now();
+        }
+        if let Some(path) = checkpoint.filter(|_| done || saved.elapsed() >= SAVE) {
+            save(path, &encode(&head, &rng, &outcomes)).unwrap_or_else(|error| {
+                eprintln!("\n{}: {}", path, error);
+                process::exit(1);
+            });
+            saved = Instant::now();
+        }
+    }
+    eprintln!();
+
+    if options.double {
+        println!(
+            "{} runs of {} s of the double pendulum, drawn from {} with seed {}",
+            options.runs, options.duration, options.randomize, seed
+        );
+    } else {
+        println!(
+            "{} runs of {} s with {}, drawn from {} with seed {}",
+            options.runs,
+            options.duration,
+            options.integrator.name(),
+            options.randomize,
+            seed
+        );
+        let periods: Vec<f64> = outcomes.iter().filter_map(|it| it.period).collect();
+        println!();
+        summarize("period", "s", &periods);
+        let tensions: Vec<f64> = outcomes.iter().map(|it| it.tension).collect();
+        println!();
+        summarize("max tension", "mg", &tensions);
+    }
+    let times: Vec<f64> = outcomes.iter().filter_map(|it| it.flipped).collect();
+    println!();
+    summarize("time to the first flip", "s", &times);
+    let (lo, hi) = interval(flips, outcomes.len());
+    println!();
+    println!(
+        "{}flip probability within {} s: {:.1}%, 95% between {:.1}% and {:.1}%, {} of {} going over the top",
+        if options.double { "second rod " } else { "" },
+        options.duration,
+        100.0 * flips as f64 / outcomes.len() as f64,
+        100.0 * lo,
+        100.0 * hi,
+        flips,
+        outcomes.len()
+    );
+}
+
+// one run, from the next start `rng` draws
+fn draw(
+    options: &MonteCarloOptions,
+    randomizer: &Randomizer,
+    defaults: &Options,
+    rng: &mut Rng,
+) -> Outcome {
+    let mut start = Start {
+        params: Params {
+            length: L,
+            gravity: G,
+            damping: defaults.damping,
+            drag: defaults.drag,
+            wind: 0.0,
+            drive: defaults.drive,
+            drive_frequency: defaults.drive_frequency,
+            drive2: defaults.drive2,
+            drive_frequency2: defaults.drive_frequency2,
+            torque: 0.0,
+        },
+        theta: THETA_0,
+        w: 0.0,
+        second: [THETA_0, 0.0],
+    };
+    randomizer.draw(rng, &mut start);
+    // the second rod starts in line with the first unless it is drawn too
+    if !randomizer.sets("theta2") {
+        start.second[0] = start.theta;
+    }
+    if options.double {
+        simulate_double(options, &start)
+    } else {
+        simulate(options, &start)
+    }
+}
+
+// what the draws and the runs depend on, which a resumed batch has to
+// match: the version, the seed, --duration and --dt, the integrator and
+// --double, and a hash of the --randomize constraints
+fn header(options: &MonteCarloOptions, seed: u64, source: &str) -> Vec<u8> {
+    let scheme = Scheme::ALL.iter().position(|&it| it == options.integrator);
+    let hash = source
+        .bytes()
+        .fold(0, |hash, it| rng::mix(hash ^ it as u64));
+    let mut bytes = vec![VERSION];
+    bytes.extend_from_slice(&seed.to_le_bytes());
+    bytes.extend_from_slice(&options.duration.to_le_bytes());
+    bytes.extend_from_slice(&options.dt.to_le_bytes());
+    bytes.push(scheme.unwrap_or(0) as u8);
+    bytes.push(options.double as u8);
+    bytes.extend_from_slice(&hash.to_le_bytes());
+    bytes
+}
+
+// the header, then little-endian the generator's state, how many runs
+// are done and each one's period, most tension and flip time, NaN for none
+fn encode(head: &[u8], rng: &Rng, outcomes: &[Outcome]) -> Vec<u8> {
+    let mut bytes = head.to_vec();
+    bytes.extend_from_slice(&rng.state().to_le_bytes());
+    bytes.extend_from_slice(&(outcomes.len() as u64).to_le_bytes());
+    for outcome in outcomes {
+        bytes.extend_from_slice(&outcome.period.unwrap_or(f64::NAN).to_le_bytes());
+        bytes.extend_from_slice(&outcome.tension.to_le_bytes());
+        bytes.extend_from_slice(&outcome.flipped.unwrap_or(f64::NAN).to_le_bytes());
+    }
+    bytes
+}
+
+// the seed, the generator and the runs done of a checkpoint of `encode`,
+// if it was of a batch with these options
+fn resume(
+    bytes: &[u8],
+    options: &MonteCarloOptions,
+    source: &str,
+) -> Result<(u64, Rng, Vec<Outcome>), String> {
+    let word = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+    if bytes.len() < HEAD || bytes[0] != VERSION {
+        return Err("not a montecarlo checkpoint, or one of another version".to_string());
+    }
+    let seed = word(1);
+    if options.seed.is_some_and(|it| it != seed) {
+        return Err(format!(
+            "the runs were drawn with seed {}, not --seed",
+            seed
+        ));
+    }
+    if !bytes.starts_with(&header(options, seed, source)) {
+        return Err(
+            "the runs were of another --duration, --dt, --integrator, --double or --randomize file"
+                .to_string(),
+        );
+    }
+    let rng = Rng::new(word(HEAD - 16));
+    let count = word(HEAD - 8) as usize;
+    if bytes.len() != HEAD + count * RUN {
+        return Err("cut short, or not a montecarlo checkpoint".to_string());
+    }
+    if count as u64 > options.runs {
+        return Err(format!("already has {} runs, more than --runs", count));
+    }
+    let number = |at: usize| Some(f64::from_bits(word(at))).filter(|it| !it.is_nan());
+    let outcomes = (0..count)
+        .map(|i| HEAD + i * RUN)
+        .map(|at| Outcome {
+            period: number(at),
+            tension: f64::from_bits(word(at + 8)),
+            flipped: number(at + 16),
+        })
+        .collect();
+    Ok((seed, rng, outcomes))
+}
+
+// writes `bytes` beside `path` first and then moves them over it, so that
+// being stopped midway leaves the last checkpoint whole
+fn save(path: &str, bytes: &[u8]) -> io::Result<()> {
+    let partial = format!("{}.partial", path);
+    fs::write(&partial, bytes)?;
+    fs::rename(&partial, path)
+}
+
+// the Wilson score interval of `hits` out of `n`, which unlike p ± z·σ
+// stays within 0 and 1 and doesn't shrink to nothing when none flip yet
+fn interval(hits: usize, n: usize) -> (f64, f64) {
+    let n = n as f64;
+    let p = hits as f64 / n;
+    let z2 = Z * Z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+fn simulate(options: &MonteCarloOptions, start: &Start) -> Outcome {
+    let params = &start.params;
+    let mut pendulum = Pendulum {
+        theta: start.theta,
+        w: start.w,
+    };
+    let mut detector = Detector::new(start.theta);
+    let tension = |pendulum: &Pendulum<f64>| {
+        pendulum.theta.cos() + params.length * pendulum.w * pendulum.w / params.gravity
+    };
+    let mut outcome = Outcome {
+        period: None,
+        tension: tension(&pendulum),
+        flipped: None,
+    };
+    let mut crossings = Vec::new();
+    let steps = (options.duration / options.dt).round() as usize;
+    for i in 0..steps {
+        let time = i as f64 * options.dt;
+        options
+            .integrator
+            .step(&mut pendulum, params, time, options.dt);
+        if !pendulum.theta.is_finite() || !pendulum.w.is_finite() {
+            break;
+        }
+        let now = time + options.dt;
+        for event in detector.update(pendulum.theta) {
+            match event {
+                SimEvent::ZeroCrossing if crossings.len() < 3 => crossings.push(now),
+                SimEvent::Flip if outcome.flipped.is_none() => outcome.flipped = Some(now),
+                _ => {}
+            }
+        }
+        outcome.tension = outcome.tension.max(tension(&pendulum));
+    }
+    if let [first, _, third] = crossings[..] {
+        outcome.period = Some(third - first);
+    }
+    outcome
+}
+
+// the double pendulum, each rod half of `length` with equal bobs, until its
+// second rod first goes over the top, undamped and undriven
+fn simulate_double(options: &MonteCarloOptions, start: &Start) -> Outcome {
+    let params = DoubleParams {
+        l1: start.params.length / 2.0,
+        l2: start.params.length / 2.0,
+        m1: 1.0,
+        m2: 1.0,
+        gravity: start.params.gravity,
+    };
+    let mut pendulum = DoublePendulum {
+        theta1: start.theta,
+        theta2: start.second[0],
+        w1: start.w,
+        w2: start.second[1],
+    };
+    // which turn the second rod is on, counted from straight up
+    let turn = |theta: f64| (theta + PI).div_euclid(2.0 * PI);
+    let first = turn(pendulum.theta2);
+    let mut outcome = Outcome {
+        period: None,
+        tension: 0.0,
+        flipped: None,
+    };
+    let steps = (options.duration / options.dt).round() as usize;
+    for i in 0..steps {
+        pendulum.step(&params, options.dt);
+        if !pendulum.theta2.is_finite() {
+            break;
+        }
+        if turn(pendulum.theta2) != first {
+            outcome.flipped = Some((i + 1) as f64 * options.dt);
+            break;
+        }
+    }
+    outcome
+}
+
+// the mean, σ and a histogram of `values`, in `unit`
+fn summarize(name: &str, unit: &str, values: &[f64]) {
+    if values.is_empty() {
+        println!("{}: none of the runs had one", name);
+        return;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let sigma = (values.iter().map(|it| (it - mean).powi(2)).sum::<f64>() / n).sqrt();
+    println!(
+        "{}: mean {:.4} {}, σ {:.4} {}, from {} runs",
+        name,
+        mean,
+        unit,
+        sigma,
+        unit,
+        values.len()
+    );
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (hi - lo) / BINS as f64;
+    if width <= 0.0 {
+        println!("  all {:.4} {}", lo, unit);
+        return;
+    }
+    let mut counts = [0usize; BINS];
+    for value in values {
+        counts[(((value - lo) / width) as usize).min(BINS - 1)] += 1;
+    }
+    let most = counts.iter().copied().max().unwrap_or(1);
+    for (i, &count) in counts.iter().enumerate() {
+        let from = lo + width * i as f64;
+        println!(
+            "  {:>10.4} - {:<10.4} {:<bar$} {}",
+            from,
+            from + width,
+            "#".repeat(count * BAR / most),
+            count,
+            bar = BAR
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_the_wilson_interval() {
+        let close = |(lo, hi): (f64, f64), (want_lo, want_hi): (f64, f64)| {
+            assert!(
+                (lo - want_lo).abs() < 1e-4 && (hi - want_hi).abs() < 1e-4,
+                "{:?}",
+                (lo, hi)
+            );
+        };
+        close(interval(50, 100), (0.4038, 0.5962));
+        // none yet still leaves room for some, and all for some not
+        close(interval(0, 10), (0.0, 0.2775));
+        close(interval(10, 10), (0.7225, 1.0));
+        for (hits, n) in [(3, 7), (1, 1000), (999, 1000)] {
+            let (lo, hi) = interval(hits, n);
+            let (other_lo, other_hi) = interval(n - hits, n);
+            let p = hits as f64 / n as f64;
+            assert!(lo < p && p < hi);
+            close((lo, hi), (1.0 - other_hi, 1.0 - other_lo));
+        }
+        let (wide, narrow) = (interval(5, 10), interval(500, 1000));
+        assert!(narrow.1 - narrow.0 < wide.1 - wide.0);
+    }
+
+    #[test]
+    fn resumes_as_if_never_stopped() {
+        let source = "theta in 120..170\nw in -1..1\n";
+        let randomizer = Randomizer::parse(source, "test").unwrap();
+        let defaults = Options::default();
+        let mut options = MonteCarloOptions {
+            randomize: "test".to_string(),
+            runs: 6,
+            duration: 5.0,
+            dt: 1.0 / 120.0,
+            integrator: Scheme::RungeKutta4,
+            seed: None,
+            double: false,
+            checkpoint: None,
+            resume_from: None,
+        };
+        let mut rng = Rng::new(7);
+        let straight: Vec<Outcome> = (0..6)
+            .map(|_| draw(&options, &randomizer, &defaults, &mut rng))
+            .collect();
+
+        let mut rng = Rng::new(7);
+        let before: Vec<Outcome> = (0..4)
+            .map(|_| draw(&options, &randomizer, &defaults, &mut rng))
+            .collect();
+        let head = header(&options, 7, source);
+        let bytes = encode(&head, &rng, &before);
+        let (seed, mut rng, mut resumed) = resume(&bytes, &options, source).unwrap();
+        assert_eq!(seed, 7);
+        assert_eq!(resumed, before);
+        while resumed.len() < 6 {
+            resumed.push(draw(&options, &randomizer, &defaults, &mut rng));
+        }
+        assert_eq!(resumed, straight);
+
+        // not of this batch, or not whole
+        assert!(resume(&bytes, &options, "theta in 0..10\n").is_err());
+        assert!(resume(&bytes[..bytes.len() - 1], &options, source).is_err());
+        options.seed = Some(8);
+        assert!(resume(&bytes, &options, source).is_err());
+        options.seed = None;
+        options.dt = 1.0 / 60.0;
+        assert!(resume(&bytes, &options, source).is_err());
+        options.dt = 1.0 / 120.0;
+        options.runs = 3;
+        assert!(resume(&bytes, &options, source).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::sensors::Reading;
+
+const VERSION: u8 = 4;
+// frames queued for a slow viewer before newer ones are dropped
+const BACKLOG: usize = 8;
+const RECONNECT: Duration = Duration::from_secs(1);
+
+/// State the host sends every frame, in s, rad, rad/s and cm, what the
+/// simulated sensors read, as `--sensors-out` writes them, and how the host
+/// is keeping up.
+///
+/// On the wire it is a version byte followed by the four values as
+/// little-endian f64, then the encoder's counts as a little-endian i64, the
+/// accelerometer along the path and the rod and the gyro as f64, and the
+/// host's speed as an f64 and the steps it dropped as a u64, 81 bytes in
+/// total.
+#[derive(Clone, Copy, Debug)]
+pub struct Frame {
+    pub time: f64,
+    pub theta: f64,
+    pub w: f64,
+    // the rod can be retuned on the host
+    pub length: f64,
+    pub reading: Reading,
+    // simulated seconds per wall-clock one on the host, and the steps it
+    // gave up on to keep up
+    pub speed: f64,
+    pub dropped: u64,
+}
+
+impl Frame {
+    const SIZE: usize = 1 + 4 * 8 + 8 + 3 * 8 + 8 + 8;
+
+    fn encode(&self) -> [u8; Frame::SIZE] {
+        let mut bytes = [0; Frame::SIZE];
+        bytes[0] = VERSION;
+        let Reading {
+            counts,
+            tangential,
+            radial,
+            gyro,
+        } = self.reading;
+        for (i, value) in [self.time, self.theta, self.w, self.length]
+            .iter()
+            .enumerate()
+        {
+            bytes[1 + i * 8..9 + i * 8].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes[33..41].copy_from_slice(&counts.to_le_bytes());
+        for (i, value) in [tangential, radial, gyro].iter().enumerate() {
+            bytes[41 + i * 8..49 + i * 8].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes[65..73].copy_from_slice(&self.speed.to_le_bytes());
+        bytes[73..81].copy_from_slice(&self.dropped.to_le_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &[u8; Frame::SIZE]) -> Option<Frame> {
+        if bytes[0] != VERSION {
+            return None;
+        }
+        let eight = |at: usize| -> [u8; 8] { bytes[at..at + 8].try_into().unwrap() };
+        let value = |i: usize| f64::from_le_bytes(eight(1 + i * 8));
+        Some(Frame {
+            time: value(0),
+            theta: value(1),
+            w: value(2),
+            length: value(3),
+            reading: Reading {
+                counts: i64::from_le_bytes(eight(33)),
+                tangential: value(5),
+                radial: value(6),
+                gyro: value(7),
+            },
+            speed: value(8),
+            dropped: u64::from_le_bytes(eight(73)),
+        })
+    }
+}
+
+/// Runs the physics and streams its state to every connected viewer.
+pub struct Host {
+    viewers: Arc<Mutex<Vec<SyncSender<Frame>>>>,
+}
+
+impl Host {
+    pub fn listen(address: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let viewers = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&viewers);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // each viewer gets its own writer so a slow one can't stall the others
+                let (sender, receiver) = mpsc::sync_channel::<Frame>(BACKLOG);
+                accepted.lock().unwrap().push(sender);
+                thread::spawn(move || {
+                    let mut stream = stream;
+                    let _ = stream.set_nodelay(true);
+                    for frame in receiver {
+                        if stream.write_all(&frame.encode()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        Ok(Host { viewers })
+    }
+
+    pub fn broadcast(&self, frame: Frame) {
+        // viewers that went away are forgotten, full queues just skip this frame
+        self.viewers
+            .lock()
+            .unwrap()
+            .retain(|it| !matches!(it.try_send(frame), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+/// Follows a host, reconnecting in the background whenever the link drops.
+pub struct Viewer {
+    latest: Arc<Mutex<Option<Frame>>>,
+    connected: Arc<AtomicBool>,
+}
+
+impl Viewer {
+    // `address` is resolved again on every attempt, so it may be a host name
+    pub fn connect(address: String) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let connected = Arc::new(AtomicBool::new(false));
+        let (frames, link) = (Arc::clone(&latest), Arc::clone(&connected));
+        thread::spawn(move || loop {
+            if let Ok(mut stream) = TcpStream::connect(address.as_str()) {
+                link.store(true, Ordering::Relaxed);
+                let mut bytes = [0; Frame::SIZE];
+                while stream.read_exact(&mut bytes).is_ok() {
+                    match Frame::decode(&bytes) {
+                        Some(frame) => *frames.lock().unwrap() = Some(frame),
+                        // a host speaking another version, try again later
+                        None => break,
+                    }
+                }
+                link.store(false, Ordering::Relaxed);
+            }
+            thread::sleep(RECONNECT);
+        });
+        Viewer { latest, connected }
+    }
+
+    pub fn latest(&self) -> Option<Frame> {
+        *self.latest.lock().unwrap()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let frame = Frame {
+            time: 12.5,
+            theta: -0.75,
+            w: 2.25,
+            length: 150.0,
+            reading: Reading {
+                counts: -489,
+                tangential: 0.125,
+                radial: 9.875,
+                gyro: 2.25,
+            },
+            speed: 0.5,
+            dropped: 1200,
+        };
+        let decoded = Frame::decode(&frame.encode()).unwrap();
+        assert_eq!(
+            (decoded.time, decoded.theta, decoded.w, decoded.length),
+            (frame.time, frame.theta, frame.w, frame.length)
+        );
+        assert_eq!(decoded.reading, frame.reading);
+        assert_eq!(
+            (decoded.speed, decoded.dropped),
+            (frame.speed, frame.dropped)
+        );
+        let mut other = frame.encode();
+        other[0] = VERSION - 1;
+        assert!(Frame::decode(&other).is_none());
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+/// A screen region with its own cached texture that is only redrawn every
+/// `refresh`, so expensive plots don't have to be rebuilt at the frame rate.
+pub struct Panel<'a> {
+    texture: Texture<'a>,
+    rect: Rect,
+    refresh: Duration,
+    last: Option<Instant>,
+}
+
+impl<'a> Panel<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, rect: Rect, hz: f64) -> Self {
+        let mut texture = texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, rect.width(), rect.height())
+            .expect("Unable to create panel texture");
+        texture.set_blend_mode(BlendMode::Blend);
+        Panel {
+            texture,
+            rect,
+            refresh: Duration::from_secs_f64(1.0 / hz),
+            last: None,
+        }
+    }
+
+    pub fn set_hz(&mut self, hz: f64) {
+        self.refresh = Duration::from_secs_f64(1.0 / hz);
+    }
+
+    // forces a redraw on the next frame
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Copies the panel to the canvas, first regenerating it with `render` if
+    /// it is due. `render` draws in panel coordinates, with (0, 0) at its top left.
+    pub fn draw<F>(&mut self, canvas: &mut WindowCanvas, render: F)
+    where
+        F: FnOnce(&mut WindowCanvas, Rect),
+    {
+        if self.last.is_none_or(|it| it.elapsed() >= self.refresh) {
+            let local = Rect::new(0, 0, self.rect.width(), self.rect.height());
+            canvas
+                .with_texture_canvas(&mut self.texture, |panel| {
+                    panel.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                    panel.clear();
+                    render(panel, local);
+                })
+                .expect("Unable to draw panel");
+            self.last = Some(Instant::now());
+        }
+        canvas
+            .copy(&self.texture, None, self.rect)
+            .expect("Unable to copy panel");
+    }
+}
@@ -0,0 +1,269 @@
+use std::f64::consts::TAU;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::Instant;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::rwops::RWops;
+
+use crate::bundle;
+use crate::cli::ParametricOptions;
+use crate::events;
+use crate::physics;
+use crate::{text, BORDER, CENTER, G, HEIGHT, WIDTH};
+
+// cm
+const LENGTH: f64 = 150.0;
+// s
+const DT: f64 = 1.0 / 600.0;
+// s of simulation a frame can fall behind by, past which time is dropped
+// rather than the pendulum racing to catch up after a stall
+const MAX_BACKLOG: f64 = 0.25;
+// 1/s, air drag, without which any drive at 2ω0 would be unstable
+const DAMPING: f64 = 0.05;
+// rad, the tiny swing the instability grows from
+const NUDGE: f64 = 0.01;
+// what the arrows move the drive within, also the extent of the export
+const RATIOS: (f64, f64) = (0.5, 4.0);
+// cm
+const DEPTHS: (f64, f64) = (0.0, 40.0);
+
+/// The pivot moving up and down as a·cos(Ω·t).
+#[derive(Clone, Copy)]
+struct Drive {
+    // Ω/ω0
+    ratio: f64,
+    // a, in cm
+    depth: f64,
+}
+
+impl Drive {
+    // rad/s
+    fn frequency(&self) -> f64 {
+        self.ratio * (G / LENGTH).sqrt()
+    }
+
+    // cm, up from the pivot's middle position
+    fn pivot(&self, time: f64) -> f64 {
+        self.depth * (self.frequency() * time).cos()
+    }
+
+    /// Gravity as felt in the frame of the moving pivot, in cm/s². Swings
+    /// grow when it is strongest as the bob passes the bottom and weakest at
+    /// the ends, twice a swing, which is why 2ω0 pumps them the most.
+    fn gravity(&self, time: f64) -> f64 {
+        G - self.frequency().powi(2) * self.pivot(time)
+    }
+
+    // d/dt of [θ, ω, t], with sin θ ≈ θ when `linear`
+    fn derivatives(&self, y: &[f64; 3], linear: bool) -> [f64; 3] {
+        let restoring = if linear { y[0] } else { y[0].sin() };
+        [
+            y[1],
+            -self.gravity(y[2]) / LENGTH * restoring - DAMPING * y[1],
+            1.0,
+        ]
+    }
+
+    /// Rate in 1/s at which small swings grow, negative when they die out.
+    ///
+    /// From Floquet theory: the linearised motion over one drive period is a
+    /// linear map of (θ, ω), found by stepping both unit states through it,
+    /// and its larger eigenvalue is what a swing is multiplied by per period.
+    fn growth(&self) -> f64 {
+        let period = TAU / self.frequency();
+        let steps = 200;
+        let [a, b] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(|mut y| {
+            for _ in 0..steps {
+                y = physics::rk4(y, period / steps as f64, |y| self.derivatives(y, true));
+            }
+            y
+        });
+        let trace = a[0] + b[1];
+        let determinant = a[0] * b[1] - a[1] * b[0];
+        let discriminant = trace * trace - 4.0 * determinant;
+        let multiplier = if discriminant >= 0.0 {
+            (trace.abs() + discriminant.sqrt()) / 2.0
+        } else {
+            // a complex pair, of equal size
+            determinant.sqrt()
+        };
+        multiplier.ln() / period
+    }
+}
+
+/// Runs the `parametric` subcommand.
+pub fn run(options: &ParametricOptions) {
+    let sdl_context = sdl2::init().unwrap();
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(bundle::font()).unwrap(), 16)
+        .unwrap();
+    let mut window = sdl_context
+        .video()
+        .unwrap()
+        .window("Parametric resonance", WIDTH, HEIGHT)
+        .position_centered()
+        .build()
+        .expect("Window couldn't be created.");
+    bundle::set_icon(&mut window);
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    let hint = text(
+        &texture_creator,
+        &font,
+        "up/down: frequency, left/right: depth, R: restart, E: export",
+    );
+
+    let mut drive = Drive {
+        ratio: options.ratio.clamp(RATIOS.0, RATIOS.1),
+        depth: options.depth.clamp(DEPTHS.0, DEPTHS.1),
+    };
+    let mut growth = drive.growth();
+    // θ, ω and t
+    let mut state = [NUDGE, 0.0, 0.0];
+    // s of wall-clock time not yet simulated, stepped off in fixed steps
+    // so the motion is the same whatever the frame rate
+    let mut accumulator = 0.0;
+    let mut now = Instant::now();
+    let mut events = sdl_context.event_pump().unwrap();
+    'main: loop {
+        for event in events.poll_iter() {
+            let changed = match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'main,
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => match keycode {
+                    Keycode::R => {
+                        state = [NUDGE, 0.0, 0.0];
+                        false
+                    }
+                    Keycode::E => {
+                        match export(&options.out) {
+                            Ok(()) => println!("Saved {}", options.out),
+                            Err(error) => eprintln!("Unable to write {}: {}", options.out, error),
+                        }
+                        false
+                    }
+                    Keycode::Up => {
+                        drive.ratio = (drive.ratio + 0.02).min(RATIOS.1);
+                        true
+                    }
+                    Keycode::Down => {
+                        drive.ratio = (drive.ratio - 0.02).max(RATIOS.0);
+                        true
+                    }
+                    Keycode::Right => {
+                        drive.depth = (drive.depth + 1.0).min(DEPTHS.1);
+                        true
+                    }
+                    Keycode::Left => {
+                        drive.depth = (drive.depth - 1.0).max(DEPTHS.0);
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            if changed {
+                growth = drive.growth();
+            }
+        }
+
+        accumulator = (accumulator + now.elapsed().as_secs_f64()).min(MAX_BACKLOG);
+        now = Instant::now();
+        while accumulator >= DT {
+            state = physics::rk4(state, DT, |y| drive.derivatives(y, false));
+            accumulator -= DT;
+        }
+        let [theta, _, time] = state;
+
+        canvas.set_draw_color(Color::WHITE);
+        canvas.clear();
+        // the pivot's track, and the pivot and the pendulum on it
+        let pivot = (CENTER.0, CENTER.1 - drive.pivot(time).round() as i16);
+        canvas
+            .vline(
+                CENTER.0,
+                CENTER.1 - drive.depth as i16,
+                CENTER.1 + drive.depth as i16,
+                Color::RGB(200, 200, 200),
+            )
+            .expect("Unable to draw line");
+        let bob = (
+            pivot.0 + (theta.sin() * LENGTH).round() as i16,
+            pivot.1 + (theta.cos() * LENGTH).round() as i16,
+        );
+        canvas
+            .aa_line(pivot.0, pivot.1, bob.0, bob.1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(pivot.0, pivot.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+
+        let lines = [
+            format!("Ω/ω0: {:.2}, pivot ±{:.0} cm", drive.ratio, drive.depth),
+            format!("θ: {:.3} rad", events::wrap(theta)),
+            if growth > 0.0 {
+                format!("small swings grow ×e every {:.1} s", 1.0 / growth)
+            } else {
+                "small swings die out".to_string()
+            },
+        ];
+        let mut y = BORDER;
+        for line in &lines {
+            let texture = text(&texture_creator, &font, line);
+            let query = texture.query();
+            canvas
+                .copy(
+                    &texture,
+                    None,
+                    Rect::new(BORDER, y, query.width, query.height),
+                )
+                .unwrap();
+            y += query.height as i32;
+        }
+        let query = hint.query();
+        canvas
+            .copy(
+                &hint,
+                None,
+                Rect::new(
+                    BORDER,
+                    HEIGHT as i32 - BORDER - query.height as i32,
+                    query.width,
+                    query.height,
+                ),
+            )
+            .unwrap();
+        canvas.present();
+    }
+}
+
+// the growth rate over a grid of drives, as CSV: positive inside the tongues
+// of instability around Ω/ω0 = 2, 1, 2/3..., which narrow as the depth goes to 0
+fn export(path: &str) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "ratio,depth_cm,growth_per_s")?;
+    for i in 0..=((RATIOS.1 - RATIOS.0) / 0.02).round() as usize {
+        let ratio = RATIOS.0 + i as f64 * 0.02;
+        for j in 0..=((DEPTHS.1 - DEPTHS.0) / 0.5).round() as usize {
+            let depth = DEPTHS.0 + j as f64 * 0.5;
+            let growth = Drive { ratio, depth }.growth();
+            writeln!(out, "{:.2},{:.1},{:.5}", ratio, depth, growth)?;
+        }
+    }
+    out.flush()
+}
@@ -0,0 +1,71 @@
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::physics::Params;
+use crate::CENTER;
+
+/// The largest value of something so far and the angle of the bob when it was reached.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Peak {
+    pub value: f64,
+    pub theta: f64,
+}
+
+impl Peak {
+    fn hold(&mut self, value: f64, theta: f64) {
+        if value > self.value {
+            *self = Peak { value, theta };
+        }
+    }
+}
+
+/// Peak-hold of the swing's angle, speed and rod tension, kept until cleared,
+/// like the needle a peak meter leaves at the loudest level.
+///
+/// Tension is in units of the bob's weight, T/mg = cos θ + L·ω²/g, since the
+/// bob has no mass of its own here: 1 hanging still, 3 at the bottom of a
+/// swing released from horizontal, whatever L and g.
+#[derive(Default)]
+pub struct Peaks {
+    // rad, from the bottom
+    pub angle: Peak,
+    // cm/s
+    pub speed: Peak,
+    pub tension: Peak,
+}
+
+impl Peaks {
+    pub fn update(&mut self, params: &Params<f64>, theta: f64, w: f64) {
+        let theta = events::wrap(theta);
+        self.angle.hold(theta.abs(), theta);
+        self.speed.hold(params.length * w.abs(), theta);
+        self.tension
+            .hold(theta.cos() + params.length * w * w / params.gravity, theta);
+    }
+
+    /// Ticks where the rod was when each peak was reached, for a rod of
+    /// `length` px: across the bob's path for the angle and speed, and across
+    /// the middle of the rod for its tension.
+    pub fn draw(&self, canvas: &mut WindowCanvas, length: f64) {
+        for (peak, radius, color) in [
+            (self.angle, length, Color::RGB(0, 0, 255)),
+            // as the speed vector
+            (self.speed, length, Color::RED),
+            (self.tension, length / 2.0, Color::RGB(0, 150, 0)),
+        ] {
+            let (sin, cos) = peak.theta.sin_cos();
+            let point = |radius: f64| {
+                (
+                    CENTER.0 + (sin * radius).round() as i16,
+                    CENTER.1 + (cos * radius).round() as i16,
+                )
+            };
+            let (inner, outer) = (point(radius - 8.0), point(radius + 8.0));
+            canvas
+                .thick_line(inner.0, inner.1, outer.0, outer.1, 2, color)
+                .expect("Unable to draw line");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::physics::Params;
+
+/// Rotating phasor of the pendulum, z = θ - i·ω/ω0 with ω0 = √(g/L).
+///
+/// A small swing θ = A·cos(ω0·t + φ) makes z = A·e^(i(ω0·t + φ)), a point
+/// going round a circle of radius A at ω0 whose real part is θ, the usual
+/// picture for AC circuits. Larger swings bend the circle and slow the point
+/// down at the ends, as the period gets longer.
+pub struct Phasor {
+    // largest |z| so far in rad, the panel is scaled to fit it
+    extent: f64,
+}
+
+impl Phasor {
+    pub fn new() -> Self {
+        Phasor { extent: 0.0 }
+    }
+
+    pub fn z(params: &Params<f64>, theta: f64, w: f64) -> (f64, f64) {
+        let w0 = (params.gravity / params.length).sqrt();
+        (events::wrap(theta), -w / w0)
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        center: (i16, i16),
+        radius: i16,
+        params: &Params<f64>,
+        theta: f64,
+        w: f64,
+    ) {
+        let (re, im) = Phasor::z(params, theta, w);
+        let magnitude = re.hypot(im);
+        self.extent = self.extent.max(magnitude).max(1e-3);
+        let scale = (radius - 6) as f64 / self.extent;
+        let axis = Color::RGBA(0, 0, 255, 100);
+        canvas
+            .filled_circle(center.0, center.1, radius, Color::RGBA(255, 255, 255, 220))
+            .expect("Unable to draw circle");
+        canvas
+            .hline(center.0 - radius, center.0 + radius, center.1, axis)
+            .expect("Unable to draw line");
+        canvas
+            .vline(center.0, center.1 - radius, center.1 + radius, axis)
+            .expect("Unable to draw line");
+        // reference circle at the current magnitude
+        canvas
+            .aa_circle(
+                center.0,
+                center.1,
+                (magnitude * scale).round() as i16,
+                Color::RGB(150, 150, 150),
+            )
+            .expect("Unable to draw circle");
+        // imaginary axis pointing up
+        let tip = (
+            center.0 + (re * scale).round() as i16,
+            center.1 - (im * scale).round() as i16,
+        );
+        canvas
+            .aa_line(center.0, center.1, tip.0, tip.1, Color::BLACK)
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(tip.0, tip.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        // down to the real axis, which is where θ is
+        canvas
+            .vline(tip.0, tip.1, center.1, Color::RGBA(255, 0, 0, 120))
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(tip.0, center.1, 3, Color::RED)
+            .expect("Unable to draw circle");
+    }
+
+    pub fn reset(&mut self) {
+        self.extent = 0.0;
+    }
+}
@@ -0,0 +1,672 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use crate::dual::Dual;
+use crate::events;
+use crate::integrator::{self, Integrator, RungeKutta4, Scheme};
+use crate::scalar::Scalar;
+use crate::G;
+
+/// Physical constants of the pendulum, in cm, cm/s2 and 1/s.
+#[derive(Clone, Copy, Debug)]
+pub struct Params<S> {
+    pub length: S,
+    pub gravity: S,
+    // viscous drag b/m, slowing ω by b/m·ω
+    pub damping: S,
+    // quadratic air drag ½·ρ·Cd·A/m in 1/cm, slowing ω by it·L·ω·|ω|
+    pub drag: S,
+    // horizontal push of the wind on the bob per unit mass, in cm/s², to the right
+    pub wind: S,
+    // driving torque over the moment of inertia, A·cos(Ω·t), in rad/s² and rad/s
+    pub drive: S,
+    pub drive_frequency: S,
+    // and a second added to it, A2·cos(Ω2·t), quasi-periodic with the first
+    // when Ω2/Ω is irrational
+    pub drive2: S,
+    pub drive_frequency2: S,
+    // torque of the external controller over the moment of inertia, in rad/s²
+    pub torque: S,
+}
+
+/// What the parameters can be set to, from the command line, the keys and
+/// schedules alike, keeping the swing on screen and the motion watchable.
+pub const LENGTHS: RangeInclusive<f64> = 50.0..=200.0;
+pub const GRAVITIES: RangeInclusive<f64> = 100.0..=3000.0;
+pub const DAMPINGS: RangeInclusive<f64> = 0.0..=2.0;
+pub const DRIVES: RangeInclusive<f64> = 0.0..=50.0;
+pub const DRIVE_FREQUENCIES: RangeInclusive<f64> = 0.02..=20.0;
+/// The air drag's coefficient, 0.47 for a sphere, and the bob's cross-section in cm².
+pub const DRAG_COEFFICIENTS: RangeInclusive<f64> = 0.0..=2.0;
+pub const CROSS_SECTIONS: RangeInclusive<f64> = 1.0..=2000.0;
+pub const DRAGS: RangeInclusive<f64> = 0.0..=drag(*DRAG_COEFFICIENTS.end(), *CROSS_SECTIONS.end());
+
+// kg/cm³ of air at sea level, and kg of the bob
+const AIR: f64 = 1.2e-6;
+const BOB: f64 = 1.0;
+
+/// The air drag ½·ρ·Cd·A/m of `Params`, in 1/cm, for a drag coefficient
+/// and a cross-section in cm².
+pub const fn drag(coefficient: f64, cross_section: f64) -> f64 {
+    0.5 * AIR * coefficient * cross_section / BOB
+}
+
+/// Where the pendulum can be taken, from a key, for their gravity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum World {
+    Earth,
+    Moon,
+    Mars,
+    Jupiter,
+    // next to weightless, below what `GRAVITIES` lets the keys go to,
+    // floating round for minutes a swing
+    ZeroG,
+}
+
+impl World {
+    pub const ALL: [World; 5] = [
+        World::Earth,
+        World::Moon,
+        World::Mars,
+        World::Jupiter,
+        World::ZeroG,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            World::Earth => "Earth",
+            World::Moon => "Moon",
+            World::Mars => "Mars",
+            World::Jupiter => "Jupiter",
+            World::ZeroG => "zero-g",
+        }
+    }
+
+    // cm/s², at the surface
+    pub fn gravity(self) -> f64 {
+        match self {
+            World::Earth => G,
+            World::Moon => 162.0,
+            World::Mars => 371.0,
+            World::Jupiter => 2479.0,
+            World::ZeroG => 1.0,
+        }
+    }
+
+    /// The world of this gravity, if it's one of them.
+    pub fn of(gravity: f64) -> Option<Self> {
+        World::ALL.into_iter().find(|it| it.gravity() == gravity)
+    }
+
+    /// The one after the world of `gravity` in `ALL`, wrapping around, or
+    /// Earth for a gravity of none of them.
+    pub fn after(gravity: f64) -> Self {
+        match World::ALL.iter().position(|it| it.gravity() == gravity) {
+            Some(i) => World::ALL[(i + 1) % World::ALL.len()],
+            None => World::Earth,
+        }
+    }
+}
+
+impl Params<f64> {
+    pub fn cast<S: Scalar>(&self) -> Params<S> {
+        Params {
+            length: S::from_f64(self.length),
+            gravity: S::from_f64(self.gravity),
+            damping: S::from_f64(self.damping),
+            drag: S::from_f64(self.drag),
+            wind: S::from_f64(self.wind),
+            drive: S::from_f64(self.drive),
+            drive_frequency: S::from_f64(self.drive_frequency),
+            drive2: S::from_f64(self.drive2),
+            drive_frequency2: S::from_f64(self.drive_frequency2),
+            torque: S::from_f64(self.torque),
+        }
+    }
+}
+
+/// State of a simple pendulum: angle from the vertical and angular velocity.
+#[derive(Clone, Copy, Debug)]
+pub struct Pendulum<S> {
+    pub theta: S,
+    pub w: S,
+}
+
+impl<S: Scalar> Pendulum<S> {
+    pub fn new(theta: S) -> Self {
+        Pendulum {
+            theta,
+            w: S::from_f64(0.0),
+        }
+    }
+
+    /// Advances the pendulum by `dt` s with fourth order Runge-Kutta, for
+    /// parameters without a drive.
+    pub fn step(&mut self, params: &Params<S>, dt: S) {
+        RungeKutta4.step(self, params, S::from_f64(0.0), dt);
+    }
+}
+
+/// Angle the pendulum in this state swings up to, in rad. Close to π for a
+/// pendulum that goes over the top.
+pub fn amplitude(params: &Params<f64>, theta: f64, w: f64) -> f64 {
+    // energy conservation: cos θmax = cos θ - ω²·L / 2g
+    let cos = theta.cos() - w * w * params.length / (2.0 * params.gravity);
+    cos.clamp(-1.0, 1.0).acos()
+}
+
+/// Exact period of a pendulum swinging with `amplitude` rad, in s:
+/// T = 2π·√(L/g) / AGM(1, cos(θmax/2)), which is 2π·√(L/g) for small swings.
+pub fn period(params: &Params<f64>, amplitude: f64) -> f64 {
+    // the period diverges as the amplitude approaches π
+    let (mut a, mut b) = (1.0, (amplitude.abs().min(PI - 1e-6) / 2.0).cos());
+    for _ in 0..64 {
+        if a - b <= 1e-15 * a {
+            break;
+        }
+        (a, b) = ((a + b) / 2.0, (a * b).sqrt());
+    }
+    2.0 * PI * (params.length / params.gravity).sqrt() / a
+}
+
+/// The three numbers the damped, driven pendulum's motion depends on once
+/// time is counted in swings, 1/ω0 with ω0 = √(g/L): the quality factor
+/// Q = ω0/b, infinite undamped, the drive ratio Ω/ω0 and the drive's
+/// strength against gravity's, A/ω0². Any two pendulums with the same
+/// three swing alike, however long and wherever they are.
+pub fn groups(params: &Params<f64>) -> (f64, f64, f64) {
+    let natural = (params.gravity / params.length).sqrt();
+    (
+        natural / params.damping,
+        params.drive_frequency / natural,
+        params.drive / (natural * natural),
+    )
+}
+
+/// Angular momentum about the pivot per unit mass, in cm²/s. The bob is a
+/// point mass, so its moment of inertia is m·l².
+pub fn angular_momentum(params: &Params<f64>, w: f64) -> f64 {
+    params.length * params.length * w
+}
+
+/// Kinetic and potential energy per unit mass, in cm²/s², the potential
+/// from the bottom of the swing: l²·ω²/2 and g·l·(1 - cos θ).
+pub fn energy(params: &Params<f64>, theta: f64, w: f64) -> (f64, f64) {
+    let kinetic = (params.length * w).powi(2) / 2.0;
+    (
+        kinetic,
+        params.gravity * params.length * (1.0 - theta.cos()),
+    )
+}
+
+/// Net torque about the pivot per unit mass, in cm²/s²: gravity's, the
+/// damping's, the air drag's, the wind's and the drive's, the rod pulling
+/// straight through the pivot. It is the rate of change of `angular_momentum`.
+pub fn torque(params: &Params<f64>, time: f64, theta: f64, w: f64) -> f64 {
+    params.length * params.length * integrator::acceleration(params, time, theta, w)
+}
+
+/// What a change of length or gravity keeps constant, instead of the pendulum
+/// jumping to whatever motion the old θ and ω mean with the new parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conserve {
+    // θ and ω as they are
+    State,
+    Energy,
+    // about the pivot, as when reeling in a string
+    AngularMomentum,
+}
+
+impl Conserve {
+    pub fn name(self) -> &'static str {
+        match self {
+            Conserve::State => "state",
+            Conserve::Energy => "energy",
+            Conserve::AngularMomentum => "momentum",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [Conserve::State, Conserve::Energy, Conserve::AngularMomentum]
+            .into_iter()
+            .find(|it| it.name() == name)
+    }
+}
+
+/// θ and ω after the parameters change from `from` to `to`, keeping `conserve`.
+pub fn retune(
+    from: &Params<f64>,
+    to: &Params<f64>,
+    theta: f64,
+    w: f64,
+    conserve: Conserve,
+) -> (f64, f64) {
+    match conserve {
+        Conserve::State => (theta, w),
+        // L = m·l²·ω
+        Conserve::AngularMomentum => (theta, w * (from.length / to.length).powi(2)),
+        Conserve::Energy => {
+            // per unit mass, from the bottom: E = l²·ω²/2 + g·l·(1 - cos θ)
+            let energy =
+                (from.length * w).powi(2) / 2.0 + from.gravity * from.length * (1.0 - theta.cos());
+            let kinetic = energy - to.gravity * to.length * (1.0 - theta.cos());
+            if kinetic >= 0.0 {
+                (theta, (2.0 * kinetic).sqrt() / to.length * w.signum())
+            } else {
+                // not enough energy to be this high any more, it swings lower
+                let cos = 1.0 - energy / (to.gravity * to.length);
+                let wrapped = events::wrap(theta);
+                (theta - wrapped + cos.acos() * wrapped.signum(), 0.0)
+            }
+        }
+    }
+}
+
+/// Tracks ∂θ/∂L and ∂θ/∂g by stepping two dual-number copies of the pendulum,
+/// each seeded with respect to one parameter, in lockstep with the main one.
+#[derive(Clone)]
+pub struct Sensitivity {
+    wrt_length: Pendulum<Dual>,
+    wrt_gravity: Pendulum<Dual>,
+}
+
+impl Sensitivity {
+    pub fn new(theta: f64, w: f64) -> Self {
+        let pendulum = Pendulum {
+            theta: Dual::constant(theta),
+            w: Dual::constant(w),
+        };
+        Sensitivity {
+            wrt_length: pendulum,
+            wrt_gravity: pendulum,
+        }
+    }
+
+    pub fn step(&mut self, params: &Params<f64>, time: f64, dt: f64, scheme: Scheme) {
+        let (time, dt) = (Dual::constant(time), Dual::constant(dt));
+        scheme.step(
+            &mut self.wrt_length,
+            &Params {
+                length: Dual::variable(params.length),
+                gravity: Dual::constant(params.gravity),
+                damping: Dual::constant(params.damping),
+                drag: Dual::constant(params.drag),
+                wind: Dual::constant(params.wind),
+                drive: Dual::constant(params.drive),
+                drive_frequency: Dual::constant(params.drive_frequency),
+                drive2: Dual::constant(params.drive2),
+                drive_frequency2: Dual::constant(params.drive_frequency2),
+                torque: Dual::constant(params.torque),
+            },
+            time,
+            dt,
+        );
+        scheme.step(
+            &mut self.wrt_gravity,
+            &Params {
+                length: Dual::constant(params.length),
+                gravity: Dual::variable(params.gravity),
+                damping: Dual::constant(params.damping),
+                drag: Dual::constant(params.drag),
+                wind: Dual::constant(params.wind),
+                drive: Dual::constant(params.drive),
+                drive_frequency: Dual::constant(params.drive_frequency),
+                drive2: Dual::constant(params.drive2),
+                drive_frequency2: Dual::constant(params.drive_frequency2),
+                torque: Dual::constant(params.torque),
+            },
+            time,
+            dt,
+        );
+    }
+
+    // a kick doesn't depend on the parameters, both copies just take it
+    pub fn kick(&mut self, dw: f64) {
+        self.wrt_length.w += Dual::constant(dw);
+        self.wrt_gravity.w += Dual::constant(dw);
+    }
+
+    // rad/cm
+    pub fn dtheta_dlength(&self) -> f64 {
+        self.wrt_length.theta.du
+    }
+
+    // rad/(cm/s2)
+    pub fn dtheta_dgravity(&self) -> f64 {
+        self.wrt_gravity.theta.du
+    }
+}
+
+/// Steps the same pendulum in single and double precision from the same
+/// initial conditions, to show how quickly the two trajectories drift apart.
+#[derive(Clone)]
+pub struct Precision {
+    single: Pendulum<f32>,
+    double: Pendulum<f64>,
+}
+
+impl Precision {
+    pub fn new(theta: f64, w: f64) -> Self {
+        Precision {
+            single: Pendulum {
+                theta: theta as f32,
+                w: w as f32,
+            },
+            double: Pendulum { theta, w },
+        }
+    }
+
+    pub fn step(&mut self, params: &Params<f64>, time: f64, dt: f64, scheme: Scheme) {
+        scheme.step(&mut self.single, &params.cast(), time as f32, dt as f32);
+        scheme.step(&mut self.double, params, time, dt);
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.single.w += dw as f32;
+        self.double.w += dw;
+    }
+
+    // |θ64 - θ32| in rad
+    pub fn divergence(&self) -> f64 {
+        (self.double.theta - self.single.theta as f64).abs()
+    }
+}
+
+/// Lengths in cm, masses in arbitrary units, gravity in cm/s2.
+#[derive(Clone, Copy, Debug)]
+pub struct DoubleParams {
+    pub l1: f64,
+    pub l2: f64,
+    pub m1: f64,
+    pub m2: f64,
+    pub gravity: f64,
+}
+
+/// Two pendulums, the second hanging from the bob of the first, with both
+/// angles measured from the vertical.
+#[derive(Clone, Copy, Debug)]
+pub struct DoublePendulum {
+    pub theta1: f64,
+    pub theta2: f64,
+    pub w1: f64,
+    pub w2: f64,
+}
+
+impl DoublePendulum {
+    pub fn new(theta1: f64, theta2: f64) -> Self {
+        DoublePendulum {
+            theta1,
+            theta2,
+            w1: 0.0,
+            w2: 0.0,
+        }
+    }
+
+    // fourth order Runge-Kutta, the motion is chaotic and Euler drifts off quickly
+    pub fn step(&mut self, params: &DoubleParams, dt: f64) {
+        let state = [self.theta1, self.theta2, self.w1, self.w2];
+        [self.theta1, self.theta2, self.w1, self.w2] =
+            rk4(state, dt, |it| double_derivatives(params, it));
+    }
+}
+
+// d/dt of [θ1, θ2, ω1, ω2], from the Lagrangian of the double pendulum
+fn double_derivatives(params: &DoubleParams, state: &[f64; 4]) -> [f64; 4] {
+    let DoubleParams {
+        l1,
+        l2,
+        m1,
+        m2,
+        gravity: g,
+    } = *params;
+    let [theta1, theta2, w1, w2] = *state;
+    let delta = theta1 - theta2;
+    let den = 2.0 * m1 + m2 - m2 * (2.0 * delta).cos();
+    let a1 = (-g * (2.0 * m1 + m2) * theta1.sin()
+        - m2 * g * (theta1 - 2.0 * theta2).sin()
+        - 2.0 * delta.sin() * m2 * (w2 * w2 * l2 + w1 * w1 * l1 * delta.cos()))
+        / (l1 * den);
+    let a2 = 2.0
+        * delta.sin()
+        * (w1 * w1 * l1 * (m1 + m2)
+            + g * (m1 + m2) * theta1.cos()
+            + w2 * w2 * l2 * m2 * delta.cos())
+        / (l2 * den);
+    [w1, w2, a1, a2]
+}
+
+/// Lengths in cm and masses in arbitrary units of each rod of a chain, from
+/// the pivot down, and gravity in cm/s2.
+#[derive(Clone, Debug)]
+pub struct ChainParams {
+    pub lengths: Vec<f64>,
+    pub masses: Vec<f64>,
+    pub gravity: f64,
+}
+
+impl ChainParams {
+    // mass hanging from rod i, its own bob included
+    fn below(&self, i: usize) -> f64 {
+        self.masses[i..].iter().sum()
+    }
+}
+
+/// Pendulum of any number of massless rods, each with a point mass at its
+/// end hanging from the one above, all angles measured from the vertical.
+/// Two rods make the double pendulum.
+#[derive(Clone, Debug)]
+pub struct Chain {
+    pub theta: Vec<f64>,
+    pub w: Vec<f64>,
+}
+
+impl Chain {
+    pub fn new(params: &ChainParams, theta: f64) -> Self {
+        Chain {
+            theta: vec![theta; params.lengths.len()],
+            w: vec![0.0; params.lengths.len()],
+        }
+    }
+
+    // fourth order Runge-Kutta, as for the double pendulum
+    pub fn step(&mut self, params: &ChainParams, dt: f64) {
+        let offset = |from: &Chain, k: &(Vec<f64>, Vec<f64>), h: f64| Chain {
+            theta: from
+                .theta
+                .iter()
+                .zip(&k.0)
+                .map(|(y, k)| y + k * h)
+                .collect(),
+            w: from.w.iter().zip(&k.1).map(|(y, k)| y + k * h).collect(),
+        };
+        let k1 = self.derivatives(params);
+        let k2 = offset(self, &k1, dt / 2.0).derivatives(params);
+        let k3 = offset(self, &k2, dt / 2.0).derivatives(params);
+        let k4 = offset(self, &k3, dt).derivatives(params);
+        for i in 0..self.theta.len() {
+            self.theta[i] += dt / 6.0 * (k1.0[i] + 2.0 * k2.0[i] + 2.0 * k3.0[i] + k4.0[i]);
+            self.w[i] += dt / 6.0 * (k1.1[i] + 2.0 * k2.1[i] + 2.0 * k3.1[i] + k4.1[i]);
+        }
+    }
+
+    /// dθ/dt and dω/dt from Lagrange's equations, M(θ)·dω/dt = f(θ, ω), with
+    /// M_ij = μ_ij·l_i·l_j·cos(θi - θj) and f_i = -Σj μ_ij·l_i·l_j·sin(θi - θj)·ω_j²
+    /// - μ_ii·g·l_i·sin θi, μ_ij being the mass below both rods i and j.
+    fn derivatives(&self, params: &ChainParams) -> (Vec<f64>, Vec<f64>) {
+        let n = self.theta.len();
+        let l = &params.lengths;
+        // the augmented matrix [M | f], solved in place
+        let mut m = vec![vec![0.0; n + 1]; n];
+        for i in 0..n {
+            for j in 0..n {
+                let coupling = params.below(i.max(j)) * l[i] * l[j];
+                let delta = self.theta[i] - self.theta[j];
+                m[i][j] = coupling * delta.cos();
+                m[i][n] -= coupling * delta.sin() * self.w[j] * self.w[j];
+            }
+            m[i][n] -= params.below(i) * params.gravity * l[i] * self.theta[i].sin();
+        }
+        // Gaussian elimination, M being symmetric positive definite needs no pivoting
+        for k in 0..n {
+            let (above, below) = m.split_at_mut(k + 1);
+            let pivot = &above[k];
+            for row in below {
+                let factor = row[k] / pivot[k];
+                for (it, p) in row[k..].iter_mut().zip(&pivot[k..]) {
+                    *it -= factor * p;
+                }
+            }
+        }
+        let mut a = vec![0.0; n];
+        for i in (0..n).rev() {
+            let known: f64 = (i + 1..n).map(|j| m[i][j] * a[j]).sum();
+            a[i] = (m[i][n] - known) / m[i][i];
+        }
+        (self.w.clone(), a)
+    }
+
+    // kinetic plus potential, the latter from the pivot's height, in mass units times cm²/s²
+    pub fn energy(&self, params: &ChainParams) -> f64 {
+        let mut energy = 0.0;
+        // velocity and height of the bob at the end of the rods so far
+        let (mut vx, mut vy, mut y) = (0.0, 0.0, 0.0);
+        for i in 0..self.theta.len() {
+            let (l, theta, w) = (params.lengths[i], self.theta[i], self.w[i]);
+            vx += l * w * theta.cos();
+            vy -= l * w * theta.sin();
+            y += l * theta.cos();
+            energy += params.masses[i] * ((vx * vx + vy * vy) / 2.0 - params.gravity * y);
+        }
+        energy
+    }
+}
+
+/// Spring constant over the bob's mass in 1/s², natural length of the
+/// spring in cm and gravity in cm/s2.
+#[derive(Clone, Copy, Debug)]
+pub struct SpringParams {
+    pub stiffness: f64,
+    pub rest_length: f64,
+    pub gravity: f64,
+}
+
+impl SpringParams {
+    // cm, where the bob hangs at rest
+    pub fn hanging_length(&self) -> f64 {
+        self.rest_length + self.gravity / self.stiffness
+    }
+}
+
+/// Pendulum on a spring instead of a rod, the bob bouncing in and out while
+/// it swings: its distance from the pivot, its angle from the vertical and
+/// their rates of change.
+#[derive(Clone, Copy, Debug)]
+pub struct SpringPendulum {
+    pub r: f64,
+    pub theta: f64,
+    pub dr: f64,
+    pub w: f64,
+}
+
+impl SpringPendulum {
+    pub fn step(&mut self, params: &SpringParams, dt: f64) {
+        let state = [self.r, self.theta, self.dr, self.w];
+        [self.r, self.theta, self.dr, self.w] = rk4(state, dt, |it| spring_derivatives(params, it));
+    }
+
+    // kinetic plus gravity's and the spring's, per unit mass, in cm²/s²
+    pub fn energy(&self, params: &SpringParams) -> f64 {
+        let stretch = self.r - params.rest_length;
+        (self.dr * self.dr + (self.r * self.w).powi(2)) / 2.0
+            - params.gravity * self.r * self.theta.cos()
+            + params.stiffness * stretch * stretch / 2.0
+    }
+}
+
+// d/dt of [r, θ, dr/dt, ω], from the Lagrangian in polar coordinates: the
+// spring and gravity pull along r, with the centrifugal term, and the
+// Coriolis term speeds up the swing as the bob moves in
+fn spring_derivatives(params: &SpringParams, state: &[f64; 4]) -> [f64; 4] {
+    let [r, theta, dr, w] = *state;
+    let radial =
+        r * w * w + params.gravity * theta.cos() - params.stiffness * (r - params.rest_length);
+    let angular = -(params.gravity * theta.sin() + 2.0 * dr * w) / r;
+    [dr, w, radial, angular]
+}
+
+/// Pendulum free to swing in any direction, as the bob position relative to
+/// the pivot and its velocity, in cm and cm/s with z pointing down.
+///
+/// Kept in cartesian coordinates since the spherical angles are singular
+/// right below the pivot, where a sand pendulum spends a lot of its time.
+#[derive(Clone, Copy, Debug)]
+pub struct SphericalPendulum {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+impl SphericalPendulum {
+    /// Starts `theta` rad from the vertical along x, moving sideways along y
+    /// at `speed` cm/s.
+    pub fn new(params: &Params<f64>, theta: f64, speed: f64) -> Self {
+        SphericalPendulum {
+            position: [
+                params.length * theta.sin(),
+                0.0,
+                params.length * theta.cos(),
+            ],
+            velocity: [0.0, speed, 0.0],
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        dot(&self.velocity, &self.velocity).sqrt()
+    }
+
+    pub fn step(&mut self, params: &Params<f64>, dt: f64) {
+        let [x, y, z] = self.position;
+        let [vx, vy, vz] = self.velocity;
+        let [x, y, z, vx, vy, vz] = rk4([x, y, z, vx, vy, vz], dt, |state| {
+            let [x, y, z, vx, vy, vz] = *state;
+            // gravity minus whatever the rod pulls back along itself to keep
+            // the bob on the sphere: a = g - r·(|v|² + r·g) / L²
+            let pull = (vx * vx + vy * vy + vz * vz + params.gravity * z)
+                / (params.length * params.length);
+            [vx, vy, vz, -x * pull, -y * pull, params.gravity - z * pull]
+        });
+        // RK4 drifts off the sphere slowly, put the bob back on it
+        let scale = params.length / dot(&[x, y, z], &[x, y, z]).sqrt();
+        let position = [x * scale, y * scale, z * scale];
+        let velocity = [vx, vy, vz];
+        let radial = dot(&velocity, &position) / (params.length * params.length);
+        self.position = position;
+        self.velocity = std::array::from_fn(|i| velocity[i] - radial * position[i]);
+    }
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// One classic Runge-Kutta step of `dy/dt = f(y)`.
+pub fn rk4<const N: usize, F>(y: [f64; N], dt: f64, f: F) -> [f64; N]
+where
+    F: Fn(&[f64; N]) -> [f64; N],
+{
+    let offset = |k: &[f64; N], h: f64| {
+        let mut out = y;
+        for (o, k) in out.iter_mut().zip(k) {
+            *o += k * h;
+        }
+        out
+    };
+    let k1 = f(&y);
+    let k2 = f(&offset(&k1, dt / 2.0));
+    let k3 = f(&offset(&k2, dt / 2.0));
+    let k4 = f(&offset(&k3, dt));
+    let mut out = y;
+    for i in 0..N {
+        out[i] += dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+    }
+    out
+}
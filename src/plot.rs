@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+
+// values at or below this are drawn on the bottom of a log plot
+const LOG_FLOOR: f64 = 1e-16;
+
+/// Scrolling time series of the last `capacity` samples.
+#[derive(Clone)]
+pub struct Plot {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl Plot {
+    pub fn new(capacity: usize) -> Self {
+        Plot {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.samples.back().copied()
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value);
+    }
+
+    /// Draws the samples as a line on a log10 axis fitted to `rect`.
+    pub fn draw_log(&self, canvas: &mut WindowCanvas, rect: Rect, color: Color) {
+        let logs: Vec<f64> = self
+            .samples
+            .iter()
+            .map(|it| it.max(LOG_FLOOR).log10())
+            .collect();
+        let min = logs.iter().copied().fold(f64::INFINITY, f64::min).floor();
+        // at least one decade so a flat line doesn't fill the whole height
+        let max = logs
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .max(min + 1.0);
+        self.draw(canvas, rect, color, &logs, min, max);
+    }
+
+    /// Draws the samples as a line on a linear axis centred on zero, fitted
+    /// to `rect`, times `sign`, so -1 turns it upside down.
+    pub fn draw_linear(&self, canvas: &mut WindowCanvas, rect: Rect, color: Color, sign: f64) {
+        let values: Vec<f64> = self.samples.iter().copied().collect();
+        let extent = values
+            .iter()
+            .fold(0.0, |extent: f64, it| extent.max(it.abs()))
+            .max(f64::MIN_POSITIVE);
+        self.draw(canvas, rect, color, &values, -sign * extent, sign * extent);
+    }
+
+    fn draw(
+        &self,
+        canvas: &mut WindowCanvas,
+        rect: Rect,
+        color: Color,
+        values: &[f64],
+        min: f64,
+        max: f64,
+    ) {
+        canvas
+            .rectangle(
+                rect.left() as i16,
+                rect.top() as i16,
+                rect.right() as i16 - 1,
+                rect.bottom() as i16 - 1,
+                Color::RGB(200, 200, 200),
+            )
+            .expect("Unable to draw rectangle");
+        let point = |i: usize, value: f64| {
+            let x = rect.left() as f64 + i as f64 * rect.width() as f64 / self.capacity as f64;
+            let y = (rect.bottom() - 1) as f64
+                - (value - min) / (max - min) * (rect.height() - 1) as f64;
+            (x.round() as i16, y.round() as i16)
+        };
+        for (i, pair) in values.windows(2).enumerate() {
+            let (x0, y0) = point(i, pair[0]);
+            let (x1, y1) = point(i + 1, pair[1]);
+            canvas
+                .aa_line(x0, y0, x1, y1, color)
+                .expect("Unable to draw line");
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::{CENTER, L};
+
+// frames of trail behind the shadow
+const TRAIL: usize = 90;
+// the line the shadow moves along, just below the swing
+const LINE_Y: i16 = CENTER.1 + L as i16 + 12;
+
+/// Shadow of the bob on a horizontal line below it, as if lit from straight
+/// above. For small swings it moves in simple harmonic motion, like the shadow
+/// of a point going round a circle in the textbook analogy, lingering at the
+/// ends and rushing through the middle, which its fading trail shows.
+pub struct Projection {
+    trail: VecDeque<i16>,
+}
+
+impl Projection {
+    pub fn new() -> Self {
+        Projection {
+            trail: VecDeque::with_capacity(TRAIL),
+        }
+    }
+
+    pub fn push(&mut self, x: i16) {
+        if self.trail.len() == TRAIL {
+            self.trail.pop_front();
+        }
+        self.trail.push_back(x);
+    }
+
+    pub fn clear(&mut self) {
+        self.trail.clear();
+    }
+
+    /// Draws the line, the trail and the shadow of the bob at `bob`.
+    pub fn draw(&self, canvas: &mut WindowCanvas, bob: (i16, i16)) {
+        let color = Color::RGBA(0, 0, 255, 100);
+        canvas
+            .hline(CENTER.0 - L as i16, CENTER.0 + L as i16, LINE_Y, color)
+            .expect("Unable to draw line");
+        // dashed ray of light from the bob down to its shadow
+        let mut y = bob.1 + 8;
+        while y < LINE_Y - 4 {
+            canvas
+                .vline(bob.0, y, (y + 3).min(LINE_Y - 4), color)
+                .expect("Unable to draw line");
+            y += 8;
+        }
+        for (age, x) in self.trail.iter().rev().enumerate() {
+            let alpha = 120 - 120 * age / TRAIL;
+            canvas
+                .filled_circle(*x, LINE_Y, 3, Color::RGBA(0, 0, 0, alpha as u8))
+                .expect("Unable to draw circle");
+        }
+        canvas
+            .filled_circle(bob.0, LINE_Y, 5, Color::RGBA(0, 0, 0, 200))
+            .expect("Unable to draw circle");
+    }
+}
@@ -0,0 +1,118 @@
+use std::time::Instant;
+
+// s a frame may spend updating and drawing, most of a 60 Hz frame, and the
+// fraction of it frames have to stay under for more detail to come back
+const BUDGET: f64 = 0.012;
+const HEADROOM: f64 = 0.5;
+// s the frame time has to stay out of them before the tier changes, and the
+// time constant it is eased with
+const HOLD: f64 = 2.0;
+const EASING: f64 = 0.5;
+
+/// How much of the scenery is drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct Tier {
+    pub name: &'static str,
+    // fraction of the trails drawn, the newest part
+    pub trail: f64,
+    // Hz the plot panels are redrawn at
+    pub plot_hz: f64,
+    // squares of the magnetic mode's basin map worked out a frame, each up
+    // to thousands of steps of a trial swing
+    pub basins: usize,
+    // squares of the chain mode's flip-time map, each up to a thousand
+    // steps of a double pendulum
+    pub flips: usize,
+}
+
+pub const TIERS: [Tier; 3] = [
+    Tier {
+        name: "full",
+        trail: 1.0,
+        plot_hz: 10.0,
+        basins: 32,
+        flips: 32,
+    },
+    Tier {
+        name: "reduced",
+        trail: 0.5,
+        plot_hz: 5.0,
+        basins: 8,
+        flips: 8,
+    },
+    Tier {
+        name: "low",
+        trail: 0.25,
+        plot_hz: 2.0,
+        basins: 2,
+        flips: 2,
+    },
+];
+
+/// Steps down the tiers while frames take longer than the budget, and back
+/// up once they have room to spare again, so the pendulum keeps moving
+/// smoothly on slow machines.
+pub struct Quality {
+    tier: usize,
+    // the most detailed tier it climbs back to
+    top: usize,
+    // s, of the frames so far, eased
+    work: f64,
+    started: Instant,
+    ended: Instant,
+    // since when the frames have been asking for another tier
+    asking: Option<Instant>,
+}
+
+impl Quality {
+    pub fn new(top: usize) -> Self {
+        Quality {
+            tier: top,
+            top,
+            work: 0.0,
+            started: Instant::now(),
+            ended: Instant::now(),
+            asking: None,
+        }
+    }
+
+    pub fn tier(&self) -> Tier {
+        TIERS[self.tier]
+    }
+
+    // below the full tier
+    pub fn reduced(&self) -> bool {
+        self.tier > 0
+    }
+
+    pub fn start_frame(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Takes the time since `start_frame` into account, returning whether
+    /// that changed the tier.
+    pub fn end_frame(&mut self) -> bool {
+        let work = self.started.elapsed().as_secs_f64();
+        let dt = self.ended.elapsed().as_secs_f64();
+        self.ended = Instant::now();
+        self.work += (work - self.work) * (1.0 - (-dt / EASING).exp());
+        let wanted = if self.work > BUDGET {
+            (self.tier + 1).min(TIERS.len() - 1)
+        } else if self.work < HEADROOM * BUDGET {
+            self.tier.saturating_sub(1).max(self.top)
+        } else {
+            self.tier
+        };
+        if wanted == self.tier {
+            self.asking = None;
+            return false;
+        }
+        let asking = *self.asking.get_or_insert_with(Instant::now);
+        if asking.elapsed().as_secs_f64() < HOLD {
+            return false;
+        }
+        self.tier = wanted;
+        self.asking = None;
+        true
+    }
+}
@@ -0,0 +1,437 @@
+use crate::convention::Convention;
+use crate::rng::Rng;
+
+// how long the quiz lets the pendulum run between questions, in seconds
+const MIN_GAP: f64 = 5.0;
+const MAX_GAP: f64 = 15.0;
+// how long the result of an answer stays on screen
+const FEEDBACK: f64 = 2.0;
+// at most this many choices, answered with keys 1 to 4
+pub const MAX_CHOICES: usize = 4;
+
+/// Quantity of the live state a question can ask about.
+#[derive(Clone, Copy, Debug)]
+enum Quantity {
+    Angle,
+    AngularVelocity,
+    Speed,
+    Height,
+}
+
+impl Quantity {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "angle" => Some(Quantity::Angle),
+            "angular_velocity" => Some(Quantity::AngularVelocity),
+            "speed" => Some(Quantity::Speed),
+            "height" => Some(Quantity::Height),
+            _ => None,
+        }
+    }
+
+    fn evaluate(self, state: &Observation) -> f64 {
+        let angles = state.angles;
+        match self {
+            Quantity::Angle => angles.angle(state.theta) * angles.scale(),
+            Quantity::AngularVelocity => angles.rate(state.w) * angles.scale(),
+            // cm -> m
+            Quantity::Speed => (state.w * state.length).abs() / 100.0,
+            Quantity::Height => state.length * (1.0 - state.theta.cos()) / 100.0,
+        }
+    }
+
+    // the unit of an angle or a rate, the convention's in place of the question's
+    fn unit(self, angles: Convention) -> Option<String> {
+        match self {
+            Quantity::Angle => Some(angles.unit().to_string()),
+            Quantity::AngularVelocity => Some(format!("{}/s", angles.unit())),
+            Quantity::Speed | Quantity::Height => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Question {
+    // the right answer is computed from the state at the moment it is asked
+    Quantity {
+        prompt: String,
+        quantity: Quantity,
+        unit: String,
+    },
+    // fixed answers written by the author
+    Choice {
+        prompt: String,
+        answer: String,
+        wrong: Vec<String>,
+    },
+}
+
+/// What the quiz needs to know about the pendulum, in rad, rad/s and cm,
+/// and the convention to give its angle and angular velocity in.
+pub struct Observation {
+    pub theta: f64,
+    pub w: f64,
+    pub length: f64,
+    pub angles: Convention,
+}
+
+// a `[kind]` header and the `key = value` lines below it, with line numbers
+struct Section {
+    number: usize,
+    kind: String,
+    fields: Vec<(usize, String, String)>,
+}
+
+struct Asked {
+    prompt: String,
+    choices: Vec<String>,
+    correct: usize,
+}
+
+/// Pauses the simulation at random moments to ask multiple choice questions.
+pub struct Quiz {
+    questions: Vec<Question>,
+    rng: Rng,
+    // seconds of running left before the next question
+    countdown: f64,
+    asked: Option<Asked>,
+    // result of the last answer and how long it stays on screen
+    feedback: Option<(String, f64)>,
+    pub correct: u32,
+    pub answered: u32,
+}
+
+impl Quiz {
+    /// Parses a quiz file: `[quantity]` and `[choice]` sections of `key = value`
+    /// lines, `#` starts a comment.
+    pub fn parse(source: &str, name: &str) -> Result<Self, String> {
+        let mut questions = Vec::new();
+        let mut section: Option<Section> = None;
+        for (i, raw) in source.lines().enumerate() {
+            let number = i + 1;
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(kind) = line.strip_prefix('[').and_then(|it| it.strip_suffix(']')) {
+                if let Some(done) = section.take() {
+                    questions.push(question(done, name)?);
+                }
+                section = Some(Section {
+                    number,
+                    kind: kind.trim().to_string(),
+                    fields: Vec::new(),
+                });
+            } else if let Some((key, value)) = line.split_once('=') {
+                match section.as_mut() {
+                    Some(section) => section.fields.push((
+                        number,
+                        key.trim().to_string(),
+                        value.trim().to_string(),
+                    )),
+                    None => {
+                        return Err(format!(
+                            "{}:{}: `{}` is outside a [quantity] or [choice] section",
+                            name,
+                            number,
+                            key.trim()
+                        ))
+                    }
+                }
+            } else {
+                return Err(format!("{}:{}: expected `key = value`", name, number));
+            }
+        }
+        if let Some(done) = section.take() {
+            questions.push(question(done, name)?);
+        }
+        if questions.is_empty() {
+            return Err(format!("{}: no questions", name));
+        }
+        let mut rng = Rng::from_time();
+        Ok(Quiz {
+            questions,
+            countdown: rng.range(MIN_GAP, MAX_GAP),
+            rng,
+            asked: None,
+            feedback: None,
+            correct: 0,
+            answered: 0,
+        })
+    }
+
+    // whether a question or its result is on screen, freezing the simulation
+    pub fn is_paused(&self) -> bool {
+        self.asked.is_some() || self.feedback.is_some()
+    }
+
+    /// Advances the quiz clock by `dt` seconds of wall time and asks the next
+    /// question once it is due.
+    pub fn tick(&mut self, dt: f64, state: &Observation) {
+        if let Some((_, left)) = self.feedback.as_mut() {
+            *left -= dt;
+            if *left <= 0.0 {
+                self.feedback = None;
+            }
+            return;
+        }
+        if self.asked.is_some() {
+            return;
+        }
+        self.countdown -= dt;
+        if self.countdown <= 0.0 {
+            self.countdown = self.rng.range(MIN_GAP, MAX_GAP);
+            let question = &self.questions[self.rng.below(self.questions.len())];
+            self.asked = Some(ask(question, state, &mut self.rng));
+        }
+    }
+
+    // `choice` counts from 0; ignored when no question is being asked
+    pub fn answer(&mut self, choice: usize) {
+        let Some(asked) = self.asked.as_ref() else {
+            return;
+        };
+        if choice >= asked.choices.len() {
+            return;
+        }
+        self.answered += 1;
+        let result = if choice == asked.correct {
+            self.correct += 1;
+            "Correct!".to_string()
+        } else {
+            format!("Wrong, the answer was: {}", asked.choices[asked.correct])
+        };
+        self.asked = None;
+        self.feedback = Some((result, FEEDBACK));
+    }
+
+    /// Lines of the overlay currently on screen, if any.
+    pub fn overlay(&self) -> Option<Vec<String>> {
+        if let Some(asked) = &self.asked {
+            let mut lines = vec![asked.prompt.clone()];
+            for (i, choice) in asked.choices.iter().enumerate() {
+                lines.push(format!("{}: {}", i + 1, choice));
+            }
+            return Some(lines);
+        }
+        self.feedback
+            .as_ref()
+            .map(|(result, _)| vec![result.clone()])
+    }
+}
+
+fn question(section: Section, name: &str) -> Result<Question, String> {
+    let Section {
+        number,
+        kind,
+        fields,
+    } = section;
+    if kind != "quantity" && kind != "choice" {
+        return Err(format!(
+            "{}:{}: unknown section [{}], expected [quantity] or [choice]",
+            name, number, kind
+        ));
+    }
+    let mut prompt = None;
+    let mut quantity = None;
+    let mut unit = String::new();
+    let mut answer = None;
+    let mut wrong = Vec::new();
+    for (line, key, value) in fields {
+        match (kind.as_str(), key.as_str()) {
+            (_, "prompt") => prompt = Some(value),
+            ("quantity", "value") => match Quantity::parse(&value) {
+                Some(it) => quantity = Some(it),
+                None => {
+                    return Err(format!(
+                        "{}:{}: unknown value `{}`, expected {}",
+                        name, line, value, "angle, angular_velocity, speed or height"
+                    ))
+                }
+            },
+            ("quantity", "unit") => unit = value,
+            ("choice", "answer") => answer = Some(value),
+            ("choice", "wrong") => wrong.push(value),
+            _ => {
+                return Err(format!(
+                    "{}:{}: unknown key `{}` in [{}]",
+                    name, line, key, kind
+                ))
+            }
+        }
+    }
+    let prompt = prompt.ok_or_else(|| format!("{}:{}: missing `prompt`", name, number))?;
+    if kind == "quantity" {
+        return Ok(Question::Quantity {
+            prompt,
+            quantity: quantity.ok_or_else(|| format!("{}:{}: missing `value`", name, number))?,
+            unit,
+        });
+    }
+    if wrong.is_empty() || wrong.len() >= MAX_CHOICES {
+        return Err(format!(
+            "{}:{}: expected 1 to {} `wrong` answers",
+            name,
+            number,
+            MAX_CHOICES - 1
+        ));
+    }
+    Ok(Question::Choice {
+        prompt,
+        answer: answer.ok_or_else(|| format!("{}:{}: missing `answer`", name, number))?,
+        wrong,
+    })
+}
+
+fn ask(question: &Question, state: &Observation, rng: &mut Rng) -> Asked {
+    let (prompt, answer, mut wrong) = match question {
+        Question::Quantity {
+            prompt,
+            quantity,
+            unit,
+        } => {
+            let value = quantity.evaluate(state);
+            let unit = quantity
+                .unit(state.angles)
+                .unwrap_or_else(|| format!(" {}", unit));
+            let format = |it: f64| format!("{:.2}{}", it, unit).trim_end().to_string();
+            let answer = format(value);
+            // distractors scaled from the right value, or offset from it near zero
+            let mut candidates: Vec<f64> = if value.abs() < 0.1 {
+                [0.2, -0.2, 0.5, -0.5, 1.0]
+                    .iter()
+                    .map(|it| value + it)
+                    .collect()
+            } else {
+                [0.5, 0.75, 1.5, 2.0, -1.0]
+                    .iter()
+                    .map(|it| value * it)
+                    .collect()
+            };
+            rng.shuffle(&mut candidates);
+            let mut wrong: Vec<String> = Vec::new();
+            for candidate in candidates {
+                let text = format(candidate);
+                if text != answer && !wrong.contains(&text) && wrong.len() < MAX_CHOICES - 1 {
+                    wrong.push(text);
+                }
+            }
+            (prompt.clone(), answer, wrong)
+        }
+        Question::Choice {
+            prompt,
+            answer,
+            wrong,
+        } => (prompt.clone(), answer.clone(), wrong.clone()),
+    };
+    rng.shuffle(&mut wrong);
+    let correct = rng.below(wrong.len() + 1);
+    let mut choices = wrong;
+    choices.insert(correct, answer);
+    Asked {
+        prompt,
+        choices,
+        correct,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE: Observation = Observation {
+        theta: 0.5,
+        w: -1.2,
+        length: 200.0,
+        angles: Convention::DEFAULT,
+    };
+
+    #[test]
+    fn parses_the_default_quiz() {
+        let quiz = Quiz::parse(include_str!("../quiz.txt"), "quiz.txt").unwrap();
+        assert!(quiz.questions.len() >= 6);
+    }
+
+    #[test]
+    fn reports_the_line_of_a_mistake() {
+        let cases = [
+            ("prompt = ?", "test:1: `prompt` is outside"),
+            (
+                "[quantity]\nprompt = ?\nvalue = mass",
+                "test:3: unknown value `mass`",
+            ),
+            (
+                "[choice]\nanswer = a\nwrong = b",
+                "test:1: missing `prompt`",
+            ),
+            (
+                "[choice]\nprompt = ?\nwrong = b",
+                "test:1: missing `answer`",
+            ),
+            ("[riddle]\nprompt = ?", "test:1: unknown section [riddle]"),
+            (
+                "[quantity]\nprompt = ?\nvalue",
+                "test:3: expected `key = value`",
+            ),
+            ("# nothing but comments", "test: no questions"),
+        ];
+        for (source, error) in cases {
+            let message = Quiz::parse(source, "test").err().unwrap();
+            assert!(
+                message.starts_with(error),
+                "{:?} gave {:?}",
+                source,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn takes_one_to_three_wrong_answers() {
+        let choice = |wrong: usize| {
+            let mut source = "[choice]\nprompt = ?\nanswer = a\n".to_string();
+            for i in 0..wrong {
+                source.push_str(&format!("wrong = {}\n", i));
+            }
+            Quiz::parse(&source, "test")
+        };
+        assert!(choice(0).is_err());
+        assert!(choice(1).is_ok());
+        assert!(choice(MAX_CHOICES - 1).is_ok());
+        assert!(choice(MAX_CHOICES).is_err());
+    }
+
+    #[test]
+    fn answers_quantities_from_the_state() {
+        let question = Question::Quantity {
+            prompt: "?".to_string(),
+            quantity: Quantity::Speed,
+            unit: "m/s".to_string(),
+        };
+        let mut rng = Rng::new(1);
+        for _ in 0..20 {
+            let asked = ask(&question, &STATE, &mut rng);
+            assert_eq!(asked.choices[asked.correct], "2.40 m/s");
+            assert_eq!(asked.choices.len(), MAX_CHOICES);
+            let mut unique = asked.choices.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(unique.len(), asked.choices.len());
+        }
+    }
+
+    #[test]
+    fn counts_right_and_wrong_answers() {
+        let mut quiz = Quiz::parse("[choice]\nprompt = ?\nanswer = a\nwrong = b", "test").unwrap();
+        quiz.tick(MAX_GAP, &STATE);
+        let correct = quiz.asked.as_ref().unwrap().correct;
+        quiz.answer(correct);
+        assert_eq!((quiz.correct, quiz.answered), (1, 1));
+        assert!(quiz.is_paused());
+        quiz.tick(FEEDBACK, &STATE);
+        assert!(!quiz.is_paused());
+        quiz.tick(MAX_GAP, &STATE);
+        quiz.answer(1 - quiz.asked.as_ref().unwrap().correct);
+        assert_eq!((quiz.correct, quiz.answered), (1, 2));
+    }
+}
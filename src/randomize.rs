@@ -0,0 +1,307 @@
+use std::ops::RangeInclusive;
+
+use crate::physics::{Params, DAMPINGS, DRIVES, DRIVE_FREQUENCIES, GRAVITIES, LENGTHS};
+use crate::rng::Rng;
+
+/// What the start can be drawn for, with the units the file gives it in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Parameter {
+    // degrees, and rad/s
+    Theta,
+    W,
+    // of the second rod of a double pendulum, from the vertical too
+    Theta2,
+    W2,
+    // cm, and m/s²
+    Length,
+    Gravity,
+    // 1/s, rad/s² and rad/s
+    Damping,
+    Drive,
+    DriveFrequency,
+}
+
+impl Parameter {
+    const ALL: [Parameter; 9] = [
+        Parameter::Theta,
+        Parameter::W,
+        Parameter::Theta2,
+        Parameter::W2,
+        Parameter::Length,
+        Parameter::Gravity,
+        Parameter::Damping,
+        Parameter::Drive,
+        Parameter::DriveFrequency,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Parameter::Theta => "theta",
+            Parameter::W => "w",
+            Parameter::Theta2 => "theta2",
+            Parameter::W2 => "w2",
+            Parameter::Length => "length",
+            Parameter::Gravity => "gravity",
+            Parameter::Damping => "damping",
+            Parameter::Drive => "drive",
+            Parameter::DriveFrequency => "drive-frequency",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Parameter::Theta | Parameter::Theta2 => "°",
+            Parameter::W | Parameter::W2 => " rad/s",
+            Parameter::Length => " cm",
+            Parameter::Gravity => " m/s²",
+            Parameter::Damping => " /s",
+            Parameter::Drive => " rad/s²",
+            Parameter::DriveFrequency => " rad/s",
+        }
+    }
+
+    // what it may be, in the file's units
+    fn range(self) -> RangeInclusive<f64> {
+        match self {
+            Parameter::Theta | Parameter::W | Parameter::Theta2 | Parameter::W2 => {
+                f64::MIN..=f64::MAX
+            }
+            Parameter::Length => LENGTHS,
+            // cm/s² -> m/s²
+            Parameter::Gravity => GRAVITIES.start() / 100.0..=GRAVITIES.end() / 100.0,
+            Parameter::Damping => DAMPINGS,
+            Parameter::Drive => DRIVES,
+            Parameter::DriveFrequency => DRIVE_FREQUENCIES,
+        }
+    }
+}
+
+/// What a parameter is at the start.
+#[derive(Clone, Copy, Debug)]
+enum Constraint {
+    // drawn uniformly from lo to hi
+    Within(f64, f64),
+    // set to this
+    Equal(f64),
+    // left as the command line or the default has it
+    Fixed,
+}
+
+/// Where a pendulum starts from, for the randomizer to draw.
+#[derive(Clone, Copy, Debug)]
+pub struct Start {
+    pub params: Params<f64>,
+    // rad and rad/s, and the same of the second rod of a double pendulum
+    pub theta: f64,
+    pub w: f64,
+    pub second: [f64; 2],
+}
+
+/// Ranges and values for the start, read from a `--randomize` file and
+/// drawn from a seed, so a run can be drawn again with the same `--seed`.
+pub struct Randomizer {
+    // in the file's order, each parameter once
+    constraints: Vec<(Parameter, Constraint)>,
+}
+
+impl Randomizer {
+    /// Parses lines of `<parameter> in <lo>..<hi>`, `<parameter> = <value>`
+    /// or `<parameter> fixed`, for `theta` in degrees, `w` in rad/s,
+    /// `theta2` and `w2` of a double pendulum's second rod, `length` in cm,
+    /// `gravity` in m/s², `damping`, `drive` and `drive-frequency`; `#`
+    /// starts a comment.
+    pub fn parse(source: &str, name: &str) -> Result<Self, String> {
+        let mut constraints: Vec<(Parameter, Constraint)> = Vec::new();
+        for (i, raw) in source.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| format!("{}:{}: {}", name, i + 1, message);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let word = words[0];
+            let parameter = Parameter::ALL
+                .into_iter()
+                .find(|it| it.name() == word)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = Parameter::ALL.iter().map(|it| it.name()).collect();
+                    error(format!(
+                        "unknown parameter `{}`, expected {}",
+                        word,
+                        names.join(", ")
+                    ))
+                })?;
+            let range = parameter.range();
+            let value = |raw: &str| {
+                number(raw).filter(|it| range.contains(it)).ok_or_else(|| {
+                    error(format!(
+                        "{} must be from {} to {}{}, got `{}`",
+                        word,
+                        range.start(),
+                        range.end(),
+                        parameter.unit(),
+                        raw
+                    ))
+                })
+            };
+            let constraint = match words[1..] {
+                ["in", bounds] => {
+                    let (lo, hi) = bounds.split_once("..").ok_or_else(|| {
+                        error(format!("expected `{} in <lo>..<hi>`, got `{}`", word, line))
+                    })?;
+                    let (lo, hi) = (value(lo)?, value(hi)?);
+                    if lo > hi {
+                        return Err(error(format!("{} is above {}", lo, hi)));
+                    }
+                    Constraint::Within(lo, hi)
+                }
+                ["=", raw] => Constraint::Equal(value(raw)?),
+                ["fixed"] => Constraint::Fixed,
+                _ => {
+                    return Err(error(format!(
+                        "expected `{0} in <lo>..<hi>`, `{0} = <value>` or `{0} fixed`",
+                        word
+                    )))
+                }
+            };
+            if constraints.iter().any(|(it, _)| *it == parameter) {
+                return Err(error(format!("{} is already constrained", word)));
+            }
+            constraints.push((parameter, constraint));
+        }
+        Ok(Randomizer { constraints })
+    }
+
+    /// Whether the parameter called `name` is drawn or set, and not left
+    /// as it was.
+    pub fn sets(&self, name: &str) -> bool {
+        self.constraints.iter().any(|(parameter, constraint)| {
+            parameter.name() == name && !matches!(constraint, Constraint::Fixed)
+        })
+    }
+
+    /// Draws `start` from `rng`, returning what it drew for the log.
+    pub fn draw(&self, rng: &mut Rng, start: &mut Start) -> Vec<String> {
+        let params = &mut start.params;
+        let mut drawn = Vec::new();
+        for &(parameter, constraint) in &self.constraints {
+            let value = match constraint {
+                Constraint::Within(lo, hi) => rng.range(lo, hi),
+                Constraint::Equal(value) => value,
+                Constraint::Fixed => continue,
+            };
+            match parameter {
+                Parameter::Theta => start.theta = value.to_radians(),
+                Parameter::W => start.w = value,
+                Parameter::Theta2 => start.second[0] = value.to_radians(),
+                Parameter::W2 => start.second[1] = value,
+                Parameter::Length => params.length = value,
+                // m/s² -> cm/s²
+                Parameter::Gravity => params.gravity = value * 100.0,
+                Parameter::Damping => params.damping = value,
+                Parameter::Drive => params.drive = value,
+                Parameter::DriveFrequency => params.drive_frequency = value,
+            }
+            drawn.push(format!(
+                "{} {:.3}{}",
+                parameter.name(),
+                value,
+                parameter.unit()
+            ));
+        }
+        drawn
+    }
+}
+
+fn number(word: &str) -> Option<f64> {
+    word.parse::<f64>().ok().filter(|it| it.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_each_kind_of_constraint() {
+        let source = "# a comment\ntheta in -30..30\n\nlength = 150 # cm\ngravity fixed\n";
+        let randomizer = Randomizer::parse(source, "start.txt").unwrap();
+        let read: Vec<String> = randomizer
+            .constraints
+            .iter()
+            .map(|(parameter, constraint)| format!("{} {:?}", parameter.name(), constraint))
+            .collect();
+        assert_eq!(
+            read,
+            [
+                "theta Within(-30.0, 30.0)",
+                "length Equal(150.0)",
+                "gravity Fixed"
+            ]
+        );
+        assert!(randomizer.sets("theta") && randomizer.sets("length"));
+        assert!(!randomizer.sets("gravity") && !randomizer.sets("theta2"));
+    }
+
+    #[test]
+    fn says_what_is_wrong_with_a_line() {
+        for (source, error) in [
+            (
+                "mass = 2",
+                "start.txt:1: unknown parameter `mass`, expected theta, w",
+            ),
+            (
+                "\nlength = 20",
+                "start.txt:2: length must be from 50 to 200 cm, got `20`",
+            ),
+            ("theta in 30..-30", "start.txt:1: 30 is above -30"),
+            ("theta in 30", "start.txt:1: expected `theta in <lo>..<hi>`"),
+            ("w = inf", "start.txt:1: w must be from"),
+            (
+                "damping",
+                "start.txt:1: expected `damping in <lo>..<hi>`, `damping = <value>`",
+            ),
+            ("w fixed\nw = 1", "start.txt:2: w is already constrained"),
+        ] {
+            let message = Randomizer::parse(source, "start.txt").err().unwrap();
+            assert!(
+                message.starts_with(error),
+                "{:?} gave {:?}",
+                source,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn draws_the_same_start_from_the_same_seed() {
+        let randomizer =
+            Randomizer::parse("theta in -30..30\ngravity = 3.7\nw fixed", "start.txt").unwrap();
+        let draw = |seed| {
+            let mut start = Start {
+                params: Params {
+                    length: 200.0,
+                    gravity: 981.0,
+                    damping: 0.0,
+                    drag: 0.0,
+                    wind: 0.0,
+                    drive: 0.0,
+                    drive_frequency: 1.0,
+                    drive2: 0.0,
+                    drive_frequency2: 0.0,
+                    torque: 0.0,
+                },
+                theta: 1.0,
+                w: 0.5,
+                second: [0.0; 2],
+            };
+            let drawn = randomizer.draw(&mut Rng::new(seed), &mut start);
+            (start, drawn)
+        };
+        let (start, drawn) = draw(7);
+        assert!(start.theta.abs() <= 30f64.to_radians());
+        assert_eq!((start.params.gravity, start.w), (370.0, 0.5));
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(draw(7).0.theta, start.theta);
+        assert_ne!(draw(8).0.theta, start.theta);
+    }
+}
@@ -0,0 +1,270 @@
+use std::f64::consts::TAU;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::WindowCanvas;
+use sdl2::surface::Surface;
+
+// samples a second of audio.wav, mono 16 bit
+const SAMPLE_RATE: u32 = 44_100;
+// s a click at a zero crossing lasts, and its pitch in Hz
+const CLICK: f64 = 0.004;
+const CLICK_PITCH: f64 = 2000.0;
+// s each caption is shown for, and the HUD lines at most it gives
+const CAPTION: f64 = 1.0;
+const CAPTION_LINES: usize = 3;
+
+/// The sound of the recording as a WAV file, written as it goes: the
+/// Doppler tone while it's on and a click every time the bob passes the
+/// bottom, one sample a 1/44100 s of wall-clock time, as the frames go.
+struct Soundtrack {
+    out: BufWriter<File>,
+    // written so far
+    samples: u32,
+    // of the tone, in turns, and s left of a click sounding
+    phase: f64,
+    click: f64,
+}
+
+impl Soundtrack {
+    fn create(path: &Path) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&header(0))?;
+        Ok(Soundtrack {
+            out,
+            samples: 0,
+            phase: 0.0,
+            click: 0.0,
+        })
+    }
+
+    // up to `until` s, a click starting now if `clicks`, and the header
+    // brought up to date so a crash still leaves a file that plays
+    fn extend(&mut self, until: f64, tone: Option<(f64, f64)>, clicks: bool) -> io::Result<()> {
+        if clicks {
+            self.click = CLICK;
+        }
+        let dt = 1.0 / SAMPLE_RATE as f64;
+        let end = (until * SAMPLE_RATE as f64) as u32;
+        while self.samples < end {
+            let mut sample = 0.0;
+            if let Some((frequency, volume)) = tone {
+                sample += volume * (self.phase * TAU).sin();
+                self.phase = (self.phase + frequency * dt).fract();
+            }
+            if self.click > 0.0 {
+                // a short burst dying away
+                let age = CLICK - self.click;
+                sample += 0.5 * (age * CLICK_PITCH * TAU).sin() * self.click / CLICK;
+                self.click -= dt;
+            }
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f64) as i16;
+            self.out.write_all(&value.to_le_bytes())?;
+            self.samples += 1;
+        }
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header(self.samples))?;
+        self.out.seek(SeekFrom::End(0))?;
+        self.out.flush()
+    }
+}
+
+// a canonical WAV header for `samples` of mono 16-bit PCM
+fn header(samples: u32) -> Vec<u8> {
+    let data = samples * 2;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // bytes a second and a sample, and bits a sample
+    header.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    header.extend_from_slice(&2u16.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data.to_le_bytes());
+    header
+}
+
+/// Captions of the recording, as SRT and WebVTT files with the same cues:
+/// one every second of wall-clock time, of what was on screen when it
+/// started, for players and editors that show subtitles.
+struct Captions {
+    srt: BufWriter<File>,
+    vtt: BufWriter<File>,
+    // written so far, and s the next one is due at
+    cues: u64,
+    next: f64,
+}
+
+impl Captions {
+    fn create(dir: &Path) -> io::Result<Self> {
+        let srt = BufWriter::new(File::create(dir.join("captions.srt"))?);
+        let mut vtt = BufWriter::new(File::create(dir.join("captions.vtt"))?);
+        writeln!(vtt, "WEBVTT")?;
+        writeln!(vtt)?;
+        Ok(Captions {
+            srt,
+            vtt,
+            cues: 0,
+            next: 0.0,
+        })
+    }
+
+    /// Cues `lines` if one is due by `wall` s, for the next second.
+    fn cue(&mut self, wall: f64, lines: &[String]) -> io::Result<()> {
+        if wall < self.next {
+            return Ok(());
+        }
+        // on the second, carrying on from now after a stall
+        let start = (wall / CAPTION).floor() * CAPTION;
+        let end = start + CAPTION;
+        self.next = end;
+        self.cues += 1;
+        let text = if lines.is_empty() {
+            "…".to_string()
+        } else {
+            lines.join("\n")
+        };
+        writeln!(
+            self.srt,
+            "{}\n{} --> {}\n{}\n",
+            self.cues,
+            timestamp(start, ','),
+            timestamp(end, ','),
+            text
+        )?;
+        writeln!(
+            self.vtt,
+            "{} --> {}\n{}\n",
+            timestamp(start, '.'),
+            timestamp(end, '.'),
+            text
+        )?;
+        self.srt.flush()?;
+        self.vtt.flush()
+    }
+}
+
+// hh:mm:ss and then milliseconds after `separator`, a comma in SRT and a dot in WebVTT
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// Every frame of the window saved into a folder as a numbered BMP, an
+/// image sequence video editors import at a frame rate, with `frames.csv`
+/// beside it giving each frame's wall-clock and simulated time and the
+/// swing, so the footage can be lined up to the frame with plots of the
+/// same data, `audio.wav` of the sound, lined up with the frames, and
+/// `captions.srt` and `captions.vtt`.
+pub struct Recorder {
+    dir: PathBuf,
+    sidecar: BufWriter<File>,
+    audio: Soundtrack,
+    captions: Captions,
+    // what the next frame's caption would say
+    caption: Vec<String>,
+    // zero crossings since the last frame
+    crossed: bool,
+    // frames saved so far, the next one's number
+    pub frame: u64,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Creates `dir` if it isn't there and starts `frames.csv`, `audio.wav`
+    /// and the captions in it.
+    pub fn open(dir: &str) -> io::Result<Self> {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir)?;
+        let mut sidecar = BufWriter::new(File::create(dir.join("frames.csv"))?);
+        writeln!(sidecar, "frame,file,wall_s,time_s,mode,theta_rad,w_rad_s")?;
+        let audio = Soundtrack::create(&dir.join("audio.wav"))?;
+        let captions = Captions::create(&dir)?;
+        Ok(Recorder {
+            dir,
+            sidecar,
+            audio,
+            captions,
+            caption: Vec::new(),
+            crossed: false,
+            frame: 0,
+            started: Instant::now(),
+        })
+    }
+
+    /// Clicks in the sound of the next frame, the bob having passed the bottom.
+    pub fn click(&mut self) {
+        self.crossed = true;
+    }
+
+    /// Captions the next frame with the dialog on screen if there is one,
+    /// or else the first of the HUD's values.
+    pub fn caption(&mut self, values: &[String], overlay: Option<&[String]>) {
+        self.caption = match overlay {
+            Some(overlay) => overlay.to_vec(),
+            None => values.iter().take(CAPTION_LINES).cloned().collect(),
+        };
+    }
+
+    /// Saves what `canvas` shows as the next frame, simulated `time` s in,
+    /// with θ and ω of the single pendulum, none in the other modes, and the
+    /// sound since the last one, the Doppler `tone`'s frequency in Hz and
+    /// its volume from 0 to 1 if it's on.
+    pub fn record(
+        &mut self,
+        canvas: &WindowCanvas,
+        time: f64,
+        mode: &str,
+        swing: Option<(f64, f64)>,
+        tone: Option<(f64, f64)>,
+    ) -> Result<(), String> {
+        let wall = self.started.elapsed().as_secs_f64();
+        self.audio
+            .extend(wall, tone, self.crossed)
+            .map_err(|error| error.to_string())?;
+        self.crossed = false;
+        self.captions
+            .cue(wall, &self.caption)
+            .map_err(|error| error.to_string())?;
+        let file = format!("frame-{:06}.bmp", self.frame);
+        let (width, height) = canvas.output_size()?;
+        let mut pixels = canvas.read_pixels(None, PixelFormatEnum::RGB24)?;
+        Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 3,
+            PixelFormatEnum::RGB24,
+        )?
+        .save_bmp(self.dir.join(&file))?;
+        let (theta, w) = match swing {
+            Some((theta, w)) => (format!("{:.6}", theta), format!("{:.6}", w)),
+            None => (String::new(), String::new()),
+        };
+        writeln!(
+            self.sidecar,
+            "{},{},{:.6},{:.6},{},{},{}",
+            self.frame, file, wall, time, mode, theta, w
+        )
+        .and_then(|_| self.sidecar.flush())
+        .map_err(|error| error.to_string())?;
+        self.frame += 1;
+        Ok(())
+    }
+}
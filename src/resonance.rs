@@ -0,0 +1,306 @@
+use std::f64::consts::TAU;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+
+use crate::cli::ResonanceOptions;
+use crate::events;
+use crate::integrator::{Integrator, Scheme};
+use crate::physics::{Params, Pendulum};
+use crate::{G, L};
+
+/// Drive frequencies the sweep can go between, over the natural one.
+pub const RATIOS: RangeInclusive<f64> = 0.1..=3.0;
+/// Swing damping of the sweep in 1/s. Without any there is no steady
+/// state to wait for, and the swing at resonance grows without bound.
+pub const SWEEP_DAMPINGS: RangeInclusive<f64> = 0.01..=2.0;
+
+// drive periods the swing is measured over once it has settled
+const MEASURED: f64 = 4.0;
+// size of the SVG plot and the margin around its axes
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 400.0;
+const MARGIN: f64 = 50.0;
+
+/// The steady swing at one drive frequency.
+struct Point {
+    // Ω/ω0
+    ratio: f64,
+    // rad, the furthest either side of the bottom once settled
+    amplitude: f64,
+}
+
+/// Runs the `resonance` subcommand: the damped single pendulum driven
+/// by A·cos(Ω·t), Ω stepped slowly from `--from` to `--to` times its
+/// natural frequency, the swing left to settle at each and its
+/// amplitude measured. The swing carries on from one frequency to the
+/// next instead of starting over, as it would on a real sweep, and with
+/// `--hysteresis` sweeps back down after from where it was left. Writes
+/// the curve with the linear response beside it as CSV and as an SVG
+/// plot, and a summary on stdout.
+pub fn run(options: &ResonanceOptions) {
+    let natural = (G / L).sqrt();
+    let params = Params {
+        length: L,
+        gravity: G,
+        damping: options.damping,
+        drag: 0.0,
+        wind: 0.0,
+        drive: options.drive,
+        drive_frequency: 0.0,
+        drive2: 0.0,
+        drive_frequency2: 0.0,
+        torque: 0.0,
+    };
+    let mut sweep = Sweep {
+        params,
+        pendulum: Pendulum { theta: 0.0, w: 0.0 },
+        time: 0.0,
+    };
+    let ratios: Vec<f64> = (0..options.steps)
+        .map(|i| {
+            let along = if options.steps > 1 {
+                i as f64 / (options.steps - 1) as f64
+            } else {
+                0.0
+            };
+            options.from + (options.to - options.from) * along
+        })
+        .collect();
+    let points: Vec<Point> = ratios
+        .iter()
+        .map(|&ratio| sweep.settle(options, natural, ratio))
+        .collect();
+    // and back down again from where the sweep up left the swing
+    let down: Vec<Point> = if options.hysteresis {
+        ratios
+            .iter()
+            .rev()
+            .map(|&ratio| sweep.settle(options, natural, ratio))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let linear = |ratio: f64| linear(&params, natural, ratio);
+    println!(
+        "resonance of the single pendulum, damping {} 1/s, drive {} rad/s², ω0 = {:.4} rad/s, Q = {:.2}",
+        options.damping,
+        options.drive,
+        natural,
+        natural / options.damping
+    );
+    if let Some(peak) = points
+        .iter()
+        .max_by(|a, b| a.amplitude.total_cmp(&b.amplitude))
+    {
+        println!(
+            "swung the most at Ω/ω0 = {:.3}, {:.4} rad; the linear response peaks at {:.3}, {:.4} rad",
+            peak.ratio,
+            peak.amplitude,
+            linear_peak(&params, natural),
+            linear(linear_peak(&params, natural))
+        );
+    }
+    if options.hysteresis {
+        for (name, branch) in [("up", &points), ("down", &down)] {
+            if let Some((before, after)) = jump(branch) {
+                println!(
+                    "the sweep {} jumped from {:.4} rad at Ω/ω0 = {:.3} to {:.4} rad at {:.3}",
+                    name, before.amplitude, before.ratio, after.amplitude, after.ratio
+                );
+            }
+        }
+    }
+    match export(&options.out, &points, &down, natural, linear) {
+        Ok(()) => println!("Saved {}", options.out),
+        Err(error) => eprintln!("Unable to write {}: {}", options.out, error),
+    }
+    match fs::write(&options.svg, svg(options, &points, &down, linear)) {
+        Ok(()) => println!("Saved {}", options.svg),
+        Err(error) => eprintln!("Unable to write {}: {}", options.svg, error),
+    }
+}
+
+/// The swept pendulum, carried on from one drive frequency to the next.
+struct Sweep {
+    params: Params<f64>,
+    pendulum: Pendulum<f64>,
+    // s
+    time: f64,
+}
+
+impl Sweep {
+    // drives the swing at `ratio` times ω0 until it settles, then
+    // measures it
+    fn settle(&mut self, options: &ResonanceOptions, natural: f64, ratio: f64) -> Point {
+        let frequency = ratio * natural;
+        // the same point of the drive's cycle at the new frequency, so
+        // changing it doesn't jolt the swing
+        if self.params.drive_frequency > 0.0 {
+            self.time *= self.params.drive_frequency / frequency;
+        }
+        self.params.drive_frequency = frequency;
+        let period = TAU / frequency;
+        let settled = self.time + options.settle * period;
+        while self.time < settled {
+            self.step(options.dt);
+        }
+        let mut amplitude: f64 = 0.0;
+        let measured = self.time + MEASURED * period;
+        while self.time < measured {
+            self.step(options.dt);
+            amplitude = amplitude.max(events::wrap(self.pendulum.theta).abs());
+        }
+        Point { ratio, amplitude }
+    }
+
+    fn step(&mut self, dt: f64) {
+        Scheme::RungeKutta4.step(&mut self.pendulum, &self.params, self.time, dt);
+        self.time += dt;
+    }
+}
+
+// the neighbouring points of a branch the swing changed the most
+// between, where it fell off or onto the upper branch
+fn jump(branch: &[Point]) -> Option<(&Point, &Point)> {
+    branch
+        .windows(2)
+        .max_by(|a, b| {
+            (a[1].amplitude - a[0].amplitude)
+                .abs()
+                .total_cmp(&(b[1].amplitude - b[0].amplitude).abs())
+        })
+        .map(|pair| (&pair[0], &pair[1]))
+}
+
+// rad, the steady swing of the linearized pendulum θ'' = -ω0²θ - bθ' +
+// A·cos(Ω·t), a Lorentzian about ω0 when the damping is light
+fn linear(params: &Params<f64>, natural: f64, ratio: f64) -> f64 {
+    let frequency = ratio * natural;
+    let detuning = natural * natural - frequency * frequency;
+    params.drive / (detuning * detuning + (params.damping * frequency).powi(2)).sqrt()
+}
+
+// Ω/ω0 the linear response is largest at, √(1 - b²/2ω0²), or 0 when
+// it's damped too heavily to peak at all
+fn linear_peak(params: &Params<f64>, natural: f64) -> f64 {
+    (1.0 - params.damping * params.damping / (2.0 * natural * natural))
+        .max(0.0)
+        .sqrt()
+}
+
+fn export(
+    path: &str,
+    up: &[Point],
+    down: &[Point],
+    natural: f64,
+    linear: impl Fn(f64) -> f64,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "sweep,ratio,frequency_rad_s,amplitude_rad,linear_rad")?;
+    let branches = up.iter().map(|it| ("up", it));
+    for (sweep, point) in branches.chain(down.iter().map(|it| ("down", it))) {
+        writeln!(
+            out,
+            "{},{:.4},{:.4},{:.6},{:.6}",
+            sweep,
+            point.ratio,
+            point.ratio * natural,
+            point.amplitude,
+            linear(point.ratio)
+        )?;
+    }
+    out.flush()
+}
+
+// the measured curve in blue, and the sweep back down in red, over the
+// linear response in gray, on axes of Ω/ω0 and amplitude
+fn svg(
+    options: &ResonanceOptions,
+    up: &[Point],
+    down: &[Point],
+    linear: impl Fn(f64) -> f64,
+) -> String {
+    let (low, high) = (options.from, options.to);
+    // the linear peak can be far off the top when the drive is strong,
+    // so the axis follows what was measured unless nothing moved
+    let measured = up
+        .iter()
+        .chain(down)
+        .map(|it| it.amplitude)
+        .fold(0.0, f64::max);
+    let top = if measured > 0.0 { 1.1 * measured } else { 1.0 };
+    let x = |ratio: f64| MARGIN + (ratio - low) / (high - low) * (WIDTH - 2.0 * MARGIN);
+    let y = |amplitude: f64| HEIGHT - MARGIN - (amplitude / top).min(1.0) * (HEIGHT - 2.0 * MARGIN);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        WIDTH, HEIGHT
+    );
+    let _ = writeln!(
+        out,
+        "<polyline fill=\"none\" stroke=\"black\" points=\"{0},{1} {0},{2} {3},{2}\"/>",
+        MARGIN,
+        MARGIN,
+        HEIGHT - MARGIN,
+        WIDTH - MARGIN
+    );
+    let label = |out: &mut String, x: f64, y: f64, anchor: &str, text: &str| {
+        let _ = writeln!(
+            out,
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"12\" text-anchor=\"{}\">{}</text>",
+            x, y, anchor, text
+        );
+    };
+    label(
+        &mut out,
+        x(low),
+        HEIGHT - MARGIN + 16.0,
+        "middle",
+        &format!("{:.2}", low),
+    );
+    label(
+        &mut out,
+        x(high),
+        HEIGHT - MARGIN + 16.0,
+        "middle",
+        &format!("{:.2}", high),
+    );
+    if (low..=high).contains(&1.0) {
+        label(&mut out, x(1.0), HEIGHT - MARGIN + 16.0, "middle", "1");
+    }
+    label(&mut out, WIDTH / 2.0, HEIGHT - 12.0, "middle", "Ω/ω0");
+    label(&mut out, MARGIN - 6.0, y(0.0) + 4.0, "end", "0");
+    label(
+        &mut out,
+        MARGIN - 6.0,
+        y(top) + 4.0,
+        "end",
+        &format!("{:.3}", top),
+    );
+    label(&mut out, MARGIN, MARGIN - 12.0, "start", "amplitude (rad)");
+    // the linear response, finer than the sweep so its peak isn't cut
+    out.push_str("<polyline fill=\"none\" stroke=\"gray\" stroke-dasharray=\"4 3\" points=\"");
+    for i in 0..=200 {
+        let ratio = low + (high - low) * i as f64 / 200.0;
+        let _ = write!(out, "{:.1},{:.1} ", x(ratio), y(linear(ratio)));
+    }
+    out.push_str("\"/>\n");
+    for (branch, color) in [(up, "rgb(0,90,200)"), (down, "rgb(200,40,40)")] {
+        if branch.is_empty() {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "<polyline fill=\"none\" stroke=\"{}\" points=\"",
+            color
+        );
+        for point in branch {
+            let _ = write!(out, "{:.1},{:.1} ", x(point.ratio), y(point.amplitude));
+        }
+        out.push_str("\"/>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
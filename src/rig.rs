@@ -0,0 +1,147 @@
+use std::f64::consts::{PI, TAU};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use serialport::SerialPort;
+
+use crate::events;
+
+// how long opening the port and writing a command may take
+const TIMEOUT: Duration = Duration::from_millis(50);
+
+/// A real cart-pole rig on a serial port, its pole's angle and its cart's
+/// position read by encoders and its cart pushed by a motor, in a line-based
+/// text protocol.
+///
+/// The rig sends `E <µs> <pole counts> <cart counts>` for every sample of its
+/// encoders: the µs of its own clock it took it at, the pole's encoder zeroed
+/// with the pole hanging straight down and counting counterclockwise as seen
+/// from the front, and the cart's zeroed in the middle of the track and
+/// counting to the right. The app answers every step with `M <cm/s²>`, the
+/// push the motor should speed the cart up by, rightwards positive. Each line
+/// ends with `\n`; lines of anything else are left alone, so the firmware can
+/// print what it likes beside them.
+pub struct Rig {
+    port: Box<dyn SerialPort>,
+    // the bytes of a line only partly read
+    partial: Vec<u8>,
+    // counts per revolution of the pole's encoder, and cm per count of the
+    // cart's
+    resolution: u32,
+    track: f64,
+    // s of the rig's clock and cm and θ from upright of the sample before,
+    // for telling the speeds from
+    before: Option<(f64, f64, f64)>,
+    // cm/s², the push commanded last
+    push: f64,
+    // every sample, as CSV
+    log: Option<Box<dyn Write>>,
+}
+
+impl Rig {
+    pub fn open(
+        path: &str,
+        baud: u32,
+        resolution: u32,
+        track: f64,
+        mut log: Option<Box<dyn Write>>,
+    ) -> io::Result<Self> {
+        let port = serialport::new(path, baud).timeout(TIMEOUT).open()?;
+        if let Some(log) = log.as_mut() {
+            writeln!(
+                log,
+                "time_s,pole_counts,cart_counts,x_cm,theta_rad,push_cm_s2"
+            )?;
+        }
+        Ok(Rig {
+            port,
+            partial: Vec::new(),
+            resolution,
+            track,
+            before: None,
+            push: 0.0,
+            log,
+        })
+    }
+
+    /// The cart-pole as the rig's latest sample has it, if one came since:
+    /// cm and cm/s of the cart, θ of the pole from upright and ω, the speeds
+    /// from how far it moved since the sample before.
+    pub fn poll(&mut self) -> io::Result<Option<[f64; 4]>> {
+        let waiting = self.port.bytes_to_read()? as usize;
+        if waiting > 0 {
+            let mut bytes = vec![0; waiting];
+            let read = self.port.read(&mut bytes)?;
+            self.partial.extend_from_slice(&bytes[..read]);
+        }
+        let mut latest = None;
+        while let Some(end) = self.partial.iter().position(|it| *it == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            let Some((micros, pole, cart)) = sample(&String::from_utf8_lossy(&line)) else {
+                continue;
+            };
+            let time = micros as f64 / 1e6;
+            let x = cart as f64 * self.track;
+            let theta = events::wrap(pole as f64 / self.resolution as f64 * TAU - PI);
+            let (v, w) = match self.before {
+                Some((then, x_before, theta_before)) if time > then => (
+                    (x - x_before) / (time - then),
+                    events::wrap(theta - theta_before) / (time - then),
+                ),
+                _ => (0.0, 0.0),
+            };
+            self.before = Some((time, x, theta));
+            if let Some(log) = self.log.as_mut() {
+                writeln!(
+                    log,
+                    "{:.6},{},{},{:.4},{:.6},{:.1}",
+                    time, pole, cart, x, theta, self.push
+                )?;
+            }
+            latest = Some([x, v, theta, w]);
+        }
+        Ok(latest)
+    }
+
+    /// Tells the rig to push the cart with `push` cm/s².
+    pub fn command(&mut self, push: f64) -> io::Result<()> {
+        self.push = push;
+        writeln!(self.port, "M {:.1}", push)?;
+        if let Some(log) = self.log.as_mut() {
+            log.flush()?;
+        }
+        Ok(())
+    }
+}
+
+// the µs, pole counts and cart counts of a sample line, if it is one
+fn sample(line: &str) -> Option<(u64, i64, i64)> {
+    let mut parts = line.split_whitespace();
+    if parts.next() != Some("E") {
+        return None;
+    }
+    let micros = parts.next()?.parse().ok()?;
+    let pole = parts.next()?.parse().ok()?;
+    let cart = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((micros, pole, cart))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_only_whole_sample_lines() {
+        assert_eq!(sample("E 1500 -20 300\n"), Some((1500, -20, 300)));
+        assert_eq!(sample("E 1500 -20 300\r\n"), Some((1500, -20, 300)));
+        for line in [
+            "booting v1.2\n",
+            "E 1500 -20\n",
+            "E 1500 -20 300 7\n",
+            "E -1 0 0\n",
+            "M 12.5\n",
+        ] {
+            assert_eq!(sample(line), None, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, WindowCanvas};
+use sdl2::rwops::RWops;
+
+use crate::bundle;
+use crate::cli::SandOptions;
+use crate::physics::{Params, Pendulum, SphericalPendulum};
+use crate::rng::Rng;
+use crate::{save_bmp, text, BORDER, G, HEIGHT, WIDTH};
+
+// no damping in the equations, DAMPING below slows both kinds of swing alike
+const PARAMS: Params<f64> = Params {
+    length: 100.0,
+    gravity: G,
+    damping: 0.0,
+    drag: 0.0,
+    wind: 0.0,
+    drive: 0.0,
+    drive_frequency: 0.0,
+    drive2: 0.0,
+    drive_frequency2: 0.0,
+    torque: 0.0,
+};
+// rad from the vertical the bob starts at, at most
+const MAX_THETA: f64 = 0.6;
+// s
+const DT: f64 = 1.0 / 600.0;
+// 1/s, air drag slowly winding the swing down
+const DAMPING: f64 = 0.01;
+// px, sand radius when the bob stands still, as at the ends of a swing
+const MAX_BRUSH: f64 = 5.0;
+// cm/s at which the brush is half its widest
+const SLOW: f64 = 20.0;
+// px
+const ERASER: i16 = 12;
+const FLOOR: Color = Color::RGB(240, 228, 200);
+// translucent so passes over the same spot pile up darker
+const SAND: Color = Color::RGBA(120, 90, 50, 40);
+
+/// The swinging bob, seen from above.
+enum Swing {
+    // small swings along x and y, each a plain pendulum
+    Planar(Pendulum<f64>, Pendulum<f64>),
+    Spherical(SphericalPendulum),
+}
+
+impl Swing {
+    fn random(rng: &mut Rng, planar: bool) -> Self {
+        let theta = rng.range(0.5 * MAX_THETA, MAX_THETA);
+        // sideways speed as a fraction of the one that circles without swinging
+        let radius = PARAMS.length * theta.sin();
+        let speed = rng.range(0.1, 0.6) * (PARAMS.gravity * radius * theta.tan()).sqrt();
+        if planar {
+            Swing::Planar(
+                Pendulum::new(theta),
+                Pendulum {
+                    theta: 0.0,
+                    w: speed / PARAMS.length,
+                },
+            )
+        } else {
+            Swing::Spherical(SphericalPendulum::new(&PARAMS, theta, speed))
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        let drag = (-DAMPING * dt).exp();
+        match self {
+            Swing::Planar(x, y) => {
+                for axis in [x, y] {
+                    axis.step(&PARAMS, dt);
+                    axis.w *= drag;
+                }
+            }
+            Swing::Spherical(pendulum) => {
+                pendulum.step(&PARAMS, dt);
+                pendulum.velocity = pendulum.velocity.map(|it| it * drag);
+            }
+        }
+    }
+
+    // bob position on the floor below the pivot, in cm
+    fn floor(&self) -> (f64, f64) {
+        match self {
+            Swing::Planar(x, y) => (PARAMS.length * x.theta.sin(), PARAMS.length * y.theta.sin()),
+            Swing::Spherical(pendulum) => (pendulum.position[0], pendulum.position[1]),
+        }
+    }
+
+    // cm/s
+    fn speed(&self) -> f64 {
+        match self {
+            Swing::Planar(x, y) => PARAMS.length * x.w.hypot(y.w),
+            Swing::Spherical(pendulum) => pendulum.speed(),
+        }
+    }
+}
+
+/// Runs the `sand` subcommand.
+pub fn run(options: &SandOptions) {
+    let mut rng = options.seed.map_or_else(Rng::from_time, Rng::new);
+    let sdl_context = sdl2::init().unwrap();
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let font = ttf_context
+        .load_font_from_rwops(RWops::from_bytes(bundle::font()).unwrap(), 16)
+        .unwrap();
+    let mut window = sdl_context
+        .video()
+        .unwrap()
+        .window("Sand pendulum", WIDTH, HEIGHT)
+        .position_centered()
+        .build()
+        .expect("Window couldn't be created.");
+    bundle::set_icon(&mut window);
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    let texture_creator = canvas.texture_creator();
+    // the floor, accumulating sand from one frame to the next
+    let mut floor = texture_creator
+        .create_texture_target(PixelFormatEnum::RGBA8888, WIDTH, HEIGHT)
+        .expect("Unable to create floor texture");
+    let hint = text(
+        &texture_creator,
+        &font,
+        "R: new swing, X: sweep, mouse: erase, E: export",
+    );
+
+    let mut swing = Swing::random(&mut rng, options.planar);
+    let mut sweep = true;
+    let mut export = false;
+    let mut events = sdl_context.event_pump().unwrap();
+    'main: loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'main,
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    ..
+                } => swing = Swing::random(&mut rng, options.planar),
+                Event::KeyDown {
+                    keycode: Some(Keycode::X),
+                    ..
+                } => sweep = true,
+                Event::KeyDown {
+                    keycode: Some(Keycode::E),
+                    ..
+                } => export = true,
+                _ => {}
+            }
+        }
+        let mouse = events.mouse_state();
+        let eraser = mouse
+            .is_mouse_button_pressed(MouseButton::Left)
+            .then(|| (mouse.x() as i16, mouse.y() as i16));
+
+        // a frame's worth of sand, one grain of the brush per step
+        let mut grains = Vec::new();
+        for _ in 0..10 {
+            swing.step(DT);
+            let radius = MAX_BRUSH * SLOW / (swing.speed() + SLOW);
+            grains.push((screen(swing.floor()), radius.max(1.0).round() as i16));
+        }
+        canvas
+            .with_texture_canvas(&mut floor, |floor| {
+                if sweep {
+                    floor.set_draw_color(FLOOR);
+                    floor.clear();
+                }
+                for ((x, y), radius) in &grains {
+                    floor
+                        .filled_circle(*x, *y, *radius, SAND)
+                        .expect("Unable to draw circle");
+                }
+                if let Some((x, y)) = eraser {
+                    floor
+                        .filled_circle(x, y, ERASER, FLOOR)
+                        .expect("Unable to draw circle");
+                }
+            })
+            .expect("Unable to draw on floor");
+        sweep = false;
+        if export {
+            save(&mut canvas, &mut floor, &options.out);
+            export = false;
+        }
+
+        canvas.copy(&floor, None, None).unwrap();
+        // the rod and bob from above, the pivot being right over the center
+        let (x, y) = screen(swing.floor());
+        let (cx, cy) = screen((0.0, 0.0));
+        canvas
+            .aa_line(cx, cy, x, y, Color::RGB(90, 90, 90))
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(x, y, 8, Color::RGB(60, 60, 60))
+            .expect("Unable to draw circle");
+        let query = hint.query();
+        canvas
+            .copy(
+                &hint,
+                None,
+                Rect::new(
+                    BORDER,
+                    HEIGHT as i32 - BORDER - query.height as i32,
+                    query.width,
+                    query.height,
+                ),
+            )
+            .unwrap();
+        canvas.present();
+    }
+}
+
+// floor position in cm to window pixels, fitting the widest swing
+fn screen((x, y): (f64, f64)) -> (i16, i16) {
+    let scale = (HEIGHT as f64 / 2.0 - 2.0 * BORDER as f64) / (PARAMS.length * MAX_THETA.sin());
+    (
+        (WIDTH as f64 / 2.0 + x * scale).round() as i16,
+        (HEIGHT as f64 / 2.0 + y * scale).round() as i16,
+    )
+}
+
+// writes the floor, without the pendulum over it, as a BMP
+fn save(canvas: &mut WindowCanvas, floor: &mut Texture, path: &str) {
+    let mut pixels = Vec::new();
+    canvas
+        .with_texture_canvas(floor, |floor| {
+            pixels = floor
+                .read_pixels(None, PixelFormatEnum::RGB24)
+                .expect("Unable to read the floor");
+        })
+        .expect("Unable to read the floor");
+    save_bmp(&mut pixels, (WIDTH, HEIGHT), path);
+}
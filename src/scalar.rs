@@ -0,0 +1,62 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Number type the simulation core is written against, so the same equations
+/// can be stepped in single or double precision, with dual numbers or intervals.
+pub trait Scalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    fn from_f64(value: f64) -> Self;
+    // nearest f64, used for drawing and the HUD
+    fn to_f64(self) -> f64;
+    fn sin(self) -> Self;
+    fn abs(self) -> Self;
+}
+
+/// Scalar type of the main simulation, single precision with the `f32` feature.
+#[cfg(feature = "f32")]
+pub type Real = f32;
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+
+impl Scalar for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+impl Scalar for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn sin(self) -> Self {
+        f32::sin(self)
+    }
+
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
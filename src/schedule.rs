@@ -0,0 +1,307 @@
+use std::ops::RangeInclusive;
+
+use crate::physics::{GRAVITIES, LENGTHS};
+use crate::rng::Rng;
+
+/// rad/s a kick can change ω by, either way. Much more would only blow the
+/// pendulum up.
+pub const KICKS: RangeInclusive<f64> = -100.0..=100.0;
+
+// the noise of every run is the same, like the rest of the schedule
+const NOISE_SEED: u64 = 0x5eed;
+
+/// Something done to the pendulum at a set time.
+#[derive(Clone, Copy, Debug)]
+pub enum Disturbance {
+    // rad/s added to ω
+    Kick(f64),
+    // cm
+    Length(f64),
+    // cm/s²
+    Gravity(f64),
+    // random angular accelerations of up to `strength` rad/s² for `duration` s
+    Noise { strength: f64, duration: f64 },
+}
+
+/// Disturbances at given simulation times, read from a `--schedule` file so
+/// runs under the same disturbances can be repeated.
+pub struct Schedule {
+    // sorted by time, in s
+    entries: Vec<(f64, Disturbance)>,
+    // the first entry not done yet
+    next: usize,
+    // rad/s² and the time the burst ends
+    noise: Option<(f64, f64)>,
+    rng: Rng,
+}
+
+impl Schedule {
+    /// Parses lines of `<time> <kind> <value>`: `kick <rad/s>`, `length <cm>`,
+    /// `gravity <m/s²>` or `noise <rad/s²> <secs>`; `#` starts a comment.
+    pub fn parse(source: &str, name: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        for (i, raw) in source.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| format!("{}:{}: {}", name, i + 1, message);
+            let (time, kind, values) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [time, kind, ref values @ ..] => (time, kind, values.to_vec()),
+                _ => return Err(error("expected `<time> <kind> <value>`".to_string())),
+            };
+            let time = number(time)
+                .filter(|it| *it >= 0.0)
+                .ok_or_else(|| error(format!("the time must be in s from 0 up, got `{}`", time)))?;
+            let values = values
+                .iter()
+                .map(|it| {
+                    number(it).ok_or_else(|| error(format!("expected a number, got `{}`", it)))
+                })
+                .collect::<Result<Vec<f64>, String>>()?;
+            let disturbance = match (kind, values.as_slice()) {
+                ("kick", [dw]) if KICKS.contains(dw) => Disturbance::Kick(*dw),
+                ("kick", [dw]) => {
+                    return Err(error(format!(
+                        "a kick must be from {} to {} rad/s, got {}",
+                        KICKS.start(),
+                        KICKS.end(),
+                        dw
+                    )))
+                }
+                ("length", [length]) if LENGTHS.contains(length) => Disturbance::Length(*length),
+                ("length", [length]) => {
+                    return Err(error(format!(
+                        "the length must be from {} to {} cm, got {}",
+                        LENGTHS.start(),
+                        LENGTHS.end(),
+                        length
+                    )))
+                }
+                // m/s² -> cm/s²
+                ("gravity", [gravity]) if GRAVITIES.contains(&(gravity * 100.0)) => {
+                    Disturbance::Gravity(gravity * 100.0)
+                }
+                ("gravity", [gravity]) => {
+                    return Err(error(format!(
+                        "gravity must be from {} to {} m/s², got {}",
+                        GRAVITIES.start() / 100.0,
+                        GRAVITIES.end() / 100.0,
+                        gravity
+                    )))
+                }
+                ("noise", [strength, duration]) if *duration > 0.0 => Disturbance::Noise {
+                    strength: strength.abs(),
+                    duration: *duration,
+                },
+                ("noise", [_, duration]) => {
+                    return Err(error(format!(
+                        "the noise must last more than 0 s, got {}",
+                        duration
+                    )))
+                }
+                ("kick", _) => return Err(error("`kick` takes one value, in rad/s".to_string())),
+                ("length", _) => return Err(error("`length` takes one value, in cm".to_string())),
+                ("gravity", _) => {
+                    return Err(error("`gravity` takes one value, in m/s²".to_string()))
+                }
+                ("noise", _) => {
+                    return Err(error(
+                        "`noise` takes a strength in rad/s² and a duration in s".to_string(),
+                    ))
+                }
+                _ => {
+                    return Err(error(format!(
+                        "unknown kind `{}`, expected kick, length, gravity or noise",
+                        kind
+                    )))
+                }
+            };
+            entries.push((time, disturbance));
+        }
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Schedule {
+            entries,
+            next: 0,
+            noise: None,
+            rng: Rng::new(NOISE_SEED),
+        })
+    }
+
+    // back to the start, as when the pendulum is reset
+    pub fn rewind(&mut self) {
+        self.next = 0;
+        self.noise = None;
+        self.rng = Rng::new(NOISE_SEED);
+    }
+
+    /// Carries on from `time`, as if it had run up to there: what came before
+    /// it is done, and a noise burst it falls within goes on to its end.
+    pub fn seek(&mut self, time: f64) {
+        self.rewind();
+        while let Some(&(at, disturbance)) = self.entries.get(self.next) {
+            if at >= time {
+                break;
+            }
+            self.next += 1;
+            if let Disturbance::Noise { strength, duration } = disturbance {
+                self.noise = (at + duration > time).then_some((strength, at + duration));
+            }
+        }
+    }
+
+    /// The disturbances due by `time`, each returned once.
+    pub fn due(&mut self, time: f64) -> Vec<Disturbance> {
+        let mut due = Vec::new();
+        while let Some(&(at, disturbance)) = self.entries.get(self.next) {
+            if at > time {
+                break;
+            }
+            self.next += 1;
+            if let Disturbance::Noise { strength, duration } = disturbance {
+                self.noise = Some((strength, at + duration));
+            }
+            due.push(disturbance);
+        }
+        due
+    }
+
+    /// The rad/s a noise burst going on at `time` adds over a step of `dt` s.
+    pub fn noise(&mut self, time: f64, dt: f64) -> Option<f64> {
+        let (strength, until) = self.noise?;
+        if time >= until {
+            self.noise = None;
+            return None;
+        }
+        Some(self.rng.range(-strength, strength) * dt)
+    }
+}
+
+// a finite number, NaN and the infinities would wreck the simulation
+fn number(word: &str) -> Option<f64> {
+    word.parse::<f64>().ok().filter(|it| it.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_kind_in_order_of_time() {
+        let source = "# a comment\n\n\
+                      5 gravity 1.62\n\
+                      1 kick -0.5  # a push back\n\
+                      2.5 noise -3 1.5\n\
+                      0 length 120\n";
+        let schedule = Schedule::parse(source, "test").unwrap();
+        let times: Vec<f64> = schedule.entries.iter().map(|it| it.0).collect();
+        assert_eq!(times, [0.0, 1.0, 2.5, 5.0]);
+        let kinds: Vec<Disturbance> = schedule.entries.iter().map(|it| it.1).collect();
+        assert!(matches!(kinds[0], Disturbance::Length(length) if length == 120.0));
+        assert!(matches!(kinds[1], Disturbance::Kick(dw) if dw == -0.5));
+        assert!(matches!(
+            kinds[2],
+            Disturbance::Noise { strength, duration } if (strength, duration) == (3.0, 1.5)
+        ));
+        // m/s² -> cm/s²
+        assert!(
+            matches!(kinds[3], Disturbance::Gravity(gravity) if (gravity - 162.0).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_a_mistake() {
+        let cases = [
+            ("1", "test:1: expected `<time> <kind> <value>`"),
+            ("-1 kick 1", "test:1: the time must be in s from 0 up"),
+            ("NaN kick 1", "test:1: the time must be in s"),
+            ("\n1 kick fast", "test:2: expected a number, got `fast`"),
+            ("1 kick inf", "test:1: expected a number, got `inf`"),
+            ("1 kick 1 2", "test:1: `kick` takes one value"),
+            (
+                "1 kick -101",
+                "test:1: a kick must be from -100 to 100 rad/s",
+            ),
+            (
+                "1 length 49",
+                "test:1: the length must be from 50 to 200 cm",
+            ),
+            (
+                "1 length 201",
+                "test:1: the length must be from 50 to 200 cm",
+            ),
+            ("1 gravity 0", "test:1: gravity must be from"),
+            ("1 gravity 31", "test:1: gravity must be from"),
+            ("1 noise 1 0", "test:1: the noise must last more than 0 s"),
+            ("1 noise 1", "test:1: `noise` takes a strength"),
+            ("# fine\n1 shove 1", "test:2: unknown kind `shove`"),
+        ];
+        for (source, error) in cases {
+            let message = Schedule::parse(source, "test").err().unwrap();
+            assert!(
+                message.starts_with(error),
+                "{:?} gave {:?}",
+                source,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn takes_the_bounds_themselves() {
+        let source = format!(
+            "0 length {}\n0 length {}\n0 gravity {}\n0 gravity {}",
+            LENGTHS.start(),
+            LENGTHS.end(),
+            GRAVITIES.start() / 100.0,
+            GRAVITIES.end() / 100.0
+        );
+        assert_eq!(Schedule::parse(&source, "test").unwrap().entries.len(), 4);
+    }
+
+    #[test]
+    fn hands_out_each_disturbance_once() {
+        let mut schedule = Schedule::parse("1 kick 1\n2 kick 2\n2 kick 3", "test").unwrap();
+        assert!(schedule.due(0.5).is_empty());
+        assert_eq!(schedule.due(1.0).len(), 1);
+        assert!(schedule.due(1.5).is_empty());
+        assert_eq!(schedule.due(10.0).len(), 2);
+        assert!(schedule.due(20.0).is_empty());
+        schedule.rewind();
+        assert_eq!(schedule.due(10.0).len(), 3);
+    }
+
+    #[test]
+    fn seeks_past_what_came_before() {
+        let source = "1 kick 1\n2 noise 4 3\n3 kick 2\n4 kick 3";
+        let mut schedule = Schedule::parse(source, "test").unwrap();
+        schedule.seek(3.0);
+        // the burst from 2 s still going, and the kick at 3 s still to come
+        assert!(schedule.noise(3.0, 0.01).is_some());
+        assert_eq!(schedule.due(3.0).len(), 1);
+        schedule.seek(5.5);
+        assert!(schedule.noise(5.5, 0.01).is_none());
+        assert!(schedule.due(10.0).is_empty());
+    }
+
+    #[test]
+    fn noise_stays_within_its_strength_and_stops() {
+        let mut schedule = Schedule::parse("0 noise 4 1", "test").unwrap();
+        schedule.due(0.0);
+        let dt = 0.01;
+        let mut first = Vec::new();
+        for i in 0..100 {
+            let dw = schedule.noise(i as f64 * dt, dt).unwrap();
+            assert!(dw.abs() <= 4.0 * dt);
+            first.push(dw);
+        }
+        assert_eq!(schedule.noise(1.0, dt), None);
+        // the same burst again after a rewind
+        schedule.rewind();
+        schedule.due(0.0);
+        let again: Vec<f64> = (0..100)
+            .map(|i| schedule.noise(i as f64 * dt, dt).unwrap())
+            .collect();
+        assert_eq!(first, again);
+    }
+}
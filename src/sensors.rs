@@ -0,0 +1,94 @@
+use std::f64::consts::TAU;
+use std::io::{self, Write};
+
+use crate::convention::Convention;
+use crate::integrator;
+use crate::physics::Params;
+
+/// What the sensors read at one moment: the encoder's counts, the
+/// accelerometer along the path and along the rod in m/s², and the gyro in
+/// rad/s.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reading {
+    pub counts: i64,
+    pub tangential: f64,
+    pub radial: f64,
+    pub gyro: f64,
+}
+
+/// What the sensors read at `time` s with the pendulum at θ, ω, their
+/// encoder of `resolution` counts a revolution and in the `angles`
+/// convention.
+///
+/// The encoder was zeroed with the pendulum at the convention's zero and counts
+/// whole steps, so it reads θ quantized. The accelerometer, moving with
+/// the bob, feels the specific force, acceleration minus gravity: along
+/// the path that is l·dω/dt + g·sin θ, zero for a free swing, and along the
+/// rod towards the pivot l·ω² + g·cos θ, g hanging still. The gyro reads ω.
+pub fn read(
+    params: &Params<f64>,
+    time: f64,
+    theta: f64,
+    w: f64,
+    resolution: u32,
+    angles: Convention,
+) -> Reading {
+    let counts = (angles.unwrapped(theta) / TAU * resolution as f64).floor() as i64;
+    let tangential = angles.sign()
+        * (params.length * integrator::acceleration(params, time, theta, w)
+            + params.gravity * theta.sin());
+    let radial = params.length * w * w + params.gravity * theta.cos();
+    // cm/s² -> m/s²
+    Reading {
+        counts,
+        tangential: tangential / 100.0,
+        radial: radial / 100.0,
+        gyro: angles.rate(w),
+    }
+}
+
+/// Synthetic readings of the sensors a real pendulum would carry, an
+/// incremental encoder on the pivot and an IMU on the bob, written as CSV
+/// for testing estimation code against the simulation before there's
+/// hardware to run it on, in the `--angles` convention but for the unit:
+/// the gyro, like a real one, always reads rad/s.
+pub struct Sensors {
+    out: Box<dyn Write>,
+    // encoder counts per revolution
+    resolution: u32,
+    angles: Convention,
+}
+
+impl Sensors {
+    pub fn new(mut out: Box<dyn Write>, resolution: u32, angles: Convention) -> io::Result<Self> {
+        writeln!(
+            out,
+            "time_s,encoder_counts,accel_tangential_m_s2,accel_radial_m_s2,gyro_rad_s"
+        )?;
+        Ok(Sensors {
+            out,
+            resolution,
+            angles,
+        })
+    }
+
+    /// Writes what the sensors read at `time` s with the pendulum at θ, ω.
+    pub fn record(
+        &mut self,
+        params: &Params<f64>,
+        time: f64,
+        theta: f64,
+        w: f64,
+    ) -> io::Result<()> {
+        let reading = read(params, time, theta, w, self.resolution, self.angles);
+        writeln!(
+            self.out,
+            "{:.4},{},{:.4},{:.4},{:.4}",
+            time, reading.counts, reading.tangential, reading.radial, reading.gyro
+        )
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,233 @@
+use crate::convention::Convention;
+use crate::events::{Detector, SimEvent};
+use crate::integrator::{Integrator, Scheme, STEPS};
+use crate::interval::Interval;
+use crate::net::Frame;
+use crate::physics::{self, Conserve, Params, Pendulum, Precision, Sensitivity};
+use crate::plot::Plot;
+use crate::scalar::{Real, Scalar};
+use crate::sensors;
+
+// rad/s past which ω can only come from an integration gone wrong, the
+// bob of the longest rod doing 2 km/s
+const ABSURD_W: f64 = 1000.0;
+// rad the fastest term of the motion may turn through in a suggested step
+const MAX_PHASE: f64 = 0.05;
+
+/// Why the pendulum stopped: a step gave a state that makes no sense, and
+/// it was kept at the one before.
+#[derive(Clone, Copy, Debug)]
+pub struct Blowup {
+    // s, when the step started
+    pub time: f64,
+    // θ or ω, and what the step made of it
+    pub quantity: &'static str,
+    pub value: f64,
+    // the fastest term of the equation of motion, what the step was too long for
+    pub term: &'static str,
+    // s, a --dt that takes that term in small steps
+    pub dt: f64,
+}
+
+/// The pendulum plus the copies of it stepped in lockstep for the overlays.
+#[derive(Clone)]
+pub struct Simulation {
+    pub params: Params<f64>,
+    // what every copy is stepped with
+    pub scheme: Scheme,
+    // simulated seconds since the last reset
+    pub time: f64,
+    // pendulum angle and angular velocity -> θ0, w0
+    pub pendulum: Pendulum<Real>,
+    // ∂θ/∂L and ∂θ/∂g
+    pub sensitivity: Sensitivity,
+    // interval copy enclosing the accumulated rounding error
+    pub bounds: Pendulum<Interval>,
+    // f32 and f64 copies and how far apart they drift
+    pub precision: Precision,
+    pub divergence: Plot,
+    // angular momentum and torque of the main pendulum
+    pub momentum: Plot,
+    pub torque: Plot,
+    detector: Detector,
+    // once set, steps do nothing
+    pub blowup: Option<Blowup>,
+}
+
+impl Simulation {
+    pub fn new(params: Params<f64>, theta: f64, scheme: Scheme) -> Self {
+        Simulation {
+            params,
+            scheme,
+            time: 0.0,
+            pendulum: Pendulum::new(Real::from_f64(theta)),
+            sensitivity: Sensitivity::new(theta, 0.0),
+            bounds: Pendulum::new(Interval::point(theta)),
+            precision: Precision::new(theta, 0.0),
+            divergence: Plot::new(600),
+            momentum: Plot::new(600),
+            torque: Plot::new(600),
+            detector: Detector::new(theta),
+            blowup: None,
+        }
+    }
+
+    /// Advances everything by `dt` seconds, returning what happened meanwhile.
+    /// A step that blows up the pendulum is undone and stops the simulation.
+    pub fn step(&mut self, dt: f64) -> Vec<SimEvent> {
+        if self.blowup.is_some() {
+            return Vec::new();
+        }
+        let time = self.time;
+        let before = self.pendulum;
+        self.scheme.step(
+            &mut self.pendulum,
+            &self.params.cast(),
+            Real::from_f64(time),
+            Real::from_f64(dt),
+        );
+        if let Some((quantity, value)) = self.nonsense() {
+            self.pendulum = before;
+            self.blowup = Some(self.diagnose(quantity, value, dt));
+            return Vec::new();
+        }
+        self.time += dt;
+        self.sensitivity.step(&self.params, time, dt, self.scheme);
+        self.scheme.step(
+            &mut self.bounds,
+            &self.params.cast(),
+            Interval::point(time),
+            Interval::point(dt),
+        );
+        self.precision.step(&self.params, time, dt, self.scheme);
+        self.divergence.push(self.precision.divergence());
+        self.push_channels();
+        self.detector.update(self.theta())
+    }
+
+    /// Changes the angular velocity by `dw` rad/s at once, as a blow to the bob
+    /// would, in every copy alike.
+    pub fn kick(&mut self, dw: f64) {
+        self.pendulum.w += Real::from_f64(dw);
+        self.sensitivity.kick(dw);
+        self.bounds.w += Interval::point(dw);
+        self.precision.kick(dw);
+    }
+
+    /// Switches to `params`, adjusting the pendulum's motion as `conserve`
+    /// says. The lockstep copies restart from the adjusted state, what they
+    /// tracked so far belonged to the old parameters.
+    pub fn retune(&mut self, params: Params<f64>, conserve: Conserve) {
+        let (theta, w) = physics::retune(&self.params, &params, self.theta(), self.w(), conserve);
+        self.params = params;
+        self.pendulum = Pendulum {
+            theta: Real::from_f64(theta),
+            w: Real::from_f64(w),
+        };
+        self.sensitivity = Sensitivity::new(theta, w);
+        self.bounds = Pendulum {
+            theta: Interval::point(theta),
+            w: Interval::point(w),
+        };
+        self.precision = Precision::new(theta, w);
+    }
+
+    /// Takes the main pendulum's state from a host instead of stepping it. The
+    /// lockstep copies are left alone, they only make sense on the host.
+    pub fn follow(&mut self, frame: &Frame) -> Vec<SimEvent> {
+        self.time = frame.time;
+        self.params.length = frame.length;
+        self.pendulum.theta = Real::from_f64(frame.theta);
+        self.pendulum.w = Real::from_f64(frame.w);
+        self.push_channels();
+        self.detector.update(frame.theta)
+    }
+
+    // what of the main pendulum's state is infinite, NaN or absurd, if any
+    fn nonsense(&self) -> Option<(&'static str, f64)> {
+        let (theta, w) = (self.theta(), self.w());
+        if !theta.is_finite() {
+            Some(("θ", theta))
+        } else if !w.is_finite() || w.abs() > ABSURD_W {
+            Some(("ω", w))
+        } else {
+            None
+        }
+    }
+
+    // the fastest of the rates the terms of the motion go at, in rad/s, and a
+    // step short enough for it
+    fn diagnose(&self, quantity: &'static str, value: f64, dt: f64) -> Blowup {
+        let params = &self.params;
+        let drive = if params.drive > 0.0 {
+            params.drive_frequency
+        } else {
+            0.0
+        };
+        let (term, rate) = [
+            ("gravity", (params.gravity / params.length).sqrt()),
+            ("damping", params.damping),
+            ("air drag", params.drag * params.length * self.w().abs()),
+            ("wind", (params.wind.abs() / params.length).sqrt()),
+            ("drive", drive),
+            ("controller", params.torque.abs().sqrt()),
+            ("swing", self.w().abs()),
+        ]
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+        Blowup {
+            time: self.time,
+            quantity,
+            value,
+            term,
+            dt: (MAX_PHASE / rate).min(dt / 2.0).max(*STEPS.start()),
+        }
+    }
+
+    fn push_channels(&mut self) {
+        self.momentum
+            .push(physics::angular_momentum(&self.params, self.w()));
+        self.torque.push(physics::torque(
+            &self.params,
+            self.time,
+            self.theta(),
+            self.w(),
+        ));
+    }
+
+    /// The state to stream, with what sensors of an encoder of `resolution`
+    /// counts a revolution read in the `angles` convention, keeping up with
+    /// real time until the caller says otherwise.
+    pub fn frame(&self, resolution: u32, angles: Convention) -> Frame {
+        Frame {
+            time: self.time,
+            theta: self.theta(),
+            w: self.w(),
+            length: self.params.length,
+            reading: sensors::read(
+                &self.params,
+                self.time,
+                self.theta(),
+                self.w(),
+                resolution,
+                angles,
+            ),
+            speed: 1.0,
+            dropped: 0,
+        }
+    }
+
+    // rad, the highest the swing turned back since the last reset
+    pub fn amplitude_record(&self) -> f64 {
+        self.detector.record
+    }
+
+    pub fn theta(&self) -> f64 {
+        self.pendulum.theta.to_f64()
+    }
+
+    pub fn w(&self) -> f64 {
+        self.pendulum.w.to_f64()
+    }
+}
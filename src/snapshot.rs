@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use crate::integrator::Scheme;
+use crate::mode::Mode;
+use crate::physics::{
+    Params, World, DAMPINGS, DRAGS, DRIVES, DRIVE_FREQUENCIES, GRAVITIES, LENGTHS,
+};
+use crate::wind::{Gusting, GUSTS, GUST_PERIODS, WINDS};
+
+// bumped whenever the fields change meaning
+const VERSION: u32 = 1;
+
+/// What swings in the main window, its parameters and the single
+/// pendulum's state, copied as one line of JSON to paste into a chat or an
+/// issue, or as a `pendulum://` link to open the app with, and read back to
+/// carry on from there.
+///
+/// Of the other modes only which one is kept, and restored they start
+/// afresh. The wind is kept, its strength in `params`, but not where its
+/// random gusts had wandered to, and the thermal noise isn't kept either
+/// and carries on from wherever it is.
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    pub mode: Mode,
+    pub scheme: Scheme,
+    pub params: Params<f64>,
+    // the wind's gusts' fraction, kind and s
+    pub gust: f64,
+    pub gusting: Gusting,
+    pub gust_period: f64,
+    // s, rad and rad/s
+    pub time: f64,
+    pub theta: f64,
+    pub w: f64,
+}
+
+impl Snapshot {
+    pub fn to_json(self) -> String {
+        let params = &self.params;
+        format!(
+            "{{\"pendulum\":{},\"mode\":\"{}\",\"integrator\":\"{}\",\"length\":{},\"gravity\":{},\
+             \"damping\":{},\"drag\":{},\"wind\":{},\"gust\":{},\"gusting\":\"{}\",\"gust_period\":{},\
+             \"drive\":{},\"drive_frequency\":{},\"drive2\":{},\"drive_frequency2\":{},\"time\":{},\
+             \"theta\":{},\"w\":{}}}",
+            VERSION,
+            self.mode.name(),
+            self.scheme.name(),
+            params.length,
+            params.gravity,
+            params.damping,
+            params.drag,
+            params.wind,
+            self.gust,
+            self.gusting.name(),
+            self.gust_period,
+            params.drive,
+            params.drive_frequency,
+            params.drive2,
+            params.drive_frequency2,
+            self.time,
+            self.theta,
+            self.w
+        )
+    }
+
+    /// The same, less the time, as a link that starts the app from there:
+    /// `pendulum://open?mode=single&integrator=rk4&length=200&...`.
+    pub fn to_url(self) -> String {
+        let params = &self.params;
+        format!(
+            "pendulum://open?mode={}&integrator={}&length={}&gravity={}&damping={}&drag={}\
+             &wind={}&gust={}&gusting={}&gust_period={}&drive={}&drive_frequency={}&drive2={}\
+             &drive_frequency2={}&theta={}&w={}",
+            self.mode.name(),
+            self.scheme.name(),
+            params.length,
+            params.gravity,
+            params.damping,
+            params.drag,
+            params.wind,
+            self.gust,
+            self.gusting.name(),
+            self.gust_period,
+            params.drive,
+            params.drive_frequency,
+            params.drive2,
+            params.drive_frequency2,
+            self.theta,
+            self.w
+        )
+    }
+
+    /// Reads back what `to_json` or `to_url` wrote, with the parameters in
+    /// the ranges the keys keep them in. Fields left out are as in `fresh`,
+    /// and fields it doesn't know are ignored.
+    pub fn parse(source: &str, fresh: &Snapshot) -> Result<Self, String> {
+        let source = source.trim();
+        let fields = match source.strip_prefix("pendulum://") {
+            Some(link) => query(link).ok_or("not a pendulum:// link")?,
+            None => object(source).ok_or("not a JSON object or a pendulum:// link")?,
+        };
+        let number = |name: &str, range: &RangeInclusive<f64>, default: f64| {
+            let Some(raw) = fields.get(name) else {
+                return Ok(default);
+            };
+            match raw.parse::<f64>() {
+                Ok(value) if range.contains(&value) => Ok(value),
+                _ => Err(format!(
+                    "\"{}\" must be from {} to {}, got `{}`",
+                    name,
+                    range.start(),
+                    range.end(),
+                    raw
+                )),
+            }
+        };
+        let any = f64::MIN..=f64::MAX;
+        let version = VERSION as f64;
+        if number("pendulum", &any, version)? != version {
+            return Err(format!("not version {} of the format", VERSION));
+        }
+        let mode = match fields.get("mode") {
+            Some(name) => Mode::parse(name).ok_or(format!("unknown mode `{}`", name))?,
+            None => fresh.mode,
+        };
+        let scheme = match fields.get("integrator") {
+            Some(name) => Scheme::parse(name).ok_or(format!("unknown integrator `{}`", name))?,
+            None => fresh.scheme,
+        };
+        let gusting = match fields.get("gusting") {
+            Some(name) => Gusting::parse(name).ok_or(format!("unknown gusting `{}`", name))?,
+            None => fresh.gusting,
+        };
+        let defaults = &fresh.params;
+        // a world's gravity keeps too, zero-g's being below the keys' least
+        let gravity = number("gravity", &GRAVITIES, defaults.gravity).or_else(|error| {
+            fields
+                .get("gravity")
+                .and_then(|it| it.parse().ok())
+                .filter(|it| World::of(*it).is_some())
+                .ok_or(error)
+        })?;
+        Ok(Snapshot {
+            mode,
+            scheme,
+            params: Params {
+                length: number("length", &LENGTHS, defaults.length)?,
+                gravity,
+                damping: number("damping", &DAMPINGS, defaults.damping)?,
+                drag: number("drag", &DRAGS, defaults.drag)?,
+                wind: number("wind", &WINDS, defaults.wind)?,
+                drive: number("drive", &DRIVES, defaults.drive)?,
+                drive_frequency: number(
+                    "drive_frequency",
+                    &DRIVE_FREQUENCIES,
+                    defaults.drive_frequency,
+                )?,
+                drive2: number("drive2", &DRIVES, defaults.drive2)?,
+                drive_frequency2: number(
+                    "drive_frequency2",
+                    &DRIVE_FREQUENCIES,
+                    defaults.drive_frequency2,
+                )?,
+                // and pushed again by the external controller, if any
+                torque: 0.0,
+            },
+            gust: number("gust", &GUSTS, fresh.gust)?,
+            gusting,
+            gust_period: number("gust_period", &GUST_PERIODS, fresh.gust_period)?,
+            time: number("time", &(0.0..=f64::MAX), fresh.time)?,
+            theta: number("theta", &any, fresh.theta)?,
+            w: number("w", &any, fresh.w)?,
+        })
+    }
+}
+
+// the fields of a flat JSON object, or none if it isn't one
+fn object(source: &str) -> Option<HashMap<&str, String>> {
+    let inner = source.strip_prefix('{')?.strip_suffix('}')?.trim();
+    let mut fields = HashMap::new();
+    let mut rest = inner;
+    while !rest.is_empty() {
+        let (name, after) = rest.strip_prefix('"')?.split_once('"')?;
+        let after = after.trim_start().strip_prefix(':')?.trim_start();
+        // a string runs to its closing quote, anything else to the next comma
+        let end = match after.strip_prefix('"') {
+            Some(string) => string.find('"')? + 2,
+            None => after.find(',').unwrap_or(after.len()),
+        };
+        let value = after[..end].trim_end();
+        fields.insert(name, value.trim_matches('"').to_string());
+        rest = after[end..].trim_start();
+        rest = match rest.strip_prefix(',') {
+            Some(next) => next.trim_start(),
+            None if rest.is_empty() => rest,
+            None => return None,
+        };
+    }
+    Some(fields)
+}
+
+// the `name=value` pairs after the `?` of a link, the values' `%xx`
+// escapes decoded, or none if one isn't a pair
+fn query(link: &str) -> Option<HashMap<&str, String>> {
+    let query = link.split_once('?').map_or("", |(_, it)| it);
+    query
+        .split('&')
+        .filter(|it| !it.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            Some((name, decode(value)?))
+        })
+        .collect()
+}
+
+// `raw` with each `%xx` replaced by the byte it stands for, or none if one
+// is cut short or doesn't make UTF-8
+fn decode(raw: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut rest = raw.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = after
+                .get(..2)
+                .filter(|it| it.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &after[2..];
+        } else {
+            bytes.push(byte);
+            rest = after;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G, L, THETA_0};
+
+    fn fresh() -> Snapshot {
+        Snapshot {
+            mode: Mode::Single,
+            scheme: Scheme::RungeKutta4,
+            params: Params {
+                length: L,
+                gravity: G,
+                damping: 0.0,
+                drag: 0.0,
+                wind: 0.0,
+                drive: 0.0,
+                drive_frequency: 1.0,
+                drive2: 0.0,
+                drive_frequency2: 1.0,
+                torque: 0.0,
+            },
+            gust: 0.0,
+            gusting: Gusting::Sine,
+            gust_period: 5.0,
+            time: 0.0,
+            theta: THETA_0,
+            w: 0.0,
+        }
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            mode: Mode::Spring,
+            scheme: Scheme::Verlet,
+            params: Params {
+                length: 120.0,
+                gravity: 162.0,
+                damping: 0.25,
+                drag: 0.001,
+                wind: -150.0,
+                drive: 1.5,
+                drive_frequency: 2.0,
+                drive2: 0.75,
+                drive_frequency2: 3.25,
+                torque: 0.0,
+            },
+            gust: 0.4,
+            gusting: Gusting::Noise,
+            gust_period: 12.0,
+            time: 12.5,
+            theta: -0.75,
+            w: 2.25,
+        }
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let written = snapshot();
+        for (source, time) in [(written.to_json(), 12.5), (written.to_url(), 0.0)] {
+            let read = Snapshot::parse(&source, &fresh()).unwrap();
+            assert_eq!((read.mode, read.scheme), (written.mode, written.scheme));
+            assert_eq!(read.params.length, written.params.length);
+            assert_eq!(read.params.gravity, written.params.gravity);
+            assert_eq!(read.params.damping, written.params.damping);
+            assert_eq!(read.params.drag, written.params.drag);
+            assert_eq!(read.params.wind, written.params.wind);
+            assert_eq!(
+                (read.gust, read.gusting, read.gust_period),
+                (0.4, Gusting::Noise, 12.0)
+            );
+            assert_eq!(read.params.drive, written.params.drive);
+            assert_eq!(read.params.drive_frequency, written.params.drive_frequency);
+            assert_eq!(
+                (read.params.drive2, read.params.drive_frequency2),
+                (0.75, 3.25)
+            );
+            // a link starts over from 0 s
+            assert_eq!((read.time, read.theta, read.w), (time, -0.75, 2.25));
+        }
+    }
+
+    #[test]
+    fn keeps_the_parameters_in_bounds() {
+        for (source, error) in [
+            (
+                "pendulum://open?length=49",
+                "\"length\" must be from 50 to 200",
+            ),
+            ("pendulum://open?gravity=abc", "\"gravity\" must be from"),
+            (
+                "pendulum://open?gravity=2",
+                "\"gravity\" must be from 100 to 3000",
+            ),
+            ("{\"damping\":-1}", "\"damping\" must be from"),
+            ("{\"time\":-1}", "\"time\" must be from 0"),
+            ("{\"wind\":600}", "\"wind\" must be from -500 to 500"),
+            ("pendulum://open?gusting=gale", "unknown gusting `gale`"),
+            ("{\"pendulum\":2}", "not version 1"),
+            ("pendulum://open?mode=pogo", "unknown mode `pogo`"),
+            ("pendulum://open?length", "not a pendulum:// link"),
+            ("[1, 2]", "not a JSON object"),
+        ] {
+            let message = Snapshot::parse(source, &fresh()).err().unwrap();
+            assert!(
+                message.starts_with(error),
+                "{:?} gave {:?}",
+                source,
+                message
+            );
+        }
+        let floating = Snapshot::parse("pendulum://open?gravity=1", &fresh()).unwrap();
+        assert_eq!(floating.params.gravity, World::ZeroG.gravity());
+        let bare = Snapshot::parse("pendulum://open", &fresh()).unwrap();
+        assert_eq!(
+            (bare.mode, bare.params.length, bare.theta),
+            (Mode::Single, L, THETA_0)
+        );
+    }
+
+    #[test]
+    fn splits_a_query_into_its_pairs() {
+        let fields = query("open?mode=single&&theta=0.5").unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields["theta"], "0.5");
+        assert_eq!(query("open").unwrap().len(), 0);
+        assert!(query("open?theta").is_none());
+    }
+
+    #[test]
+    fn decodes_the_escapes_in_a_link() {
+        let fields = query("open?mode=%73ingle&theta=-0%2E5&about=a%20b%C3%A9").unwrap();
+        assert_eq!(fields["mode"], "single");
+        assert_eq!(fields["theta"], "-0.5");
+        assert_eq!(fields["about"], "a bé");
+        for link in [
+            "open?theta=0%2",
+            "open?theta=%zz",
+            "open?theta=%+f",
+            "open?theta=%ff",
+        ] {
+            assert!(query(link).is_none(), "{}", link);
+        }
+        let read = Snapshot::parse("pendulum://open?mode=%73pring&w=1%2E5", &fresh()).unwrap();
+        assert_eq!((read.mode, read.w), (Mode::Spring, 1.5));
+    }
+}
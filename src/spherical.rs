@@ -0,0 +1,227 @@
+use std::f64::consts::PI;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::mode::{Trail, SUBSTEPS};
+use crate::physics::{Params, SphericalPendulum};
+use crate::{CENTER, G};
+
+const PARAMS: Params<f64> = Params {
+    length: 150.0,
+    gravity: G,
+    damping: 0.0,
+    drag: 0.0,
+    wind: 0.0,
+    drive: 0.0,
+    drive_frequency: 0.0,
+    drive2: 0.0,
+    drive_frequency2: 0.0,
+    torque: 0.0,
+};
+// rad from the vertical, and the sideways speed as a fraction of the one
+// that would circle without swinging, for a fat ellipse
+const START_THETA: f64 = 0.6;
+const START_SIDEWAYS: f64 = 0.4;
+// frames of trace, long enough for the ellipse to be seen turning
+const TRAIL: usize = 900;
+// where the camera looks from: rad above the horizon and around the
+// vertical, and cm from the pivot
+const ELEVATION: f64 = 0.35;
+const AZIMUTH: f64 = 0.5;
+const DISTANCE: f64 = 700.0;
+
+/// The main window's spherical mode: the bob free to swing in any direction,
+/// seen in perspective from above. Started with a sideways push, it goes
+/// round an ellipse that slowly turns the way it goes, by 3π/8·a·b/L² rad a
+/// swing for semi-axes a and b much smaller than L.
+pub struct SphericalMode {
+    pub pendulum: SphericalPendulum,
+    pub time: f64,
+    // what the energy started at, the drift from which shows the integration error
+    energy: f64,
+    // cm from below the pivot at the last two steps, to catch the ends of
+    // the ellipse, and the azimuth of the last end
+    rho: [f64; 2],
+    apse: Option<f64>,
+    // rad the last end turned past the opposite of the one before
+    pub advance: Option<f64>,
+    trail: Trail,
+    // the bob's path as seen from straight above, on the floor
+    shadow: Trail,
+}
+
+impl SphericalMode {
+    pub fn new() -> Self {
+        let radius = PARAMS.length * START_THETA.sin();
+        let speed = START_SIDEWAYS * (PARAMS.gravity * radius * START_THETA.tan()).sqrt();
+        let pendulum = SphericalPendulum::new(&PARAMS, START_THETA, speed);
+        SphericalMode {
+            energy: energy(&pendulum),
+            pendulum,
+            time: 0.0,
+            rho: [radius; 2],
+            apse: None,
+            advance: None,
+            trail: Trail::new(TRAIL, Color::RED),
+            shadow: Trail::new(TRAIL, Color::RGB(120, 120, 120)),
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        values(&self.pendulum)
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        for _ in 0..SUBSTEPS {
+            self.pendulum.step(&PARAMS, dt / SUBSTEPS as f64);
+        }
+        self.time += dt;
+
+        let [x, y, _] = self.pendulum.position;
+        let rho = x.hypot(y);
+        if self.rho[1] > self.rho[0] && self.rho[1] > rho {
+            let azimuth = y.atan2(x);
+            if let Some(last) = self.apse {
+                // turning the way the bob goes round
+                let [vx, vy, _] = self.pendulum.velocity;
+                let sense = (x * vy - y * vx).signum();
+                self.advance = Some(sense * events::wrap(azimuth - last - PI));
+            }
+            self.apse = Some(azimuth);
+        }
+        self.rho = [self.rho[1], rho];
+    }
+
+    // rad/s added to the swing, away from the vertical
+    pub fn kick(&mut self, dw: f64) {
+        let [x, y, z] = self.pendulum.position;
+        let theta = (z / PARAMS.length).clamp(-1.0, 1.0).acos();
+        // straight below the pivot, along x
+        let phi = if x == 0.0 && y == 0.0 {
+            0.0
+        } else {
+            y.atan2(x)
+        };
+        let direction = [
+            theta.cos() * phi.cos(),
+            theta.cos() * phi.sin(),
+            -theta.sin(),
+        ];
+        for (v, d) in self.pendulum.velocity.iter_mut().zip(direction) {
+            *v += dw * PARAMS.length * d;
+        }
+        self.energy = energy(&self.pendulum);
+    }
+
+    // change of the energy since the start, over g·L, as the energy itself
+    // is 0 for a start level with the pivot
+    pub fn drift(&self) -> f64 {
+        (energy(&self.pendulum) - self.energy) / (PARAMS.gravity * PARAMS.length)
+    }
+
+    /// Draws the floor, the vertical through the pivot, the pendulum and
+    /// the traces of the bob and of its shadow.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let light = Color::RGB(200, 200, 200);
+        let floor: Vec<(i16, i16)> = (0..=48)
+            .map(|i| {
+                let angle = i as f64 / 48.0 * 2.0 * PI;
+                project([100.0 * angle.cos(), 100.0 * angle.sin(), PARAMS.length])
+            })
+            .collect();
+        for pair in floor.windows(2) {
+            canvas
+                .aa_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, light)
+                .expect("Unable to draw line");
+        }
+        let below = project([0.0, 0.0, PARAMS.length]);
+        canvas
+            .aa_line(
+                CENTER.0,
+                CENTER.1,
+                below.0,
+                below.1,
+                Color::RGBA(0, 0, 255, 100),
+            )
+            .expect("Unable to draw line");
+
+        let [x, y, z] = self.pendulum.position;
+        let bob = project([x, y, z]);
+        self.shadow.push(project([x, y, PARAMS.length]));
+        self.shadow.draw(canvas, detail);
+        self.trail.push(bob);
+        self.trail.draw(canvas, detail);
+        canvas
+            .aa_line(CENTER.0, CENTER.1, bob.0, bob.1, Color::BLACK)
+            .expect("Unable to draw line");
+        // nearer is bigger
+        let radius = (5.0 * scale([x, y, z])).round() as i16;
+        canvas
+            .aa_circle(bob.0, bob.1, radius, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [x, y, z] = self.pendulum.position;
+        vec![
+            format!(
+                "θ: {:.3} rad, φ: {:.0}°",
+                (z / PARAMS.length).clamp(-1.0, 1.0).acos(),
+                y.atan2(x).to_degrees()
+            ),
+            match self.advance {
+                Some(advance) => format!("ellipse turns {:.1}° a swing", advance.to_degrees()),
+                None => "ellipse turns …".to_string(),
+            },
+            format!("energy drift: {:.1e}", self.drift()),
+        ]
+    }
+}
+
+// per unit mass, kinetic plus potential from the pivot's height, in cm²/s²
+fn energy(pendulum: &SphericalPendulum) -> f64 {
+    let speed = pendulum.speed();
+    speed * speed / 2.0 - PARAMS.gravity * pendulum.position[2]
+}
+
+// a point in cm from the pivot, z down, turned to the camera: to the right,
+// down the screen and away from it
+fn view(point: [f64; 3]) -> [f64; 3] {
+    let [x, y, z] = point;
+    let (sin, cos) = AZIMUTH.sin_cos();
+    let (right, ahead) = (x * cos - y * sin, x * sin + y * cos);
+    let (sin, cos) = ELEVATION.sin_cos();
+    [right, z * cos - ahead * sin, ahead * cos + z * sin]
+}
+
+// how much nearer than the pivot makes things bigger
+pub fn scale(point: [f64; 3]) -> f64 {
+    DISTANCE / (DISTANCE + view(point)[2])
+}
+
+// where a point in cm from the pivot lands on the screen
+pub fn project(point: [f64; 3]) -> (i16, i16) {
+    let [right, down, _] = view(point);
+    let scale = scale(point);
+    (
+        CENTER.0 + (right * scale).round() as i16,
+        CENTER.1 + (down * scale).round() as i16,
+    )
+}
+
+// the bob's position and velocity, named, for the modes' `values`
+pub fn values(pendulum: &SphericalPendulum) -> Vec<(&'static str, f64)> {
+    let ([x, y, z], [vx, vy, vz]) = (pendulum.position, pendulum.velocity);
+    vec![
+        ("x", x),
+        ("y", y),
+        ("z", z),
+        ("vx", vx),
+        ("vy", vy),
+        ("vz", vz),
+    ]
+}
@@ -0,0 +1,128 @@
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::mode::{Trail, SUBSTEPS, TRAIL};
+use crate::physics::{SpringParams, SpringPendulum};
+use crate::CENTER;
+
+// rad and cm past the hanging length: a bounce, nearly straight down, which
+// with the default spring hands its energy back and forth with a swing
+const START_THETA: f64 = 0.1;
+const START_STRETCH: f64 = 30.0;
+// zigzags of the drawn spring, and how far they stick out, in px
+const COILS: usize = 12;
+const COIL_WIDTH: f64 = 7.0;
+// px of straight wire at either end of the spring
+const LEAD: f64 = 8.0;
+/// The spring's constant over the bob's mass k/m, in 1/s², and its natural
+/// length in cm: the bob hanging at L0 + g/(k/m) is at most about as far
+/// down as the longest rod, and the stiffest bounce still easily stepped.
+pub const STIFFNESSES: RangeInclusive<f64> = 20.0..=500.0;
+pub const REST_LENGTHS: RangeInclusive<f64> = 50.0..=150.0;
+
+/// The main window's spring mode: the rod swapped for a spring, so the bob
+/// bounces along it as well as swinging. When the bounce is twice as fast as
+/// the swing, as with the default spring, the two slowly trade their energy.
+pub struct SpringMode {
+    params: SpringParams,
+    pub pendulum: SpringPendulum,
+    pub time: f64,
+    // what the energy started at, the drift from which shows the integration error
+    energy: f64,
+    trail: Trail,
+}
+
+impl SpringMode {
+    pub fn new(params: SpringParams) -> Self {
+        let pendulum = SpringPendulum {
+            r: params.hanging_length() + START_STRETCH,
+            theta: START_THETA,
+            dr: 0.0,
+            w: 0.0,
+        };
+        SpringMode {
+            energy: pendulum.energy(&params),
+            params,
+            pendulum,
+            time: 0.0,
+            trail: Trail::new(TRAIL, Color::RED),
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let p = &self.pendulum;
+        vec![("r", p.r), ("θ", p.theta), ("r'", p.dr), ("ω", p.w)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        for _ in 0..SUBSTEPS {
+            self.pendulum.step(&self.params, dt / SUBSTEPS as f64);
+        }
+        self.time += dt;
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.pendulum.w += dw;
+        self.energy = self.pendulum.energy(&self.params);
+    }
+
+    // change of the energy since the start, over g·L of the bob hanging at
+    // rest, as the energy itself may be near 0
+    pub fn drift(&self) -> f64 {
+        let scale = self.params.gravity * self.params.hanging_length();
+        (self.pendulum.energy(&self.params) - self.energy) / scale
+    }
+
+    /// Draws the spring as a zigzag, the coils spreading out as it stretches,
+    /// the bob and its path.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let SpringPendulum { r, theta, .. } = self.pendulum;
+        // along the spring and across it
+        let (along, across) = ((theta.sin(), theta.cos()), (theta.cos(), -theta.sin()));
+        let point = |distance: f64, offset: f64| {
+            (
+                CENTER.0 + (along.0 * distance + across.0 * offset).round() as i16,
+                CENTER.1 + (along.1 * distance + across.1 * offset).round() as i16,
+            )
+        };
+        let mut points = vec![point(0.0, 0.0), point(LEAD.min(r / 2.0), 0.0)];
+        let coiled = (r - 2.0 * LEAD).max(0.0);
+        for i in 0..2 * COILS {
+            let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+            let distance = LEAD.min(r / 2.0) + coiled * (i as f64 + 0.5) / (2 * COILS) as f64;
+            points.push(point(distance, side * COIL_WIDTH));
+        }
+        points.push(point(r - LEAD.min(r / 2.0), 0.0));
+        let bob = point(r, 0.0);
+        points.push(bob);
+
+        self.trail.push(bob);
+        self.trail.draw(canvas, detail);
+        for pair in points.windows(2) {
+            canvas
+                .aa_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, Color::BLACK)
+                .expect("Unable to draw line");
+        }
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let p = &self.pendulum;
+        vec![
+            format!("θ: {:.3} rad, ω: {:.3} rad/s", events::wrap(p.theta), p.w),
+            format!(
+                "r: {:.1} cm, stretched {:+.1} cm",
+                p.r,
+                p.r - self.params.rest_length
+            ),
+            format!("energy drift: {:.1e}", self.drift()),
+        ]
+    }
+}
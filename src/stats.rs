@@ -0,0 +1,195 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::bundle;
+use crate::mode::Mode;
+
+// simulated seconds of an hour, for the achievement
+const HOUR: f64 = 3600.0;
+
+/// What can be achieved, each only once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    // the single pendulum went over the top
+    OverTheTop,
+    // swung under another world's gravity, from G
+    OffWorld,
+    // a chain of two rods or more swung chaotically for a minute
+    Butterfly,
+    // the cart-pole balanced for 10 s
+    SteadyHands,
+    // the swing pumped past the horizontal
+    Higher,
+    // every mode tried
+    GrandTour,
+    // an hour simulated, over every session
+    Devoted,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 7] = [
+        Achievement::OverTheTop,
+        Achievement::OffWorld,
+        Achievement::Butterfly,
+        Achievement::SteadyHands,
+        Achievement::Higher,
+        Achievement::GrandTour,
+        Achievement::Devoted,
+    ];
+
+    // what it's written to the file as
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::OverTheTop => "over-the-top",
+            Achievement::OffWorld => "off-world",
+            Achievement::Butterfly => "butterfly",
+            Achievement::SteadyHands => "steady-hands",
+            Achievement::Higher => "higher",
+            Achievement::GrandTour => "grand-tour",
+            Achievement::Devoted => "devoted",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::OverTheTop => "Over the top",
+            Achievement::OffWorld => "Off world",
+            Achievement::Butterfly => "Butterfly effect",
+            Achievement::SteadyHands => "Steady hands",
+            Achievement::Higher => "Higher!",
+            Achievement::GrandTour => "Grand tour",
+            Achievement::Devoted => "Devoted",
+        }
+    }
+}
+
+/// Usage statistics and achievements, kept across sessions in `stats.txt`
+/// in the user's data directory as lines of `<name> <value>`.
+pub struct Stats {
+    // none without a data directory, everything then lasting one session
+    path: Option<PathBuf>,
+    // s simulated in every mode, over every session
+    pub simulated: f64,
+    // s, the longest a chain of two rods or more swung without a reset
+    pub chaotic: f64,
+    // s, the longest the cart-pole was kept balanced
+    pub balanced: f64,
+    // the modes tried, and what was achieved, in order
+    visited: Vec<Mode>,
+    pub achieved: Vec<Achievement>,
+}
+
+impl Stats {
+    /// Reads them from the data directory, starting afresh if there are
+    /// none yet or they can't be read.
+    pub fn load() -> Self {
+        let path = bundle::data_dir().map(|it| it.join("stats.txt"));
+        let mut stats = Stats {
+            path,
+            simulated: 0.0,
+            chaotic: 0.0,
+            balanced: 0.0,
+            visited: Vec::new(),
+            achieved: Vec::new(),
+        };
+        let source = stats
+            .path
+            .as_ref()
+            .and_then(|it| fs::read_to_string(it).ok())
+            .unwrap_or_default();
+        for line in source.lines() {
+            let Some((name, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let number = value.trim().parse::<f64>().ok().filter(|it| it.is_finite());
+            match name {
+                "simulated" => stats.simulated = number.unwrap_or(0.0),
+                "chaotic" => stats.chaotic = number.unwrap_or(0.0),
+                "balanced" => stats.balanced = number.unwrap_or(0.0),
+                "visited" => {
+                    stats.visited = value.split_whitespace().filter_map(Mode::parse).collect()
+                }
+                "achieved" => {
+                    stats.achieved = value
+                        .split_whitespace()
+                        .filter_map(|key| Achievement::ALL.into_iter().find(|it| it.key() == key))
+                        .collect()
+                }
+                // from a newer version, or mangled
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// Writes them back to the data directory, if there is one.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let visited: Vec<&str> = self.visited.iter().map(|it| it.name()).collect();
+        let achieved: Vec<&str> = self.achieved.iter().map(|it| it.key()).collect();
+        fs::write(
+            path,
+            format!(
+                "simulated {:.3}\nchaotic {:.3}\nbalanced {:.3}\nvisited {}\nachieved {}\n",
+                self.simulated,
+                self.chaotic,
+                self.balanced,
+                visited.join(" "),
+                achieved.join(" ")
+            ),
+        )
+    }
+
+    /// Counts `dt` more simulated seconds, returning whether that made an hour.
+    pub fn simulate(&mut self, dt: f64) -> Option<Achievement> {
+        self.simulated += dt;
+        (self.simulated >= HOUR).then_some(Achievement::Devoted)
+    }
+
+    /// Notes `mode` as tried, returning whether that made all of them.
+    pub fn visit(&mut self, mode: Mode) -> Option<Achievement> {
+        if !self.visited.contains(&mode) {
+            self.visited.push(mode);
+        }
+        (self.visited.len() == Mode::ALL.len()).then_some(Achievement::GrandTour)
+    }
+
+    /// Records `achievement`, returning whether it's new, and then saves
+    /// it at once so a crash can't lose it.
+    pub fn achieve(&mut self, achievement: Achievement) -> bool {
+        if self.achieved.contains(&achievement) {
+            return false;
+        }
+        self.achieved.push(achievement);
+        if let Err(error) = self.save() {
+            eprintln!("Unable to save the statistics: {}", error);
+        }
+        true
+    }
+
+    /// The HUD lines of the statistics.
+    pub fn lines(&self) -> Vec<String> {
+        let names: Vec<&str> = self.achieved.iter().map(|it| it.name()).collect();
+        vec![
+            format!(
+                "simulated: {:.1} h, longest chaotic run: {:.0} s, best balance: {:.1} s",
+                self.simulated / HOUR,
+                self.chaotic,
+                self.balanced
+            ),
+            format!(
+                "achievements, {} of {}: {}",
+                self.achieved.len(),
+                Achievement::ALL.len(),
+                if names.is_empty() {
+                    "none yet".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ),
+        ]
+    }
+}
@@ -0,0 +1,250 @@
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::mode::{Trail, TRAIL};
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm of string
+const LENGTH: f64 = 150.0;
+// steps per step of `--dt`, short enough to catch the string going taut
+const SUBSTEPS: usize = 8;
+/// What the speed the bob is launched with from the bottom can be set to,
+/// as v²/(g·L): above 2 it rises past the horizontal, from 5 it loops.
+pub const LAUNCHES: RangeInclusive<f64> = 0.0..=6.0;
+
+/// The bob's motion, on the string or off it.
+#[derive(Clone, Copy, Debug)]
+enum Motion {
+    // θ from straight down and ω
+    Taut([f64; 2]),
+    // cm right of and below the pivot, and cm/s, since when
+    Slack { state: [f64; 4], since: f64 },
+}
+
+/// The main window's string mode: the bob on a string instead of a rod,
+/// launched from the bottom. The string can only pull, with g·cos θ + L·ω²
+/// over the bob's mass, so once that would have to be negative, as the
+/// bob slows above the horizontal, it goes slack and the bob flies off on
+/// a parabola until the string is taut again, the jerk taking away the
+/// speed along it.
+pub struct StringMode {
+    // v²/(g·L) at the launch
+    pub launch: f64,
+    motion: Motion,
+    pub time: f64,
+    // per unit mass at the launch, and lost to the jerks since, in cm²/s²
+    start: f64,
+    lost: f64,
+    trail: Trail,
+}
+
+impl StringMode {
+    pub fn new(launch: f64) -> Self {
+        let w = (launch * G / LENGTH).sqrt();
+        let mut string = StringMode {
+            launch,
+            motion: Motion::Taut([0.0, w]),
+            time: 0.0,
+            start: 0.0,
+            lost: 0.0,
+            trail: Trail::new(TRAIL, Color::RED),
+        };
+        string.start = string.energy();
+        string
+    }
+
+    // cm and cm/s, right of and below the pivot
+    fn position(&self) -> ([f64; 2], [f64; 2]) {
+        match self.motion {
+            Motion::Taut([theta, w]) => (
+                [LENGTH * theta.sin(), LENGTH * theta.cos()],
+                [LENGTH * w * theta.cos(), -LENGTH * w * theta.sin()],
+            ),
+            Motion::Slack {
+                state: [x, y, vx, vy],
+                ..
+            } => ([x, y], [vx, vy]),
+        }
+    }
+
+    // per unit mass, in cm²/s², nought with the bob at the pivot's height
+    fn energy(&self) -> f64 {
+        let ([_, y], [vx, vy]) = self.position();
+        0.5 * (vx * vx + vy * vy) - G * y
+    }
+
+    // over the bob's weight, what the string pulls with, none when slack
+    fn tension(&self) -> f64 {
+        match self.motion {
+            Motion::Taut(state) => pull(state) / G,
+            Motion::Slack { .. } => 0.0,
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        match self.motion {
+            Motion::Taut([theta, w]) => vec![("θ", theta), ("ω", w)],
+            Motion::Slack {
+                state: [x, y, vx, vy],
+                ..
+            } => vec![("x", x), ("y", y), ("vx", vx), ("vy", vy)],
+        }
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let h = dt / SUBSTEPS as f64;
+        for i in 0..SUBSTEPS {
+            let now = self.time + h * (i + 1) as f64;
+            self.motion = match self.motion {
+                Motion::Taut(state) => {
+                    let state =
+                        physics::rk4(state, h, |&[theta, w]| [w, -G / LENGTH * theta.sin()]);
+                    if pull(state) < 0.0 {
+                        let [theta, w] = state;
+                        Motion::Slack {
+                            state: [
+                                LENGTH * theta.sin(),
+                                LENGTH * theta.cos(),
+                                LENGTH * w * theta.cos(),
+                                -LENGTH * w * theta.sin(),
+                            ],
+                            since: now,
+                        }
+                    } else {
+                        Motion::Taut(state)
+                    }
+                }
+                // a parabola, exactly
+                Motion::Slack {
+                    state: [x, y, vx, vy],
+                    since,
+                } => {
+                    let (x, y, vy) = (x + vx * h, y + vy * h + 0.5 * G * h * h, vy + G * h);
+                    if x.hypot(y) >= LENGTH {
+                        self.jerk([x, y, vx, vy])
+                    } else {
+                        Motion::Slack {
+                            state: [x, y, vx, vy],
+                            since,
+                        }
+                    }
+                }
+            };
+        }
+        self.time += dt;
+
+        let ([x, y], _) = self.position();
+        self.trail
+            .push((CENTER.0 + x.round() as i16, CENTER.1 + y.round() as i16));
+    }
+
+    // the string taut again: the bob back on the circle, only its speed across it kept
+    fn jerk(&mut self, [x, y, vx, vy]: [f64; 4]) -> Motion {
+        let theta = x.atan2(y);
+        let across = vx * theta.cos() - vy * theta.sin();
+        let along = vx * theta.sin() + vy * theta.cos();
+        self.lost += 0.5 * along * along;
+        Motion::Taut([theta, across / LENGTH])
+    }
+
+    // rad/s added to the swing, or the same speed across the string when slack
+    pub fn kick(&mut self, dw: f64) {
+        match &mut self.motion {
+            Motion::Taut(state) => state[1] += dw,
+            Motion::Slack { state, .. } => {
+                let theta = state[0].atan2(state[1]);
+                state[2] += dw * LENGTH * theta.cos();
+                state[3] -= dw * LENGTH * theta.sin();
+            }
+        }
+    }
+
+    // launched `by` v²/(g·L) faster, afresh from the bottom
+    pub fn relaunch(&mut self, by: f64) {
+        *self = StringMode {
+            time: self.time,
+            ..StringMode::new((self.launch + by).clamp(*LAUNCHES.start(), *LAUNCHES.end()))
+        };
+    }
+
+    /// Draws the string, straight when taut and sagging when slack, and the
+    /// bob with its trail.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let ([x, y], _) = self.position();
+        let bob = (CENTER.0 + x.round() as i16, CENTER.1 + y.round() as i16);
+        self.trail.draw(canvas, detail);
+        match self.motion {
+            Motion::Taut(_) => {
+                canvas
+                    .aa_line(CENTER.0, CENTER.1, bob.0, bob.1, Color::BLACK)
+                    .expect("Unable to draw line");
+            }
+            Motion::Slack { .. } => {
+                // the slack hanging down between the pivot and the bob
+                let sag = (LENGTH - x.hypot(y)).max(0.0) / 2.0;
+                let points: Vec<(i16, i16)> = (0..=16)
+                    .map(|i| {
+                        let t = i as f64 / 16.0;
+                        (
+                            CENTER.0 + (x * t).round() as i16,
+                            CENTER.1 + (y * t + 4.0 * sag * t * (1.0 - t)).round() as i16,
+                        )
+                    })
+                    .collect();
+                for pair in points.windows(2) {
+                    canvas
+                        .aa_line(
+                            pair[0].0,
+                            pair[0].1,
+                            pair[1].0,
+                            pair[1].1,
+                            Color::RGB(150, 150, 150),
+                        )
+                        .expect("Unable to draw line");
+                }
+            }
+        }
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .aa_circle(bob.0, bob.1, 5, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let state = match self.motion {
+            Motion::Taut([theta, w]) => format!(
+                "taut, tension {:.3} mg, θ: {:.3} rad, ω: {:.3} rad/s",
+                self.tension(),
+                events::wrap(theta),
+                w
+            ),
+            Motion::Slack { since, .. } => format!(
+                "slack for {:.2} s, tension 0, falling freely",
+                self.time - since
+            ),
+        };
+        let scale = (G * LENGTH).max(f64::EPSILON);
+        vec![
+            format!("launched with v²/(g·L) = {:.2}", self.launch),
+            state,
+            format!(
+                "energy: {:+.3} g·L from the start, {:.3} g·L lost to jerks",
+                (self.energy() - self.start) / scale,
+                self.lost / scale
+            ),
+        ]
+    }
+}
+
+// cm/s², what the string would have to pull the bob with to keep it on the circle
+fn pull([theta, w]: [f64; 2]) -> f64 {
+    G * theta.cos() + LENGTH * w * w
+}
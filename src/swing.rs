@@ -0,0 +1,228 @@
+use std::ops::RangeInclusive;
+
+use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::events;
+use crate::input::{self, Action};
+use crate::mode::{Trail, TRAIL};
+use crate::physics;
+use crate::{CENTER, G};
+
+// cm from the pivot to the rider's centre of mass squatting, and to the seat
+const SQUATTING: f64 = 200.0;
+const SEAT: f64 = 215.0;
+// cm/s the rider stands up or squats at most, and s they take to settle
+const RATE: f64 = 100.0;
+const SETTLE: f64 = 0.05;
+// rad it is let go from
+const TILT: f64 = 0.15;
+// RK4 steps per step of `--dt`, short enough to follow the rider
+const SUBSTEPS: usize = 8;
+// cm Home and End change how far the rider stands up by
+const DEPTH_STEP: f64 = 5.0;
+/// How far the rider can stand up, raising their centre of mass, in cm.
+pub const PUMP_DEPTHS: RangeInclusive<f64> = 0.0..=50.0;
+
+/// The main window's swing mode: a rider on a swing pumping it, standing
+/// up to shorten the pendulum and squatting to lengthen it again, while
+/// the up and down arrows are held or, programmed, standing up at the
+/// bottom and squatting at either end as a child does.
+///
+/// The length L being the rider's centre of mass, changing it at L' adds
+/// the -2·L'·ω/L of the Coriolis force to θ'' = -g·sin θ/L: standing up
+/// while swinging fast at the bottom does more work against the pull of
+/// the rope than squatting gives back at the ends, where it pulls least,
+/// and the swing grows a little every half swing.
+pub struct SwingMode {
+    // cm the rider stands up by, and whether it is done for them
+    pub depth: f64,
+    pub auto: bool,
+    // θ, ω and L
+    state: [f64; 3],
+    // the up and down arrows held
+    held: (bool, bool),
+    pub time: f64,
+    // per unit mass in cm²/s² at the start, and how high the last turn was
+    start: f64,
+    pub turn: Option<f64>,
+    trail: Trail,
+}
+
+impl SwingMode {
+    pub fn new(depth: f64, auto: bool) -> Self {
+        let mut swing = SwingMode {
+            depth,
+            auto,
+            state: [TILT, 0.0, SQUATTING],
+            held: (false, false),
+            time: 0.0,
+            start: 0.0,
+            turn: None,
+            trail: Trail::new(TRAIL, Color::RED),
+        };
+        swing.start = swing.energy();
+        swing
+    }
+
+    /// Takes presses and releases of the up and down arrows, or of the
+    /// pad's, returning whether `event` was one, which then does nothing
+    /// else.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        match input::held(event) {
+            // the arrows the waiting pendulum's rod is changed with
+            Some((Action::Longer, down)) => self.held.0 = down,
+            Some((Action::Shorter, down)) => self.held.1 = down,
+            _ => return false,
+        }
+        true
+    }
+
+    // cm the rider's centre of mass is heading for
+    fn target(&self, [theta, w, length]: [f64; 3]) -> f64 {
+        let standing = SQUATTING - self.depth;
+        if self.auto {
+            // up on the way out from the bottom, down on the way back
+            if theta * w > 0.0 {
+                standing
+            } else {
+                SQUATTING
+            }
+        } else {
+            match self.held {
+                (true, false) => standing,
+                (false, true) => SQUATTING,
+                _ => length,
+            }
+        }
+    }
+
+    // cm/s the rider is standing up or squatting at, L'
+    fn rate(target: f64, length: f64) -> f64 {
+        ((target - length) / SETTLE).clamp(-RATE, RATE)
+    }
+
+    // per unit mass, in cm²/s², nought at rest squatting
+    fn energy(&self) -> f64 {
+        let [theta, w, length] = self.state;
+        let rate = SwingMode::rate(self.target(self.state), length);
+        0.5 * (length * length * w * w + rate * rate) + G * (SQUATTING - length * theta.cos())
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        let [theta, w, length] = self.state;
+        vec![("θ", theta), ("ω", w), ("L", length)]
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        let h = dt / SUBSTEPS as f64;
+        for _ in 0..SUBSTEPS {
+            let before = self.state[1];
+            // the rider moves when the step starts, as they would
+            let target = self.target(self.state);
+            self.state = physics::rk4(self.state, h, |&[theta, w, length]| {
+                let rate = SwingMode::rate(target, length);
+                [w, -G / length * theta.sin() - 2.0 * rate / length * w, rate]
+            });
+            if before * self.state[1] < 0.0 {
+                self.turn = Some(events::wrap(self.state[0]).abs());
+            }
+        }
+        self.time += dt;
+
+        let point = self.point(self.state[2]);
+        self.trail.push(point);
+    }
+
+    pub fn kick(&mut self, dw: f64) {
+        self.state[1] += dw;
+    }
+
+    /// Stands the rider up `by` times 5 cm further, and switches the
+    /// programmed pumping on or off, the swing carrying on.
+    pub fn pump(&mut self, by: f64, auto: Option<bool>) {
+        self.depth = (self.depth + by * DEPTH_STEP).clamp(*PUMP_DEPTHS.start(), *PUMP_DEPTHS.end());
+        self.auto = auto.unwrap_or(self.auto);
+    }
+
+    // px, `along` cm down the rope
+    fn point(&self, along: f64) -> (i16, i16) {
+        let theta = self.state[0];
+        (
+            CENTER.0 + (along * theta.sin()).round() as i16,
+            CENTER.1 + (along * theta.cos()).round() as i16,
+        )
+    }
+
+    /// Draws the rope and the seat, the rider on it standing up or
+    /// squatting, and the trail of their centre of mass.
+    pub fn draw(&mut self, canvas: &mut WindowCanvas, detail: f64) {
+        let [theta, _, length] = self.state;
+        self.trail.draw(canvas, detail);
+        let seat = self.point(SEAT);
+        canvas
+            .aa_line(CENTER.0, CENTER.1, seat.0, seat.1, Color::BLACK)
+            .expect("Unable to draw line");
+        let (dx, dy) = ((12.0 * theta.cos()) as i16, (-12.0 * theta.sin()) as i16);
+        canvas
+            .thick_line(
+                seat.0 - dx,
+                seat.1 - dy,
+                seat.0 + dx,
+                seat.1 + dy,
+                4,
+                Color::BLACK,
+            )
+            .expect("Unable to draw line");
+        // the body from the seat up, its middle at the centre of mass
+        let (middle, head) = (self.point(length), self.point(2.0 * length - SEAT));
+        canvas
+            .thick_line(seat.0, seat.1, head.0, head.1, 6, Color::RGB(200, 40, 40))
+            .expect("Unable to draw line");
+        canvas
+            .filled_circle(head.0, head.1, 7, Color::RGB(200, 40, 40))
+            .expect("Unable to draw circle");
+        canvas
+            .filled_circle(middle.0, middle.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+        canvas
+            .filled_circle(CENTER.0, CENTER.1, 3, Color::BLACK)
+            .expect("Unable to draw circle");
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let [theta, w, length] = self.state;
+        let rate = SwingMode::rate(self.target(self.state), length);
+        let scale = G * SQUATTING;
+        vec![
+            format!(
+                "{}, standing up {:.0} cm: L = {:.1} cm, L' = {:+.0} cm/s",
+                if self.auto {
+                    "pumping by itself"
+                } else {
+                    "pumped with the up and down arrows"
+                },
+                self.depth,
+                length,
+                rate
+            ),
+            format!(
+                "θ: {:.3} rad, ω: {:.3} rad/s, last turned at {}",
+                events::wrap(theta),
+                w,
+                match self.turn {
+                    Some(turn) => format!("{:.1}°", turn.to_degrees()),
+                    None => "…".to_string(),
+                }
+            ),
+            format!(
+                "energy: {:.3} g·L, {:+.3} g·L pumped in",
+                self.energy() / scale,
+                (self.energy() - self.start) / scale
+            ),
+        ]
+    }
+}
@@ -0,0 +1,86 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::rng::Rng;
+
+// how far ahead a reset is scheduled, so every instance receives it in time
+const LEAD: Duration = Duration::from_millis(250);
+const MAGIC: &str = "pendulum-reset";
+
+/// Resets several instances on a LAN at the same moment, over UDP multicast.
+///
+/// A reset is announced with a wall-clock time slightly in the future and every
+/// member, including the sender, resets when its own clock reaches it, so the
+/// machines should keep their clocks in sync (NTP). Being a plain UDP socket
+/// without SO_REUSEADDR, only one instance per machine can join a group.
+pub struct SyncGroup {
+    socket: UdpSocket,
+    group: SocketAddrV4,
+    // tells our own announcements apart when multicast loops them back
+    id: u64,
+    // milliseconds since the epoch of the next reset
+    pending: Option<u128>,
+}
+
+impl SyncGroup {
+    pub fn join(group: SocketAddrV4) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, group.port()))?;
+        socket.join_multicast_v4(group.ip(), &Ipv4Addr::UNSPECIFIED)?;
+        socket.set_nonblocking(true)?;
+        Ok(SyncGroup {
+            socket,
+            group,
+            id: Rng::from_time().next_u64(),
+            pending: None,
+        })
+    }
+
+    /// Schedules a reset for every member of the group, this one included.
+    pub fn request_reset(&mut self) -> io::Result<()> {
+        let at = now_ms() + LEAD.as_millis();
+        self.schedule(at);
+        let message = format!("{} {} {}", MAGIC, self.id, at);
+        self.socket.send_to(message.as_bytes(), self.group)?;
+        Ok(())
+    }
+
+    /// Reads the announcements received so far, returning true once a
+    /// scheduled reset is due.
+    pub fn poll(&mut self) -> bool {
+        let mut buffer = [0; 128];
+        while let Ok((size, _)) = self.socket.recv_from(&mut buffer) {
+            let message = String::from_utf8_lossy(&buffer[..size]);
+            let mut parts = message.split(' ');
+            if parts.next() != Some(MAGIC) {
+                continue;
+            }
+            let id = parts.next().and_then(|it| it.parse::<u64>().ok());
+            let at = parts.next().and_then(|it| it.parse::<u128>().ok());
+            if let (Some(id), Some(at)) = (id, at) {
+                if id != self.id {
+                    self.schedule(at);
+                }
+            }
+        }
+        match self.pending {
+            Some(at) if at <= now_ms() => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // when two members announce at once everyone keeps the latest, so they still agree
+    fn schedule(&mut self, at: u128) {
+        self.pending = Some(self.pending.map_or(at, |it| it.max(at)));
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,66 @@
+use std::ops::RangeInclusive;
+
+use crate::rng::Rng;
+
+/// What the noise's intensity D can be set to, in rad²/s³.
+pub const INTENSITIES: RangeInclusive<f64> = 0.0..=20.0;
+
+/// A white-noise torque on the single pendulum, making it a Langevin
+/// oscillator: every step of dt adds √(2·D·dt)·ξ to ω, ξ drawn from a
+/// standard normal. Against damping b/m the swing settles into thermal
+/// equilibrium, the mean of ω² coming to D/b whatever the amplitude, as the
+/// fluctuation-dissipation theorem has it; without damping it random-walks
+/// ever higher.
+pub struct Thermal {
+    // rad²/s³
+    pub intensity: f64,
+    // drawn from again on every reset, so each run is the same
+    seed: u64,
+    rng: Rng,
+    // ∫ω²·dt and the s it was taken over, since the last reset
+    sum: f64,
+    span: f64,
+}
+
+impl Thermal {
+    pub fn new(intensity: f64, seed: u64) -> Self {
+        Thermal {
+            intensity,
+            seed,
+            rng: Rng::new(seed),
+            sum: 0.0,
+            span: 0.0,
+        }
+    }
+
+    // back to the start of the same noise, as when the pendulum is reset
+    pub fn rewind(&mut self) {
+        *self = Thermal::new(self.intensity, self.seed);
+    }
+
+    /// The rad/s the noise adds to ω, swinging at `w`, over a step of `dt` s.
+    pub fn kick(&mut self, w: f64, dt: f64) -> f64 {
+        self.sum += w * w * dt;
+        self.span += dt;
+        (2.0 * self.intensity * dt).sqrt() * self.rng.gaussian()
+    }
+
+    /// The HUD line of the noise, against `damping` in 1/s.
+    pub fn line(&self, damping: f64) -> String {
+        let mean = if self.span > 0.0 {
+            format!("{:.3}", self.sum / self.span)
+        } else {
+            "…".to_string()
+        };
+        format!(
+            "thermal noise: D = {:.2} rad²/s³, mean ω²: {} rad²/s², {}",
+            self.intensity,
+            mean,
+            if damping > 0.0 {
+                format!("D/b: {:.3}", self.intensity / damping)
+            } else {
+                "heating up without damping".to_string()
+            }
+        )
+    }
+}
@@ -0,0 +1,155 @@
+use std::f64::consts::{PI, TAU};
+use std::fmt::Write;
+use std::fs;
+
+use chrono::Local;
+
+use crate::{CENTER, HEIGHT, WIDTH};
+
+// s, simulated, of each trace scored
+const TRACE: f64 = 30.0;
+// least score of a trace that is kept
+const KEPT: f64 = 25.0;
+// rad, a turn this sharp from one step to the next is a cusp, where the bob
+// stopped and went back, and not the path curling round
+const CUSP: f64 = 1.0;
+
+/// The chain mode's gallery of traces, for `--traces-out`: the path of the
+/// last bob cut into stretches of `TRACE` seconds, each scored by `score`
+/// and saved to the folder as SVG when it scores at least `KEPT`.
+pub struct Traces {
+    dir: String,
+    // cm, from the pivot to the end of the chain held straight
+    reach: f64,
+    // s, when the trace being drawn began
+    start: f64,
+    // cm, from the pivot, right and down
+    points: Vec<(f64, f64)>,
+}
+
+impl Traces {
+    pub fn new(dir: String, reach: f64) -> Self {
+        Traces {
+            dir,
+            reach,
+            start: 0.0,
+            points: Vec::new(),
+        }
+    }
+
+    /// Adds where the last bob is at `time`, and scores the trace once it
+    /// is long enough, starting the next.
+    pub fn push(&mut self, time: f64, point: (f64, f64)) {
+        self.points.push(point);
+        if time - self.start < TRACE {
+            return;
+        }
+        let score = score(&self.points, self.reach);
+        if score >= KEPT {
+            let path = format!(
+                "{}/trace-{}-{:.0}.svg",
+                self.dir,
+                Local::now().format("%Y%m%d-%H%M%S"),
+                score
+            );
+            match fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, svg(&self.points))) {
+                Ok(()) => println!("Saved {}, scoring {:.1}", path, score),
+                Err(error) => eprintln!("Unable to write {}: {}", path, error),
+            }
+        }
+        self.start = time;
+        self.points = vec![point];
+    }
+}
+
+/// How much a path has to look at: the geometric mean of how long it is, in
+/// lengths of the `reach` of the chain, and of how many full turns it curls
+/// through, leaving out its cusps. A small swing scores little by either
+/// measure, and a chaotic one a few times what it takes to be kept.
+pub fn score(points: &[(f64, f64)], reach: f64) -> f64 {
+    let steps: Vec<(f64, f64)> = points
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1))
+        .filter(|(dx, dy)| dx.hypot(*dy) > 1e-9)
+        .collect();
+    let length: f64 = steps.iter().map(|(dx, dy)| dx.hypot(*dy)).sum();
+    let turning: f64 = steps
+        .windows(2)
+        .map(|pair| {
+            let turn = pair[1].1.atan2(pair[1].0) - pair[0].1.atan2(pair[0].0);
+            (turn + PI).rem_euclid(TAU) - PI
+        })
+        .filter(|it| it.abs() < CUSP)
+        .map(f64::abs)
+        .sum();
+    (length / reach * turning / TAU).sqrt()
+}
+
+// the path as one polyline on the window's canvas, the pivot where it is drawn
+fn svg(points: &[(f64, f64)]) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <polyline fill=\"none\" stroke=\"black\" stroke-width=\"0.5\" points=\"",
+        WIDTH, HEIGHT
+    );
+    for (x, y) in points {
+        let _ = write!(
+            out,
+            "{:.1},{:.1} ",
+            CENTER.0 as f64 + x,
+            CENTER.1 as f64 + y
+        );
+    }
+    out.push_str("\"/>\n</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Chain, ChainParams};
+
+    // where the end of a double pendulum of two metre rods,
+    // let go at rest from `degrees`, is every step of 30 s
+    fn swing(degrees: f64) -> Vec<(f64, f64)> {
+        let params = ChainParams {
+            lengths: vec![100.0, 100.0],
+            masses: vec![1.0, 1.0],
+            gravity: 981.0,
+        };
+        let mut chain = Chain::new(&params, degrees.to_radians());
+        (0..3600)
+            .map(|_| {
+                for _ in 0..4 {
+                    chain.step(&params, 1.0 / 480.0);
+                }
+                chain
+                    .theta
+                    .iter()
+                    .zip(&params.lengths)
+                    .fold((0.0, 0.0), |(x, y), (theta, length)| {
+                        (x + theta.sin() * length, y + theta.cos() * length)
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scores_by_length_and_curling() {
+        // back and forth along a line turns only at its cusps
+        let line: Vec<(f64, f64)> = (0..400)
+            .map(|i| ((i as f64 / 20.0 * PI).sin() * 50.0, 0.0))
+            .collect();
+        assert!(score(&line, 100.0) < 1e-6);
+        // once round a circle of the reach is a turn along 2π reaches
+        let circle: Vec<(f64, f64)> = (0..=1000)
+            .map(|i| i as f64 / 1000.0 * TAU)
+            .map(|it| (100.0 * it.cos(), 100.0 * it.sin()))
+            .collect();
+        assert!((score(&circle, 100.0) - TAU.sqrt()).abs() < 1e-2);
+        // a small swing is passed over, a chaotic one kept
+        assert!(score(&swing(10.0), 200.0) < KEPT);
+        assert!(score(&swing(150.0), 200.0) >= KEPT);
+    }
+}
@@ -0,0 +1,195 @@
+use std::ops::RangeInclusive;
+
+use crate::control::{self, CART_DRAG, POLE};
+use crate::events;
+use crate::mode::SUBSTEPS;
+use crate::physics;
+use crate::G;
+
+// s the twin runs on its own before it is started over from the rig's
+// state; balanced upright the cart-pole is unstable, so any model left
+// longer drifts off whatever its fit
+const HORIZON: f64 = 1.0;
+// s of samples the refit remembers, older ones fading out
+const MEMORY: f64 = 10.0;
+// cm the refit pole can come to, and 1/s of drag the refit cart
+const POLES: RangeInclusive<f64> = 10.0..=1000.0;
+const DRAGS: RangeInclusive<f64> = 0.0..=20.0;
+// how much the remembered samples must have moved the cart, in cm²/s, and
+// pulled at its pole, in cm²/s³, before the refit goes by them
+const MOVED: f64 = 100.0;
+const PULLED: f64 = 10_000.0;
+
+/// The digital twin of a real cart-pole rig: a simulated cart-pole, pushed
+/// the same as the rig, which shows how far the model is from the real thing.
+/// Every `HORIZON` s it is started over from the rig's state, and the RMS
+/// of how far it was off over the run is the residual.
+///
+/// With `refit`, the pole's length and the cart's drag are fit online to
+/// the rig's samples by least squares, the cart's drag from
+/// a - x'' = drag·x' and the pole's length from
+/// L·θ'' = g·sin θ - x''·cos θ, the samples fading out over `MEMORY` s.
+pub struct Twin {
+    // cm of the model's pole, and 1/s of drag on its cart
+    pub pole: f64,
+    pub drag: f64,
+    pub refit: bool,
+    // cm and cm/s of the model's cart, θ of its pole from upright and ω
+    pub state: [f64; 4],
+    // s since it was started over from the rig's state
+    since: f64,
+    // rad² s and cm² s of θ and x off the rig's so far this run, and the
+    // RMS of the last whole run, in rad and cm
+    errors: (f64, f64),
+    pub residual: Option<(f64, f64)>,
+    // the rig's last sample, the s since, and the push over them
+    sample: Option<[f64; 4]>,
+    gap: f64,
+    impulse: f64,
+    // the fading sums of the least squares: of x'², x'·(a - x''), of
+    // (g·sin θ - x''·cos θ)² and of it times θ''
+    sums: [f64; 4],
+}
+
+impl Twin {
+    pub fn new(refit: bool) -> Self {
+        Twin {
+            pole: POLE,
+            drag: CART_DRAG,
+            refit,
+            state: [0.0; 4],
+            since: 0.0,
+            errors: (0.0, 0.0),
+            residual: None,
+            sample: None,
+            gap: 0.0,
+            impulse: 0.0,
+            sums: [0.0; 4],
+        }
+    }
+
+    /// Steps the model on by `dt` s under `thrust`, after the rig was
+    /// `observed` at, a new sample if `fresh`.
+    pub fn step(&mut self, observed: &[f64; 4], fresh: bool, thrust: f64, dt: f64) {
+        if fresh {
+            if self.sample.is_none() {
+                self.state = *observed;
+            }
+            if self.refit {
+                self.fit(observed);
+            }
+            self.sample = Some(*observed);
+            self.gap = 0.0;
+            self.impulse = 0.0;
+        }
+        let Some(sample) = self.sample else {
+            return;
+        };
+        let off = events::wrap(self.state[2] - sample[2]);
+        self.errors.0 += off * off * dt;
+        self.errors.1 += (self.state[0] - sample[0]).powi(2) * dt;
+        if self.since >= HORIZON {
+            self.residual = Some((
+                (self.errors.0 / self.since).sqrt(),
+                (self.errors.1 / self.since).sqrt(),
+            ));
+            self.state = sample;
+            self.since = 0.0;
+            self.errors = (0.0, 0.0);
+        }
+        let (pole, drag) = (self.pole, self.drag);
+        for _ in 0..SUBSTEPS {
+            self.state = physics::rk4(self.state, dt / SUBSTEPS as f64, |it| {
+                control::derivatives_of(it, thrust, pole, drag)
+            });
+        }
+        self.since += dt;
+        self.gap += dt;
+        self.impulse += thrust * dt;
+    }
+
+    // takes in how the rig moved from its last sample to `observed`
+    fn fit(&mut self, observed: &[f64; 4]) {
+        let Some([_, v, theta, w]) = self.sample else {
+            return;
+        };
+        let gap = self.gap;
+        if gap <= 0.0 {
+            return;
+        }
+        let push = self.impulse / gap;
+        let a = (observed[1] - v) / gap;
+        let dw = (observed[3] - w) / gap;
+        let pull = G * theta.sin() - a * theta.cos();
+        let fade = (-gap / MEMORY).exp();
+        for (sum, term) in self
+            .sums
+            .iter_mut()
+            .zip([v * v, v * (push - a), pull * pull, pull * dw])
+        {
+            *sum = *sum * fade + term * gap;
+        }
+        if self.sums[0] > MOVED {
+            self.drag = (self.sums[1] / self.sums[0]).clamp(*DRAGS.start(), *DRAGS.end());
+        }
+        if self.sums[2] > PULLED && self.sums[3] > 0.0 {
+            self.pole = (self.sums[2] / self.sums[3]).clamp(*POLES.start(), *POLES.end());
+        }
+    }
+
+    /// The HUD lines of the twin.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "twin: pole {:.1} cm, drag {:.2} /s{}",
+            self.pole,
+            self.drag,
+            if self.refit { ", refitting" } else { "" }
+        )];
+        lines.push(match self.residual {
+            Some((theta, x)) => {
+                format!("residual over {} s: {:.4} rad, {:.2} cm", HORIZON, theta, x)
+            }
+            None => "residual: waiting for the rig…".to_string(),
+        });
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a rig with a shorter pole and more drag than the model, shaken back
+    // and forth under its pole hanging down, sampled every 10 ms
+    fn rig(twin: &mut Twin, seconds: f64) {
+        let dt = 0.01;
+        let mut state = [0.0, 0.0, 3.0, 0.0];
+        for i in 0..(seconds / dt) as usize {
+            let thrust = 800.0 * (i as f64 * dt * 3.0).sin();
+            twin.step(&state, true, thrust, dt);
+            for _ in 0..SUBSTEPS {
+                state = physics::rk4(state, dt / SUBSTEPS as f64, |it| {
+                    control::derivatives_of(it, thrust, 120.0, 1.5)
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_its_model_without_a_refit() {
+        let mut twin = Twin::new(false);
+        rig(&mut twin, 20.0);
+        assert_eq!((twin.pole, twin.drag), (POLE, CART_DRAG));
+        assert!(twin.residual.is_some_and(|it| it.0 > 0.01));
+    }
+
+    #[test]
+    fn refits_to_the_rig() {
+        let mut twin = Twin::new(true);
+        rig(&mut twin, 20.0);
+        assert!((twin.pole - 120.0).abs() < 5.0, "{}", twin.pole);
+        assert!((twin.drag - 1.5).abs() < 0.2, "{}", twin.drag);
+        let (theta, _) = twin.residual.unwrap();
+        assert!(theta < 0.01, "{}", theta);
+    }
+}
@@ -0,0 +1,148 @@
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::mode::SUBSTEPS;
+use crate::physics;
+use crate::spherical;
+use crate::G;
+
+// cm the longest pendulum may hang down at most, to stay on screen
+const MAX_LENGTH: f64 = 190.0;
+// rad they are all let go from together
+const AMPLITUDE: f64 = 0.25;
+// cm along the bar between pivots, all of them spread over at most SPREAD
+const SPACING: f64 = 20.0;
+const SPREAD: f64 = 300.0;
+/// How many pendulums there can be, and the s they can take to come back in line.
+pub const WAVE_PENDULUMS: RangeInclusive<u64> = 2..=24;
+pub const WAVE_CYCLES: RangeInclusive<f64> = 20.0..=240.0;
+
+/// The main window's pendulum wave: a row of pendulums along a bar, each a
+/// little shorter than the one before, seen in perspective as in the
+/// spherical mode. In one cycle the longest swings M times, the next M + 1
+/// and so on, so let go together they drift apart into travelling waves,
+/// standing ones and seeming chaos, and line up again at the end of it.
+///
+/// A swing of A rad takes longer than a small one, by about 1 + A²/16 +
+/// 11·A⁴/3072, which the lengths make up for so they still line up.
+pub struct WaveMode {
+    pub pendulums: usize,
+    // s, until they are all back in line
+    pub cycle: f64,
+    // the swings of the longest a cycle, and the length of each
+    swings: u32,
+    lengths: Vec<f64>,
+    // θ and ω of each
+    states: Vec<[f64; 2]>,
+    pub time: f64,
+}
+
+impl WaveMode {
+    pub fn new(pendulums: usize, cycle: f64) -> Self {
+        let stretch = 1.0 + AMPLITUDE.powi(2) / 16.0 + 11.0 * AMPLITUDE.powi(4) / 3072.0;
+        let length = |swings: f64| G * (cycle / (2.0 * PI * swings * stretch)).powi(2);
+        // the fewest swings for which the longest fits
+        let mut swings = 1;
+        while length(swings as f64) > MAX_LENGTH {
+            swings += 1;
+        }
+        WaveMode {
+            pendulums,
+            cycle,
+            swings,
+            lengths: (0..pendulums)
+                .map(|i| length((swings as usize + i) as f64))
+                .collect(),
+            states: vec![[AMPLITUDE, 0.0]; pendulums],
+            time: 0.0,
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, f64)> {
+        self.states
+            .iter()
+            .flat_map(|[theta, w]| [("θ", *theta), ("ω", *w)])
+            .collect()
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        for (state, &length) in self.states.iter_mut().zip(&self.lengths) {
+            for _ in 0..SUBSTEPS {
+                *state = physics::rk4(*state, dt / SUBSTEPS as f64, |&[theta, w]| {
+                    [w, -G / length * theta.sin()]
+                });
+            }
+        }
+        self.time += dt;
+    }
+
+    // rad/s added to every swing alike
+    pub fn kick(&mut self, dw: f64) {
+        for state in &mut self.states {
+            state[1] += dw;
+        }
+    }
+
+    // `pendulums` more of them and a cycle `cycle` s longer, let go afresh,
+    // the clock of their cycle with them
+    pub fn retune(&mut self, pendulums: i64, cycle: f64) {
+        let count = (self.pendulums as i64 + pendulums)
+            .clamp(*WAVE_PENDULUMS.start() as i64, *WAVE_PENDULUMS.end() as i64);
+        let cycle = (self.cycle + cycle).clamp(*WAVE_CYCLES.start(), *WAVE_CYCLES.end());
+        *self = WaveMode::new(count as usize, cycle);
+    }
+
+    // cm along the bar of the `i`th pivot, the bar centred under the camera
+    fn pivot(&self, i: usize) -> f64 {
+        let spacing = SPACING.min(SPREAD / (self.pendulums - 1) as f64);
+        (i as f64 - (self.pendulums - 1) as f64 / 2.0) * spacing
+    }
+
+    /// Draws the bar and every pendulum hanging from it, nearer ones bigger.
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        let ends = (
+            spherical::project([self.pivot(0) - SPACING, 0.0, 0.0]),
+            spherical::project([self.pivot(self.pendulums - 1) + SPACING, 0.0, 0.0]),
+        );
+        canvas
+            .thick_line(ends.0 .0, ends.0 .1, ends.1 .0, ends.1 .1, 3, Color::BLACK)
+            .expect("Unable to draw line");
+        for (i, (&[theta, _], &length)) in self.states.iter().zip(&self.lengths).enumerate() {
+            let x = self.pivot(i);
+            let pivot = spherical::project([x, 0.0, 0.0]);
+            let point = [x, length * theta.sin(), length * theta.cos()];
+            let bob = spherical::project(point);
+            canvas
+                .aa_line(pivot.0, pivot.1, bob.0, bob.1, Color::RGB(150, 150, 150))
+                .expect("Unable to draw line");
+            let radius = (6.0 * spherical::scale(point)).round() as i16;
+            canvas
+                .filled_circle(bob.0, bob.1, radius, Color::RGB(200, 40, 40))
+                .expect("Unable to draw circle");
+        }
+    }
+
+    /// The HUD lines of the mode.
+    pub fn lines(&self) -> Vec<String> {
+        let last = self.swings as usize + self.pendulums - 1;
+        vec![
+            format!(
+                "{} pendulums, back in line every {:.0} s: {:.1} s in",
+                self.pendulums,
+                self.cycle,
+                self.time % self.cycle
+            ),
+            format!(
+                "longest {:.1} cm swinging {} times a cycle, shortest {:.1} cm {} times",
+                self.lengths[0],
+                self.swings,
+                self.lengths[self.pendulums - 1],
+                last
+            ),
+        ]
+    }
+}
@@ -0,0 +1,161 @@
+use std::f64::consts::TAU;
+use std::ops::RangeInclusive;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
+
+use crate::rng::Rng;
+use crate::{HEIGHT, WIDTH};
+
+// the gusts of every run are the same, like the schedule's noise
+const GUST_SEED: u64 = 0x6057;
+// px between the rows of streamlines and between the arrows along them
+const ROWS: i16 = 60;
+const SPACING: f64 = 90.0;
+// s of simulated time an arrow takes to go from one to the next, in the strongest wind
+const DRIFT: f64 = 0.5;
+/// The wind's push on the bob per unit mass, in cm/s², to the right
+/// positive: as strong as half of Earth's gravity either way.
+pub const WINDS: RangeInclusive<f64> = -500.0..=500.0;
+/// How much it gusts, as a fraction of its strength, and the s a gust lasts.
+pub const GUSTS: RangeInclusive<f64> = 0.0..=1.0;
+pub const GUST_PERIODS: RangeInclusive<f64> = 0.5..=60.0;
+
+/// How the wind gusts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gusting {
+    // rising and falling evenly, once a period
+    Sine,
+    // wandering at random, staying much the same over a period
+    Noise,
+}
+
+impl Gusting {
+    pub fn name(self) -> &'static str {
+        match self {
+            Gusting::Sine => "sine",
+            Gusting::Noise => "noise",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [Gusting::Sine, Gusting::Noise]
+            .into_iter()
+            .find(|it| it.name() == name)
+    }
+}
+
+/// A horizontal wind on the single pendulum's bob, pushing it along with
+/// a force that doesn't care how it moves, so the swing centres where
+/// tan θ = wind/g instead of straight down. Gusts vary it by up to `gust`
+/// of its strength, evenly or at random.
+pub struct Wind {
+    // cm/s², and the gusts' fraction and s
+    pub strength: f64,
+    pub gust: f64,
+    pub gusting: Gusting,
+    pub period: f64,
+    // cm/s², blowing now
+    pub now: f64,
+    // the random gusts, kept to about ±1
+    level: f64,
+    rng: Rng,
+    // px the streamlines have drifted along
+    drift: f64,
+}
+
+impl Wind {
+    pub fn new(strength: f64, gust: f64, gusting: Gusting, period: f64) -> Self {
+        Wind {
+            strength,
+            gust,
+            gusting,
+            period,
+            now: strength,
+            level: 0.0,
+            rng: Rng::new(GUST_SEED),
+            drift: 0.0,
+        }
+    }
+
+    // the same gusts again, as when the pendulum is reset
+    pub fn rewind(&mut self) {
+        *self = Wind::new(self.strength, self.gust, self.gusting, self.period);
+    }
+
+    /// Blows `by` cm/s² harder to the right.
+    pub fn blow(&mut self, by: f64) {
+        self.strength = (self.strength + by).clamp(*WINDS.start(), *WINDS.end());
+        self.now = self.strength;
+    }
+
+    /// The wind at `time` s, a step of `dt` s after the last.
+    pub fn step(&mut self, time: f64, dt: f64) -> f64 {
+        let gusts = match self.gusting {
+            Gusting::Sine => (TAU * time / self.period).sin(),
+            // Ornstein-Uhlenbeck, forgetting itself over a period
+            Gusting::Noise => {
+                self.level += -self.level * dt / self.period
+                    + (2.0 * dt / self.period).sqrt() * self.rng.gaussian();
+                self.level.clamp(-1.0, 1.0)
+            }
+        };
+        self.now = self.strength * (1.0 + self.gust * gusts);
+        self.drift =
+            (self.drift + self.now / WINDS.end() * SPACING / DRIFT * dt).rem_euclid(SPACING);
+        self.now
+    }
+
+    /// Draws rows of arrows across the window blowing the way of the wind,
+    /// longer and drifting faster the stronger it is.
+    pub fn draw(&self, canvas: &mut WindowCanvas) {
+        if self.now == 0.0 {
+            return;
+        }
+        let strength = self.now.abs() / WINDS.end();
+        let length = 10.0 + 30.0 * strength.min(1.0);
+        let direction = self.now.signum();
+        let color = Color::RGB(150, 190, 230);
+        for (row, y) in (ROWS / 2..HEIGHT as i16).step_by(ROWS as usize).enumerate() {
+            // every other row half a spacing on, so they don't line up in columns
+            let offset = self.drift + if row % 2 == 0 { 0.0 } else { SPACING / 2.0 };
+            let mut x = offset.rem_euclid(SPACING) - SPACING;
+            while x < WIDTH as f64 + SPACING {
+                let (tail, head) = (x as i16, (x + direction * length) as i16);
+                let barb = (-direction * 5.0) as i16;
+                canvas
+                    .aa_line(tail, y, head, y, color)
+                    .expect("Unable to draw line");
+                canvas
+                    .aa_line(head, y, head + barb, y - 3, color)
+                    .expect("Unable to draw line");
+                canvas
+                    .aa_line(head, y, head + barb, y + 3, color)
+                    .expect("Unable to draw line");
+                x += SPACING;
+            }
+        }
+    }
+
+    /// The HUD line of the wind, against `gravity` in cm/s².
+    pub fn line(&self, gravity: f64) -> String {
+        let gusts = if self.gust > 0.0 {
+            format!(
+                ", gusting {} by ±{:.0}% over {:.1} s",
+                self.gusting.name(),
+                self.gust * 100.0,
+                self.period
+            )
+        } else {
+            String::new()
+        };
+        format!(
+            "wind: {:+.2} m/s², now {:+.2}{}, leaning to {:+.1}°",
+            self.strength / 100.0,
+            self.now / 100.0,
+            gusts,
+            self.now.atan2(gravity).to_degrees()
+        )
+    }
+}